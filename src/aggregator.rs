@@ -0,0 +1,314 @@
+//! Cross-market price aggregation.
+//!
+//! An [`Aggregator`] queries every configured [`Marketplace`] concurrently for a set of items and
+//! reports, per item, the best ask, best bid, listing count and spread on each market and across
+//! all of them. A market that fails is reported next to the results of the others instead of
+//! failing the whole query.
+
+use futures::stream::{self, StreamExt};
+use tracing::debug;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::fx::RateTable;
+use crate::identity::ItemIdentity;
+use crate::listing::NormalizedListing;
+use crate::marketplace::{Market, MarketBid, Marketplace, MarketplaceError, Operation};
+use crate::money::{Currency, Money};
+
+/// Number of offers requested from each market per item.
+const DEFAULT_SAMPLE_SIZE: u32 = 50;
+/// Number of market queries in flight at once.
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Prices of one item on one market.
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketQuote {
+    pub market: Market,
+    pub best_ask: Option<NormalizedListing>,
+    pub best_bid: Option<MarketBid>,
+    /// Number of offers on the market: the count the market reports when it summarizes its
+    /// offers (Buff), otherwise the offers seen, capped by the aggregator's sample size.
+    pub listing_count: usize,
+    /// Best ask minus best bid, in the market's currency.
+    pub spread: Option<Money>,
+}
+
+/// A market operation that failed while aggregating an item.
+#[derive(Debug)]
+pub struct MarketFailure {
+    pub market: Market,
+    pub operation: Operation,
+    pub error: Error,
+}
+
+/// The best price across all markets, converted to the aggregator's currency.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BestPrice {
+    pub market: Market,
+    pub price: Money,
+}
+
+/// Prices of one item across all markets, in the aggregator's currency.
+#[derive(Debug, Clone, Serialize)]
+pub struct OverallQuote {
+    pub best_ask: Option<BestPrice>,
+    pub best_bid: Option<BestPrice>,
+    pub listing_count: usize,
+    pub spread: Option<Money>,
+}
+
+/// Aggregated prices of one item.
+#[derive(Debug)]
+pub struct ItemAggregate {
+    pub identity: ItemIdentity,
+    /// One quote per market that answered at least one query.
+    pub quotes: Vec<MarketQuote>,
+    pub overall: OverallQuote,
+    pub failures: Vec<MarketFailure>,
+}
+
+impl ItemAggregate {
+    /// Whether any market failed for this item.
+    pub fn is_partial(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    pub fn quote(&self, market: Market) -> Option<&MarketQuote> {
+        self.quotes.iter().find(|quote| quote.market == market)
+    }
+}
+
+/// Queries several markets for the prices of a set of items.
+pub struct Aggregator {
+    markets: Vec<Arc<dyn Marketplace>>,
+    currency: Currency,
+    rates: Option<RateTable>,
+    sample_size: u32,
+    concurrency: usize,
+}
+
+impl Aggregator {
+    /// Creates an aggregator over `markets`, comparing prices in USD.
+    pub fn new(markets: Vec<Arc<dyn Marketplace>>) -> Self {
+        Self {
+            markets,
+            currency: Currency::USD,
+            rates: None,
+            sample_size: DEFAULT_SAMPLE_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Compares prices in `currency`, converting quotes with `rates`.
+    ///
+    /// Without rates, quotes in other currencies are left out of the overall view.
+    pub fn with_currency(mut self, currency: Currency, rates: Option<RateTable>) -> Self {
+        self.currency = currency;
+        self.rates = rates;
+        self
+    }
+
+    /// Sets how many offers are requested from each market per item.
+    pub fn with_sample_size(mut self, sample_size: u32) -> Self {
+        self.sample_size = sample_size.max(1);
+        self
+    }
+
+    /// Sets how many market queries run at once.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn markets(&self) -> impl Iterator<Item = Market> + '_ {
+        self.markets.iter().map(|market| market.market())
+    }
+
+    /// Aggregates the prices of `items`, in the order given.
+    pub async fn aggregate(&self, items: &[ItemIdentity]) -> Vec<ItemAggregate> {
+        // The queries are built up front so the returned future stays `Send` when spawned.
+        let queries = items
+            .iter()
+            .enumerate()
+            .flat_map(|(index, identity)| {
+                self.markets.iter().map(move |market| async move {
+                    (index, self.query_market(market.as_ref(), identity).await)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = stream::iter(queries)
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        results.sort_by_key(|(index, (quote, _))| (*index, quote.as_ref().map(|quote| quote.market)));
+
+        let mut aggregates = items
+            .iter()
+            .map(|identity| ItemAggregate {
+                identity: identity.clone(),
+                quotes: Vec::new(),
+                overall: OverallQuote {
+                    best_ask: None,
+                    best_bid: None,
+                    listing_count: 0,
+                    spread: None,
+                },
+                failures: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for (index, (quote, failures)) in results {
+            let aggregate = &mut aggregates[index];
+            aggregate.quotes.extend(quote);
+            aggregate.failures.extend(failures);
+        }
+        for aggregate in &mut aggregates {
+            aggregate.overall = self.overall(&aggregate.quotes);
+        }
+        aggregates
+    }
+
+    /// Aggregates a single item.
+    pub async fn aggregate_one(&self, identity: &ItemIdentity) -> ItemAggregate {
+        self.aggregate(std::slice::from_ref(identity))
+            .await
+            .pop()
+            .expect("one aggregate per item")
+    }
+
+    async fn query_market(
+        &self,
+        market: &dyn Marketplace,
+        identity: &ItemIdentity,
+    ) -> (Option<MarketQuote>, Vec<MarketFailure>) {
+        let name = identity.market_hash_name();
+        let capabilities = market.capabilities();
+        let mut failures = Vec::new();
+        let mut fail = |operation, error: MarketplaceError| {
+            failures.push(MarketFailure {
+                market: market.market(),
+                operation,
+                error: error.into(),
+            })
+        };
+
+        let asks = if capabilities.list_offers {
+            market
+                .list_offers(&name, self.sample_size)
+                .await
+                .map(|offers| offers.into_iter().filter(|offer| matches_phase(offer, identity)).collect::<Vec<_>>())
+                .map_err(|error| (Operation::ListOffers, error))
+        } else if capabilities.lowest_ask {
+            market
+                .lowest_ask(&name)
+                .await
+                .map(|offer| offer.into_iter().collect())
+                .map_err(|error| (Operation::LowestAsk, error))
+        } else {
+            Ok(Vec::new())
+        };
+        let asks = match asks {
+            Ok(asks) => Some(asks),
+            Err((operation, error)) => {
+                fail(operation, error);
+                None
+            }
+        };
+
+        let bid = if capabilities.highest_bid {
+            match market.highest_bid(&name).await {
+                Ok(bid) => Some(bid),
+                Err(error) => {
+                    fail(Operation::HighestBid, error);
+                    None
+                }
+            }
+        } else {
+            Some(None)
+        };
+
+        if asks.is_none() && bid.is_none() {
+            return (None, failures);
+        }
+
+        let listing_count = asks.iter().flatten().map(NormalizedListing::offer_count).sum();
+        let best_ask = asks
+            .unwrap_or_default()
+            .into_iter()
+            .min_by_key(|offer| offer.price.minor_units());
+        let best_bid = bid.flatten();
+        let spread = match (&best_ask, &best_bid) {
+            (Some(ask), Some(bid)) => ask.price.checked_sub(bid.price).ok(),
+            _ => None,
+        };
+        debug!(
+            "{} on {}: {} offers, best ask {:?}",
+            name,
+            market.market(),
+            listing_count,
+            best_ask.as_ref().map(|ask| ask.price.to_string())
+        );
+
+        let quote = MarketQuote {
+            market: market.market(),
+            best_ask,
+            best_bid,
+            listing_count,
+            spread,
+        };
+        (Some(quote), failures)
+    }
+
+    fn overall(&self, quotes: &[MarketQuote]) -> OverallQuote {
+        let asks = quotes.iter().filter_map(|quote| {
+            let ask = quote.best_ask.as_ref()?;
+            Some(BestPrice {
+                market: quote.market,
+                price: self.convert(ask.price)?,
+            })
+        });
+        let bids = quotes.iter().filter_map(|quote| {
+            let bid = quote.best_bid.as_ref()?;
+            Some(BestPrice {
+                market: quote.market,
+                price: self.convert(bid.price)?,
+            })
+        });
+
+        let best_ask = asks.min_by_key(|ask| ask.price.minor_units());
+        let best_bid = bids.max_by_key(|bid| bid.price.minor_units());
+        let spread = match (best_ask, best_bid) {
+            (Some(ask), Some(bid)) => ask.price.checked_sub(bid.price).ok(),
+            _ => None,
+        };
+
+        OverallQuote {
+            best_ask,
+            best_bid,
+            listing_count: quotes.iter().map(|quote| quote.listing_count).sum(),
+            spread,
+        }
+    }
+
+    /// Converts `amount` into the aggregator's currency, if possible.
+    fn convert(&self, amount: Money) -> Option<Money> {
+        if amount.currency() == self.currency {
+            return Some(amount);
+        }
+        amount.convert_to(self.currency, self.rates.as_ref()?).ok()
+    }
+}
+
+/// Whether `offer` can be the item: offers that report a Doppler phase must match the wanted one.
+fn matches_phase(offer: &NormalizedListing, identity: &ItemIdentity) -> bool {
+    let Some(wanted) = identity.phase else {
+        return true;
+    };
+    match offer.identity().ok().and_then(|offer| offer.phase) {
+        Some(phase) => phase == wanted,
+        None => true,
+    }
+}
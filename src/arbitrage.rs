@@ -0,0 +1,299 @@
+//! Arbitrage detection over aggregated prices.
+//!
+//! The [`ArbitrageScanner`] pairs the best ask on one market with the exit price on another and
+//! keeps the pairs whose net proceeds, after each market's fees, exceed the purchase cost.
+
+use tracing::debug;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::cmp::Ordering;
+
+use crate::aggregator::{ItemAggregate, MarketQuote};
+use crate::fees::FeeTable;
+use crate::fx::RateTable;
+use crate::identity::ItemIdentity;
+use crate::listing::{NormalizedListing, Tradability};
+use crate::marketplace::Market;
+use crate::money::{Currency, Money, MoneyError};
+
+/// How an item bought for arbitrage is sold again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Exit {
+    /// Sold instantly into the highest buy order.
+    Bid,
+    /// Listed at the current lowest ask.
+    Listing,
+}
+
+/// Order in which opportunities are ranked, best first.
+///
+/// Every ranking puts offers that can be resold sooner first: tradable offers, then offers of
+/// unknown tradability, then locked offers by days remaining, and last those locked for an
+/// unknown time. The ranking orders opportunities within each of these groups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Ranking {
+    #[default]
+    NetProfit,
+    Roi,
+    Liquidity,
+}
+
+/// Buying an item on one market and selling it on another.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArbitrageOpportunity {
+    pub identity: ItemIdentity,
+    pub buy_market: Market,
+    pub sell_market: Market,
+    /// The offer to buy.
+    pub listing: NormalizedListing,
+    pub exit: Exit,
+    /// Purchase price including buyer fees, in the scanner's currency.
+    pub cost: Money,
+    /// Gross sale price, in the selling market's currency.
+    pub sale_price: Money,
+    /// Sale price after seller and withdrawal fees.
+    pub net_proceeds: Money,
+    pub net_profit: Money,
+    /// Net profit relative to the cost.
+    pub roi: Decimal,
+    /// Number of offers on the selling market, as reported in [`MarketQuote::listing_count`].
+    pub liquidity: usize,
+    pub tradability: Tradability,
+}
+
+impl ArbitrageOpportunity {
+    /// Days before the bought item can be traded again, if it is locked for a known time.
+    pub fn trade_lock_days(&self) -> Option<u32> {
+        match self.tradability {
+            Tradability::Locked { days_remaining } => days_remaining,
+            _ => None,
+        }
+    }
+}
+
+/// Finds and ranks arbitrage opportunities in aggregated prices.
+pub struct ArbitrageScanner {
+    fees: FeeTable,
+    currency: Currency,
+    rates: Option<RateTable>,
+    min_profit: Option<Money>,
+    min_roi: Decimal,
+    max_trade_lock_days: Option<u32>,
+    keep_unknown_tradability: bool,
+    exit_via_listing: bool,
+    ranking: Ranking,
+}
+
+impl ArbitrageScanner {
+    /// Creates a scanner comparing prices in USD, with default fees.
+    pub fn new() -> Self {
+        Self {
+            fees: FeeTable::default(),
+            currency: Currency::USD,
+            rates: None,
+            min_profit: None,
+            min_roi: Decimal::ZERO,
+            max_trade_lock_days: None,
+            keep_unknown_tradability: true,
+            exit_via_listing: true,
+            ranking: Ranking::default(),
+        }
+    }
+
+    pub fn with_fees(mut self, fees: FeeTable) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Compares prices in `currency`, converting other currencies with `rates`.
+    pub fn with_currency(mut self, currency: Currency, rates: Option<RateTable>) -> Self {
+        self.currency = currency;
+        self.rates = rates;
+        self
+    }
+
+    /// Drops opportunities earning less than `min_profit`, which must be in the scanner's
+    /// currency: set it with [`ArbitrageScanner::with_currency`] first.
+    pub fn with_min_profit(mut self, min_profit: Money) -> Result<Self, MoneyError> {
+        if min_profit.currency() != self.currency {
+            return Err(MoneyError::CurrencyMismatch(min_profit.currency(), self.currency));
+        }
+        self.min_profit = Some(min_profit);
+        Ok(self)
+    }
+
+    /// Drops opportunities with a lower return on investment, e.g. `0.05` for 5%.
+    pub fn with_min_roi(mut self, min_roi: Decimal) -> Self {
+        self.min_roi = min_roi;
+        self
+    }
+
+    /// Drops offers trade locked for more than `days`, or for an unknown time. Without it, locked
+    /// offers are kept and ranked after tradable ones.
+    ///
+    /// Offers whose market does not report tradability at all, like every Buff listing, are
+    /// not locked offers: [`ArbitrageScanner::with_unknown_tradability`] decides on those.
+    pub fn with_max_trade_lock_days(mut self, days: u32) -> Self {
+        self.max_trade_lock_days = Some(days);
+        self
+    }
+
+    /// Whether offers of unknown tradability are kept when a maximum trade lock is set. They are
+    /// kept by default, and ranked after tradable offers.
+    pub fn with_unknown_tradability(mut self, keep: bool) -> Self {
+        self.keep_unknown_tradability = keep;
+        self
+    }
+
+    /// Whether selling by listing at the lowest ask counts as an exit, besides selling into bids.
+    pub fn with_listing_exit(mut self, enabled: bool) -> Self {
+        self.exit_via_listing = enabled;
+        self
+    }
+
+    pub fn with_ranking(mut self, ranking: Ranking) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Returns the profitable opportunities in `aggregates`, best first.
+    pub fn scan(&self, aggregates: &[ItemAggregate]) -> Vec<ArbitrageOpportunity> {
+        let mut opportunities = aggregates
+            .iter()
+            .flat_map(|aggregate| self.scan_item(aggregate))
+            .collect::<Vec<_>>();
+        opportunities.sort_by(|a, b| self.compare(a, b));
+        opportunities
+    }
+
+    fn scan_item(&self, aggregate: &ItemAggregate) -> Vec<ArbitrageOpportunity> {
+        let mut opportunities = Vec::new();
+        for buy in &aggregate.quotes {
+            let Some(listing) = &buy.best_ask else {
+                continue;
+            };
+            if !self.lock_allowed(listing.tradability) {
+                continue;
+            }
+            for sell in aggregate.quotes.iter().filter(|sell| sell.market != buy.market) {
+                for (exit, sale_price) in self.exits(sell) {
+                    if let Some(opportunity) = self.evaluate(aggregate, buy, listing, sell, exit, sale_price) {
+                        opportunities.push(opportunity);
+                    }
+                }
+            }
+        }
+        opportunities
+    }
+
+    fn exits(&self, sell: &MarketQuote) -> Vec<(Exit, Money)> {
+        let mut exits = Vec::new();
+        if let Some(bid) = &sell.best_bid {
+            exits.push((Exit::Bid, bid.price));
+        }
+        if self.exit_via_listing {
+            if let Some(ask) = &sell.best_ask {
+                exits.push((Exit::Listing, ask.price));
+            }
+        }
+        exits
+    }
+
+    fn evaluate(
+        &self,
+        aggregate: &ItemAggregate,
+        buy: &MarketQuote,
+        listing: &NormalizedListing,
+        sell: &MarketQuote,
+        exit: Exit,
+        sale_price: Money,
+    ) -> Option<ArbitrageOpportunity> {
+        let kind = aggregate.identity.kind;
+        let cost = self.fees.model(buy.market).purchase_cost(listing.price, kind).ok()?;
+        let net_proceeds = self.fees.model(sell.market).net_proceeds(sale_price, kind).ok()?;
+
+        let cost = self.convert(cost)?;
+        let net_proceeds = self.convert(net_proceeds)?;
+        let net_profit = net_proceeds.checked_sub(cost).ok()?;
+        if net_profit.is_negative() || net_profit.is_zero() {
+            return None;
+        }
+
+        let roi = net_profit.ratio_to(cost).ok()?;
+        if roi < self.min_roi {
+            return None;
+        }
+        if let Some(min_profit) = self.min_profit {
+            // A minimum in another currency keeps nothing rather than everything.
+            if net_profit.checked_sub(min_profit).ok()?.is_negative() {
+                return None;
+            }
+        }
+
+        debug!(
+            "{}: buy on {} for {}, sell on {} for {} net",
+            aggregate.identity, buy.market, cost, sell.market, net_proceeds
+        );
+        Some(ArbitrageOpportunity {
+            identity: aggregate.identity.clone(),
+            buy_market: buy.market,
+            sell_market: sell.market,
+            listing: listing.clone(),
+            exit,
+            cost,
+            sale_price,
+            net_proceeds,
+            net_profit,
+            roi,
+            liquidity: sell.listing_count,
+            tradability: listing.tradability,
+        })
+    }
+
+    fn lock_allowed(&self, tradability: Tradability) -> bool {
+        let Some(max_days) = self.max_trade_lock_days else {
+            return true;
+        };
+        match tradability {
+            Tradability::Tradable => true,
+            Tradability::Locked { days_remaining: Some(days) } => days <= max_days,
+            Tradability::Locked { days_remaining: None } => false,
+            Tradability::Unknown => self.keep_unknown_tradability,
+        }
+    }
+
+    fn compare(&self, a: &ArbitrageOpportunity, b: &ArbitrageOpportunity) -> Ordering {
+        let profit = b.net_profit.minor_units().cmp(&a.net_profit.minor_units());
+        let roi = b.roi.cmp(&a.roi);
+        let liquidity = b.liquidity.cmp(&a.liquidity);
+        let lock = lock_rank(a.tradability).cmp(&lock_rank(b.tradability));
+        lock.then(match self.ranking {
+            Ranking::NetProfit => profit.then(roi).then(liquidity),
+            Ranking::Roi => roi.then(profit).then(liquidity),
+            Ranking::Liquidity => liquidity.then(profit).then(roi),
+        })
+    }
+
+    fn convert(&self, amount: Money) -> Option<Money> {
+        if amount.currency() == self.currency {
+            return Some(amount);
+        }
+        amount.convert_to(self.currency, self.rates.as_ref()?).ok()
+    }
+}
+
+/// Sort key putting offers that can be resold sooner first.
+fn lock_rank(tradability: Tradability) -> (u8, u32) {
+    match tradability {
+        Tradability::Tradable => (0, 0),
+        Tradability::Unknown => (1, 0),
+        Tradability::Locked { days_remaining: Some(days) } => (2, days),
+        Tradability::Locked { days_remaining: None } => (3, 0),
+    }
+}
+
+impl Default for ArbitrageScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
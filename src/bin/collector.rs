@@ -0,0 +1,78 @@
+use log::{error, info, warn};
+use std::env;
+use std::sync::Arc;
+
+use backend::catalog::ItemCatalog;
+use backend::collector::{shutdown_signal, Collector, CollectorConfig, CollectorError, LogSink, Sink, StorageSink};
+use backend::marketplace::Marketplace;
+use backend::storage::Storage;
+use backend::{BuffMarketClient, CSFloatClient, DMarketClient};
+
+const DEFAULT_CONFIG_PATH: &str = "collector.json";
+
+#[tokio::main]
+async fn main() {
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    let config_path = env::args().nth(1).unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string());
+    if let Err(e) = run(&config_path).await {
+        error!("Collector failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(config_path: &str) -> Result<(), CollectorError> {
+    info!("Loading collector config from {}", config_path);
+    let config = CollectorConfig::load(config_path)?;
+
+    let catalog = match &config.catalog {
+        Some(path) => Arc::new(ItemCatalog::load_or_default(path)?),
+        None => Arc::new(ItemCatalog::new()),
+    };
+
+    let sink: Arc<dyn Sink> = match &config.database {
+        Some(path) => Arc::new(StorageSink::new(Arc::new(Storage::open(path)?))),
+        None => Arc::new(LogSink),
+    };
+
+    let markets = connect_markets(&catalog);
+    if markets.is_empty() {
+        warn!("No market credentials configured, jobs will collect nothing");
+    }
+
+    Collector::new(&config, markets, sink).run(shutdown_signal()).await;
+
+    if let Some(path) = &config.catalog {
+        info!("Saving {} catalog entries to {}", catalog.len(), path.display());
+        catalog.save(path)?;
+    }
+    Ok(())
+}
+
+/// Creates a client for every market whose credentials are set in the environment.
+fn connect_markets(catalog: &Arc<ItemCatalog>) -> Vec<Arc<dyn Marketplace>> {
+    let mut markets: Vec<Arc<dyn Marketplace>> = Vec::new();
+
+    match DMarketClient::new() {
+        Ok(client) => markets.push(Arc::new(client.with_catalog(Arc::clone(catalog)))),
+        Err(e) => warn!("Skipping DMarket: {}", e),
+    }
+
+    match CSFloatClient::new() {
+        Ok(client) => markets.push(Arc::new(client.with_catalog(Arc::clone(catalog)))),
+        Err(e) => warn!("Skipping CSFloat: {}", e),
+    }
+
+    match BuffMarketClient::builder()
+        .credentials_from_env()
+        .and_then(|builder| builder.build())
+    {
+        Ok(client) => markets.push(Arc::new(client.with_catalog(Arc::clone(catalog)))),
+        Err(e) => warn!("Skipping BuffMarket: {}", e),
+    }
+
+    markets
+}
@@ -43,9 +43,9 @@ async fn main() {
                             None => "N/A".to_string(),
                         };
                         
-                        info!("Listing: {} - ${:.2} - Float: {}", 
+                        info!("Listing: {} - {} - Float: {}", 
                              listing.item.market_hash_name, 
-                             listing.price as f64 / 100.0, 
+                             listing.price, 
                              float_display);
                     }
                 }
@@ -1,43 +1,80 @@
-use reqwest::header::{HeaderMap, COOKIE, USER_AGENT, HeaderValue};
-use reqwest::Client;
-use std::time::{SystemTime, UNIX_EPOCH};
+use reqwest::header::{HeaderMap, COOKIE, HeaderValue};
+use reqwest::{Client, Method, Response, StatusCode};
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::endpoints;
 use super::error::BuffMarketError;
 use super::models::{GoodsBuyOrderResponse, MarketGoodsResponse, MarketGoodsItem};
+use crate::catalog::ItemCatalog;
+use crate::http::{request_span, Cassette, EndpointGroup, HttpSettings, Idempotency, MarketApiError, RateLimiter, RetryPolicy, Secret, Transport};
+use crate::marketplace::Market;
 
 const DEFAULT_USER_AGENT: &str =
     "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36";
 
 const X_CSRFTOKEN_HEADER: &str = "x-csrftoken";
 
+/// The session cookie and CSRF token are left out of the client's `Debug` output.
 #[derive(Debug, Clone)]
 pub struct BuffMarketClient {
     client: Client,
-    session_cookie: String,
-    csrf_token: String,
+    market_base_url: String,
+    buy_orders_base_url: String,
+    session_cookie: Secret,
+    csrf_token: Secret,
+    catalog: Option<Arc<ItemCatalog>>,
+    transport: Transport,
 }
 
 impl BuffMarketClient {
-    pub fn new(session_cookie: String, csrf_token: String) -> Self {
-        let mut headers = HeaderMap::new();
-        headers.insert(USER_AGENT, DEFAULT_USER_AGENT.parse().unwrap());
-        // It's important to set the cookie for requests to buff.market
-        // The specific cookie needed is typically obtained by logging into buff.163.com
-        // and copying it from the browser's developer tools (Network tab -> Request Headers -> cookie).
-        // Example format: "Device-Id=...; Locale-Supported=...; game=...; NTES_YD_SESS=...; S_INFO=...; P_INFO=...; remember_me=...; session=...; csrf_token=..."
-
-        let client = Client::builder()
-            .default_headers(headers)
-            // .cookie_store(true) // Consider if reqwest's cookie store is beneficial here
+    pub fn new(session_cookie: impl Into<Secret>, csrf_token: impl Into<Secret>) -> Self {
+        Self::builder()
+            .credentials(session_cookie, csrf_token)
             .build()
-            .unwrap(); // Consider error handling for client creation
+            .expect("default HTTP client settings are valid")
+    }
 
-        BuffMarketClient {
-            client,
-            session_cookie,
-            csrf_token,
-        }
+    /// Starts configuring a client, e.g. to point it at other hosts or to set timeouts.
+    pub fn builder() -> BuffMarketClientBuilder {
+        BuffMarketClientBuilder::default()
+    }
+
+    /// Records the goods this client sees in `catalog`, and uses it to resolve item names.
+    pub fn with_catalog(mut self, catalog: Arc<ItemCatalog>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    pub fn catalog(&self) -> Option<&Arc<ItemCatalog>> {
+        self.catalog.as_ref()
+    }
+
+    /// Retries failed requests according to `retry`, so that a transient failure does not abort
+    /// a whole crawl.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.transport.set_retry_policy(retry);
+        self
+    }
+
+    /// Limits requests with `limiter` instead of the market's default quotas. Pass a clone of
+    /// one limiter to several clients to make them share a quota.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.transport.set_rate_limiter(limiter);
+        self
+    }
+
+    /// The limiter of this client, including how long requests waited on it.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        self.transport.rate_limiter()
+    }
+
+    /// Records this client's traffic to `cassette`, or answers requests from it when it is
+    /// replaying.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.transport.set_cassette(cassette);
+        self
     }
 
     fn get_timestamp_ms() -> u128 {
@@ -56,8 +93,9 @@ impl BuffMarketClient {
         // The timestamp parameter `_` seems important.
         let timestamp = Self::get_timestamp_ms();
         let url = format!(
-            "{}?game={}&goods_id={}&page_num={}&_={}",
-            endpoints::BUFF_BUY_ORDERS_API_URL, // We'll define this in endpoints/mod.rs
+            "{}{}?game={}&goods_id={}&page_num={}&_={}",
+            self.buy_orders_base_url,
+            endpoints::BUY_ORDERS_PATH,
             game,
             goods_id,
             page_num,
@@ -65,45 +103,57 @@ impl BuffMarketClient {
         );
 
         let mut headers = HeaderMap::new();
-        headers.insert(COOKIE, self.session_cookie.parse().map_err(|_| BuffMarketError::InvalidInput("Invalid session cookie format".to_string()))?);
+        headers.insert(COOKIE, self.session_cookie.expose().parse().map_err(|_| BuffMarketError::InvalidInput("Invalid session cookie format".to_string()))?);
 
 
+        let span = request_span(Market::BuffMarket, &Method::GET, &url);
         let response = self
-            .client
-            .get(&url)
-            .headers(headers) // Send the cookie with this specific request
-            .send()
+            .transport
+            .send(span, EndpointGroup::MarketData, Idempotency::Idempotent, || {
+                Ok::<_, BuffMarketError>(self.client.get(&url).headers(headers.clone())) // Send the cookie with this specific request
+            })
             .await?;
 
-        if response.status().is_success() {
-            let body = response.text().await?;
-            // For debugging:
-            // println!("Raw JSON response: {}", body);
-            let parsed_response: GoodsBuyOrderResponse = serde_json::from_str(&body)
-                .map_err(BuffMarketError::JsonParse)?;
-
-            if parsed_response.code != "OK" { // "OK" is a common success code, but verify for Buff
-                 // Log the raw body if parsing succeeds but API indicates error
-                // eprintln!("API Error. Raw response: {}", body);
-                return Err(BuffMarketError::ApiError {
-                    message: parsed_response.msg.unwrap_or_else(|| "Unknown API error".to_string()),
-                });
-            }
-            Ok(parsed_response)
-        } else {
-            // If the status is not a success, `error_for_status()` will consume the response
-            // and return an `Err(reqwest::Error)`.
-            // We then explicitly convert this `reqwest::Error` into our `BuffMarketError::HttpRequest`.
-            match response.error_for_status() {
-                Ok(_) => {
-                    // This case should ideally not be reached if status().is_success() was false.
-                    // If it somehow is, it means error_for_status found a success status code (e.g. after a redirect)
-                    // that we didn't initially catch. This is unlikely.
-                    Err(BuffMarketError::Unknown)
-                }
-                Err(err) => Err(BuffMarketError::HttpRequest(err)),
+        let status = response.status();
+        let body = read_body(response).await?;
+        let parsed_response: GoodsBuyOrderResponse = serde_json::from_str(&body)
+            .map_err(BuffMarketError::JsonParse)?;
+        check_code(status, &parsed_response.code, parsed_response.msg.as_deref())?;
+
+        if let (Some(catalog), Some(data)) = (&self.catalog, &parsed_response.data) {
+            for asset in data.items.iter().filter_map(|item| item.asset_info.as_ref()) {
+                catalog.record_buff(&asset.market_hash_name, asset.goods_id);
             }
         }
+        Ok(parsed_response)
+    }
+
+    /// Retrieves the buy orders of an item by its `market_hash_name`.
+    ///
+    /// The `goods_id` is taken from the catalog when known, and otherwise looked up with a market
+    /// search.
+    pub async fn get_buy_orders_by_name(
+        &self,
+        game: &str,
+        market_hash_name: &str,
+        page_num: i32,
+    ) -> Result<GoodsBuyOrderResponse, BuffMarketError> {
+        let goods_id = match self.catalog.as_ref().and_then(|catalog| catalog.buff_goods_id(market_hash_name)) {
+            Some(goods_id) => goods_id,
+            None => self.lookup_goods_id(game, market_hash_name).await?,
+        };
+        self.get_buy_orders(game, goods_id, page_num).await
+    }
+
+    async fn lookup_goods_id(&self, game: &str, market_hash_name: &str) -> Result<i64, BuffMarketError> {
+        let response = self.search_market_listings(game, market_hash_name, 1, 20).await?;
+        response
+            .data
+            .into_iter()
+            .flat_map(|data| data.items)
+            .find(|item| item.market_hash_name == market_hash_name)
+            .map(|item| item.goods_internal_id)
+            .ok_or_else(|| BuffMarketError::MissingData(format!("No goods found for {}", market_hash_name)))
     }
 
     pub async fn get_market_listings(
@@ -112,46 +162,65 @@ impl BuffMarketClient {
         page_num: i32,
         page_size: i32,
     ) -> Result<MarketGoodsResponse, BuffMarketError> {
-        let url = format!(
-            "{}?game={}&page_num={}&page_size={}",
-            endpoints::BUFF_MARKET_GOODS_API_URL,
+        self.fetch_market_goods(game, None, page_num, page_size).await
+    }
+
+    /// Searches the market goods of `game` whose name matches `search`.
+    pub async fn search_market_listings(
+        &self,
+        game: &str,
+        search: &str,
+        page_num: i32,
+        page_size: i32,
+    ) -> Result<MarketGoodsResponse, BuffMarketError> {
+        self.fetch_market_goods(game, Some(search), page_num, page_size).await
+    }
+
+    async fn fetch_market_goods(
+        &self,
+        game: &str,
+        search: Option<&str>,
+        page_num: i32,
+        page_size: i32,
+    ) -> Result<MarketGoodsResponse, BuffMarketError> {
+        let mut url = format!(
+            "{}{}?game={}&page_num={}&page_size={}",
+            self.market_base_url,
+            endpoints::MARKET_GOODS_PATH,
             game,
             page_num,
             page_size
         );
+        if let Some(search) = search {
+            url = format!("{}&search={}", url, urlencoding::encode(search));
+        }
 
         let mut headers = HeaderMap::new();
-        headers.insert(COOKIE, self.session_cookie.parse().map_err(|_| BuffMarketError::InvalidInput("Invalid session cookie format".to_string()))?);
-        headers.insert(X_CSRFTOKEN_HEADER, self.csrf_token.parse().map_err(|_| BuffMarketError::InvalidInput("Invalid CSRF token format".to_string()))?);
+        headers.insert(COOKIE, self.session_cookie.expose().parse().map_err(|_| BuffMarketError::InvalidInput("Invalid session cookie format".to_string()))?);
+        headers.insert(X_CSRFTOKEN_HEADER, self.csrf_token.expose().parse().map_err(|_| BuffMarketError::InvalidInput("Invalid CSRF token format".to_string()))?);
         headers.insert(reqwest::header::ORIGIN, HeaderValue::from_static("https://buff.market"));
         headers.insert(reqwest::header::REFERER, HeaderValue::from_static("https://buff.market/"));
 
+        let span = request_span(Market::BuffMarket, &Method::GET, &url);
         let response = self
-            .client
-            .get(&url)
-            .headers(headers)
-            .send()
+            .transport
+            .send(span, EndpointGroup::MarketData, Idempotency::Idempotent, || {
+                Ok::<_, BuffMarketError>(self.client.get(&url).headers(headers.clone()))
+            })
             .await?;
 
-        if response.status().is_success() {
-            let body = response.text().await?;
-            let parsed_response: MarketGoodsResponse = serde_json::from_str(&body)
-                .map_err(|e| {
-                    BuffMarketError::JsonParse(e)
-                })?;
-
-            if parsed_response.code != "OK" {
-                return Err(BuffMarketError::ApiError {
-                    message: parsed_response.msg.unwrap_or_else(|| "Unknown API error".to_string()),
-                });
-            }
-            Ok(parsed_response)
-        } else {
-            match response.error_for_status() {
-                Ok(_) => Err(BuffMarketError::Unknown),
-                Err(err) => Err(BuffMarketError::HttpRequest(err)),
+        let status = response.status();
+        let body = read_body(response).await?;
+        let parsed_response: MarketGoodsResponse = serde_json::from_str(&body)
+            .map_err(BuffMarketError::JsonParse)?;
+        check_code(status, &parsed_response.code, parsed_response.msg.as_deref())?;
+
+        if let (Some(catalog), Some(data)) = (&self.catalog, &parsed_response.data) {
+            for item in &data.items {
+                catalog.record_buff(&item.market_hash_name, item.goods_internal_id);
             }
         }
+        Ok(parsed_response)
     }
 
     pub async fn get_all_market_listings(
@@ -208,13 +277,135 @@ impl BuffMarketClient {
             if current_page > total_pages && total_pages !=0 {
                  break;
             }
-
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            // Pages are paced by the client's rate limiter.
         }
         Ok(all_items)
     }
 }
 
+/// Returns the body of a successful response, and the parsed error body otherwise.
+async fn read_body(response: Response) -> Result<String, BuffMarketError> {
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(BuffMarketError::ApiError(MarketApiError::from_body(Market::BuffMarket, status, &body)));
+    }
+    Ok(body)
+}
+
+/// Buff reports most errors with a success status and a `code` other than `"OK"`, such as
+/// `"Login Required"`.
+fn check_code(status: StatusCode, code: &str, msg: Option<&str>) -> Result<(), BuffMarketError> {
+    if code == "OK" {
+        return Ok(());
+    }
+    Err(BuffMarketError::ApiError(MarketApiError::new(
+        Market::BuffMarket,
+        status,
+        Some(code.to_string()),
+        msg.unwrap_or("Unknown API error"),
+    )))
+}
+
+/// Configures a [`BuffMarketClient`].
+#[derive(Debug, Default)]
+pub struct BuffMarketClientBuilder {
+    session_cookie: Option<Secret>,
+    csrf_token: Option<Secret>,
+    market_base_url: Option<String>,
+    buy_orders_base_url: Option<String>,
+    http: HttpSettings,
+}
+
+impl BuffMarketClientBuilder {
+    /// Sets the session cookie and CSRF token of a logged-in browser session.
+    pub fn credentials(mut self, session_cookie: impl Into<Secret>, csrf_token: impl Into<Secret>) -> Self {
+        // It's important to set the cookie for requests to buff.market
+        // The specific cookie needed is typically obtained by logging into buff.163.com
+        // and copying it from the browser's developer tools (Network tab -> Request Headers -> cookie).
+        // Example format: "Device-Id=...; Locale-Supported=...; game=...; NTES_YD_SESS=...; S_INFO=...; P_INFO=...; remember_me=...; session=...; csrf_token=..."
+        self.session_cookie = Some(session_cookie.into());
+        self.csrf_token = Some(csrf_token.into());
+        self
+    }
+
+    /// Reads the credentials from `BUFF_SESSION_COOKIE` and `BUFF_CSRF_TOKEN`, loading a `.env`
+    /// file if there is one.
+    pub fn credentials_from_env(self) -> Result<Self, BuffMarketError> {
+        dotenv::dotenv().ok();
+        let session_cookie = env::var("BUFF_SESSION_COOKIE")?;
+        let csrf_token = env::var("BUFF_CSRF_TOKEN")?;
+        Ok(self.credentials(session_cookie, csrf_token))
+    }
+
+    /// Sends every request to `base_url`, e.g. a mock server serving both hosts.
+    pub fn base_url(self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        self.market_base_url(base_url.clone()).buy_orders_base_url(base_url)
+    }
+
+    /// Sends market goods requests to `base_url` instead of [`endpoints::BUFF_MARKET_BASE_URL`].
+    pub fn market_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.market_base_url = Some(base_url.into());
+        self
+    }
+
+    /// Sends buy order requests to `base_url` instead of [`endpoints::BUFF_163_BASE_URL`].
+    pub fn buy_orders_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.buy_orders_base_url = Some(base_url.into());
+        self
+    }
+
+    /// Timeout of whole requests.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Replaces the default browser user agent.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.http.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends requests with `client`. Its own settings are used as is: the timeout, user agent
+    /// and proxy of this builder are ignored.
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http.client = Some(client);
+        self
+    }
+
+    pub fn build(self) -> Result<BuffMarketClient, BuffMarketError> {
+        let (Some(session_cookie), Some(csrf_token)) = (self.session_cookie, self.csrf_token) else {
+            return Err(BuffMarketError::InvalidInput("Buff credentials are not set".to_string()));
+        };
+        let base_url = |url: Option<String>, default: &str| {
+            url.unwrap_or_else(|| default.to_string()).trim_end_matches('/').to_string()
+        };
+
+        Ok(BuffMarketClient {
+            // .cookie_store(true) // Consider if reqwest's cookie store is beneficial here
+            client: self.http.build(DEFAULT_USER_AGENT, HeaderMap::new())?,
+            market_base_url: base_url(self.market_base_url, endpoints::BUFF_MARKET_BASE_URL),
+            buy_orders_base_url: base_url(self.buy_orders_base_url, endpoints::BUFF_163_BASE_URL),
+            session_cookie,
+            csrf_token,
+            catalog: None,
+            transport: Transport::new(RetryPolicy::default(), RateLimiter::for_market(Market::BuffMarket)),
+        })
+    }
+}
+
 // Example of how one might try to get *all* listings for a particular goods_id.
 // This would require knowing the total_pages from a first call, then iterating.
 // pub async fn get_all_buy_orders_for_item(
@@ -1,2 +1,7 @@
-pub const BUFF_BUY_ORDERS_API_URL: &str = "https://buff.163.com/api/market/goods/buy_order";
-pub const BUFF_MARKET_GOODS_API_URL: &str = "https://api.buff.market/api/market/goods"; 
\ No newline at end of file
+/// Host of the market goods endpoint.
+pub const BUFF_MARKET_BASE_URL: &str = "https://api.buff.market";
+/// Host of the buy orders endpoint.
+pub const BUFF_163_BASE_URL: &str = "https://buff.163.com";
+
+pub const MARKET_GOODS_PATH: &str = "/api/market/goods";
+pub const BUY_ORDERS_PATH: &str = "/api/market/goods/buy_order";
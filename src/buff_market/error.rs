@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::http::{CassetteError, MarketApiError};
+
 #[derive(Error, Debug)]
 pub enum BuffMarketError {
     #[error("HTTP request failed: {0}")]
@@ -8,15 +10,18 @@ pub enum BuffMarketError {
     #[error("Failed to parse JSON response: {0}")]
     JsonParse(#[from] serde_json::Error),
 
-    #[error("API error: {message}")]
-    ApiError {
-        message: String,
-        // code: Option<String>, // Depending on how Buff API reports errors
-    },
+    #[error("API error: {0}")]
+    ApiError(MarketApiError),
+
+    #[error("Cassette error: {0}")]
+    Cassette(#[from] CassetteError),
 
     #[error("Missing data in API response: {0}")]
     MissingData(String),
 
+    #[error("Environment error: {0}")]
+    EnvError(#[from] std::env::VarError),
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
@@ -3,6 +3,6 @@ pub mod endpoints;
 pub mod error;
 pub mod models;
 
-pub use client::BuffMarketClient;
+pub use client::{BuffMarketClient, BuffMarketClientBuilder};
 pub use error::BuffMarketError;
 pub use models::*; 
\ No newline at end of file
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::money::Money;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GoodsBuyOrderResponse {
     pub code: String,
@@ -23,7 +25,8 @@ pub struct Item {
     pub app_id: i32,
     pub asset_info: Option<AssetInfo>,
     pub bookmarked: bool,
-    pub buy_max_price: Option<String>,
+    #[serde(default, with = "crate::money::wire::cny_decimal_opt")]
+    pub buy_max_price: Option<Money>,
     pub buy_num: i32,
     pub can_bargain: bool,
     pub can_search_intent: bool,
@@ -32,10 +35,12 @@ pub struct Item {
     pub id: String,
     pub updated_at: i64,
     pub user_id: i64,
-    pub price: String, // This was 'url' in your python example, but 'price' seems more standard from API responses
+    #[serde(with = "crate::money::wire::cny_decimal")]
+    pub price: Money, // This was 'url' in your python example, but 'price' seems more standard from API responses
     pub state: i32,    // 0: pending, 1: active etc. (need to confirm exact states)
     pub supported_pay_method: i32,
-    pub trade_max_price: Option<String>,
+    #[serde(default, with = "crate::money::wire::cny_decimal_opt")]
+    pub trade_max_price: Option<Money>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,7 +56,7 @@ pub struct AssetInfo {
     // pub sticker_info: Option<Vec<Sticker>> // Sticker details
 }
 
-// Structs for https://api.buff.market/api/market/goods endpoint
+// Structs for the /api/market/goods endpoint
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketGoodsResponse {
@@ -69,22 +74,26 @@ pub struct MarketGoodsData {
     pub total_page: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketGoodsItem {
     pub appid: i32,
     #[serde(rename = "id")] // Assuming 'id' is the goods_id for the item itself in this context
     pub goods_internal_id: i64, // Renamed to avoid conflict if used alongside other IDs. The example shows "id": 1092
     pub name: String,
     pub market_hash_name: String,
-    pub sell_min_price: String, // Price in a string format, e.g., "605"
-    pub steam_price: Option<String>,
-    pub steam_price_cny: Option<String>,
+    #[serde(with = "crate::money::wire::cny_decimal")]
+    pub sell_min_price: Money, // Sent as a decimal string, e.g., "605"
+    #[serde(default, with = "crate::money::wire::usd_decimal_opt")]
+    pub steam_price: Option<Money>,
+    #[serde(default, with = "crate::money::wire::cny_decimal_opt")]
+    pub steam_price_cny: Option<Money>,
     pub icon_url: String,
     pub original_icon_url: Option<String>,
     pub goods_info: Option<GoodsInfo>, // Detailed info, might need further refinement
     pub info: Option<ItemInfoContainer>, // Contains tags
     pub bookmarked: Option<bool>,
-    pub buy_max_price: Option<String>,
+    #[serde(default, with = "crate::money::wire::cny_decimal_opt")]
+    pub buy_max_price: Option<Money>,
     pub buy_num: Option<i32>,
     pub can_bargain: Option<bool>,
     pub sell_num: Option<i32>,
@@ -96,7 +105,7 @@ pub struct MarketGoodsItem {
     // e.g., auction_num, can_search_by_tournament, description, game, item_id, market_min_price etc.
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoodsInfo {
     // Based on user example: goods_info: {,…}
     // This suggests it might be a complex object. For now, let's assume it might contain various details.
@@ -104,17 +113,19 @@ pub struct GoodsInfo {
     // Example fields that *might* be here based on typical market data:
     pub icon_url: Option<String>,
     pub original_icon_url: Option<String>,
-    pub steam_price: Option<String>,
-    pub steam_price_cny: Option<String>,
+    #[serde(default, with = "crate::money::wire::usd_decimal_opt")]
+    pub steam_price: Option<Money>,
+    #[serde(default, with = "crate::money::wire::cny_decimal_opt")]
+    pub steam_price_cny: Option<Money>,
     // Add other relevant fields if known
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItemInfoContainer {
     pub tags: Option<ItemTags>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItemTags {
     pub category: Option<TagDetails>,
     pub exterior: Option<TagDetails>,
@@ -124,7 +135,7 @@ pub struct ItemTags {
     pub type_tag: Option<TagDetails>, // Renamed 'type' to 'type_tag' to avoid Rust keyword conflict
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagDetails {
     pub category: String,
     pub id: i64, // Or String if it can be non-numeric
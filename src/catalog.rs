@@ -0,0 +1,228 @@
+//! Cross-market item catalog.
+//!
+//! Each market identifies items by its own IDs: DMarket `classId`, CSFloat `def_index` and
+//! `paint_index`, Buff `goods_id`. An [`ItemCatalog`] maps the canonical `market_hash_name` of an
+//! item (see [`ItemIdentity`]) to each of them. Clients given a catalog record the items they
+//! see, and the catalog can be saved to and loaded from a JSON file.
+
+use tracing::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::RwLock;
+use thiserror::Error;
+
+use crate::identity::{IdentityError, ItemIdentity};
+
+#[derive(Error, Debug)]
+pub enum CatalogError {
+    #[error("Failed to access catalog file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse catalog: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Invalid item name: {0}")]
+    Identity(#[from] IdentityError),
+}
+
+/// CSFloat's identifiers of a skin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CSFloatIds {
+    pub def_index: i32,
+    pub paint_index: Option<i32>,
+}
+
+/// The identifiers of one item on each market, where known.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketIds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dmarket_class_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub csfloat: Option<CSFloatIds>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buff_goods_id: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CatalogFile {
+    items: BTreeMap<String, MarketIds>,
+}
+
+/// Maps canonical item names to their per-market identifiers.
+///
+/// Shareable across clients behind an `Arc`; lookups and updates lock internally.
+#[derive(Debug, Default)]
+pub struct ItemCatalog {
+    items: RwLock<BTreeMap<String, MarketIds>>,
+}
+
+impl ItemCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a catalog previously written with [`save`](Self::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        let file = serde_json::from_str::<CatalogFile>(&contents)?;
+        debug!("Loaded {} catalog items from {}", file.items.len(), path.as_ref().display());
+        Ok(Self {
+            items: RwLock::new(file.items),
+        })
+    }
+
+    /// Loads the catalog at `path`, or returns an empty one if the file does not exist yet.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        if path.as_ref().exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::new())
+        }
+    }
+
+    /// Writes the catalog to `path` as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CatalogError> {
+        let file = CatalogFile {
+            items: self.read().clone(),
+        };
+        std::fs::write(path.as_ref(), serde_json::to_string_pretty(&file)?)?;
+        debug!("Saved {} catalog items to {}", file.items.len(), path.as_ref().display());
+        Ok(())
+    }
+
+    /// The identifiers known for `market_hash_name`.
+    pub fn get(&self, market_hash_name: &str) -> Option<MarketIds> {
+        let key = canonical_name(market_hash_name).ok()?;
+        self.read().get(&key).cloned()
+    }
+
+    pub fn dmarket_class_id(&self, market_hash_name: &str) -> Option<String> {
+        self.get(market_hash_name)?.dmarket_class_id
+    }
+
+    pub fn csfloat_ids(&self, market_hash_name: &str) -> Option<CSFloatIds> {
+        self.get(market_hash_name)?.csfloat
+    }
+
+    pub fn buff_goods_id(&self, market_hash_name: &str) -> Option<i64> {
+        self.get(market_hash_name)?.buff_goods_id
+    }
+
+    /// Finds the canonical name of the item with the given Buff `goods_id`.
+    pub fn name_for_buff_goods_id(&self, goods_id: i64) -> Option<String> {
+        self.read()
+            .iter()
+            .find(|(_, ids)| ids.buff_goods_id == Some(goods_id))
+            .map(|(name, _)| name.clone())
+    }
+
+    pub fn record_dmarket(&self, market_hash_name: &str, class_id: &str) {
+        if class_id.is_empty() {
+            return;
+        }
+        self.update(market_hash_name, |ids| ids.dmarket_class_id = Some(class_id.to_string()));
+    }
+
+    pub fn record_csfloat(&self, market_hash_name: &str, def_index: i32, paint_index: Option<i32>) {
+        self.update(market_hash_name, |ids| {
+            ids.csfloat = Some(CSFloatIds { def_index, paint_index })
+        });
+    }
+
+    pub fn record_buff(&self, market_hash_name: &str, goods_id: i64) {
+        self.update(market_hash_name, |ids| ids.buff_goods_id = Some(goods_id));
+    }
+
+    /// Number of items in the catalog.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// A copy of all entries, keyed by canonical name.
+    pub fn entries(&self) -> BTreeMap<String, MarketIds> {
+        self.read().clone()
+    }
+
+    fn update(&self, market_hash_name: &str, apply: impl FnOnce(&mut MarketIds)) {
+        let Ok(key) = canonical_name(market_hash_name) else {
+            return;
+        };
+        let mut items = self.items.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        apply(items.entry(key).or_default());
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, MarketIds>> {
+        self.items.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The catalog key for a name: its canonical `market_hash_name`, without any Doppler phase.
+fn canonical_name(market_hash_name: &str) -> Result<String, IdentityError> {
+    Ok(ItemIdentity::parse(market_hash_name)?.market_hash_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KARAMBIT: &str = "★ Karambit | Doppler (Factory New)";
+
+    #[test]
+    fn keys_items_by_canonical_name() {
+        let catalog = ItemCatalog::new();
+        catalog.record_dmarket("StatTrak AK-47 | Redline (Field-Tested)", "a8db:st-redline");
+        catalog.record_buff("StatTrak™ AK-47  |  Redline (Field-Tested)", 33816);
+        catalog.record_dmarket("AK-47 | Redline (Field-Tested)", "a8db:redline");
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(
+            catalog.get("StatTrak™ AK-47 | Redline (Field-Tested)"),
+            Some(MarketIds {
+                dmarket_class_id: Some("a8db:st-redline".to_string()),
+                csfloat: None,
+                buff_goods_id: Some(33816),
+            })
+        );
+        assert_eq!(catalog.buff_goods_id("AK-47 | Redline (Field-Tested)"), None);
+
+        // Every Doppler phase shares the entry of the item's `market_hash_name`.
+        catalog.record_csfloat(&format!("{} - Phase 2", KARAMBIT), 507, Some(418));
+        catalog.record_buff(&format!("{} - Ruby", KARAMBIT), 42001);
+        assert_eq!(catalog.len(), 3);
+        assert_eq!(catalog.csfloat_ids(KARAMBIT), Some(CSFloatIds { def_index: 507, paint_index: Some(418) }));
+        assert_eq!(catalog.buff_goods_id(&format!("{} - Phase 4", KARAMBIT)), Some(42001));
+        assert_eq!(catalog.name_for_buff_goods_id(42001).as_deref(), Some(KARAMBIT));
+    }
+
+    #[test]
+    fn ignores_unusable_records() {
+        let catalog = ItemCatalog::new();
+        catalog.record_dmarket("AK-47 | Redline (Field-Tested)", "");
+        catalog.record_buff("   ", 1);
+        assert!(catalog.is_empty());
+        assert_eq!(catalog.get(""), None);
+        assert_eq!(catalog.name_for_buff_goods_id(1), None);
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let path = std::env::temp_dir().join(format!("backend-catalog-{}.json", std::process::id()));
+        let catalog = ItemCatalog::new();
+        catalog.record_dmarket(KARAMBIT, "a8db:karambit");
+        catalog.record_csfloat(KARAMBIT, 507, Some(418));
+        catalog.record_buff("Revolution Case", 871);
+        catalog.save(&path).unwrap();
+
+        let loaded = ItemCatalog::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.entries(), catalog.entries());
+        assert_eq!(loaded.buff_goods_id("Revolution Case"), Some(871));
+
+        assert!(ItemCatalog::load_or_default(&path).unwrap().is_empty());
+        assert!(matches!(ItemCatalog::load(&path), Err(CatalogError::Io(_))));
+    }
+}
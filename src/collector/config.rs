@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::CollectorError;
+use crate::marketplace::Market;
+
+const DEFAULT_MARKET_CALLS: usize = 2;
+const DEFAULT_SAMPLE_SIZE: u32 = 50;
+
+/// Collector configuration, usually loaded from a JSON file such as:
+///
+/// ```json
+/// {
+///   "database": "prices.db",
+///   "market_calls": {"BuffMarket": 1},
+///   "jobs": [
+///     {"name": "buff-crawl", "interval_secs": 3600, "type": "full_crawl", "markets": ["BuffMarket"]},
+///     {"name": "hot", "interval_secs": 300, "type": "hot_items", "items": ["AK-47 | Redline (Field-Tested)"]},
+///     {"name": "inventory", "interval_secs": 900, "type": "inventory_sync"}
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollectorConfig {
+    /// SQLite database to write snapshots to. Snapshots are only logged when unset.
+    #[serde(default)]
+    pub database: Option<PathBuf>,
+    /// Item catalog file, loaded at startup and saved on shutdown.
+    #[serde(default)]
+    pub catalog: Option<PathBuf>,
+    /// Maximum number of marketplace calls running at once per market. A call is one operation
+    /// of a job, such as a lowest-ask lookup or a whole crawl, and may span several requests;
+    /// pacing of the requests themselves is left to each client's rate limiter.
+    #[serde(default, alias = "concurrency")]
+    pub market_calls: HashMap<Market, usize>,
+    pub jobs: Vec<JobConfig>,
+}
+
+impl CollectorConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CollectorError> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// The number of calls `market` may run at once.
+    pub fn market_calls_for(&self, market: Market) -> usize {
+        self.market_calls
+            .get(&market)
+            .copied()
+            .unwrap_or(DEFAULT_MARKET_CALLS)
+            .max(1)
+    }
+}
+
+/// A job run on a fixed interval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobConfig {
+    pub name: String,
+    pub interval_secs: u64,
+    /// Markets the job runs against; all configured markets when empty.
+    #[serde(default)]
+    pub markets: Vec<Market>,
+    #[serde(flatten)]
+    pub kind: JobKind,
+}
+
+impl JobConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+
+    /// Whether the job runs against `market`.
+    pub fn targets(&self, market: Market) -> bool {
+        self.markets.is_empty() || self.markets.contains(&market)
+    }
+}
+
+/// What a job collects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobKind {
+    /// Crawls every listing of the markets that support it.
+    FullCrawl {
+        #[serde(default)]
+        max_items: Option<usize>,
+    },
+    /// Aggregates the prices of a fixed set of items.
+    HotItems {
+        items: Vec<String>,
+        #[serde(default = "default_sample_size")]
+        sample_size: u32,
+    },
+    /// Fetches the user's inventory.
+    InventorySync,
+}
+
+fn default_sample_size() -> u32 {
+    DEFAULT_SAMPLE_SIZE
+}
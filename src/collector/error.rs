@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+use crate::catalog::CatalogError;
+use crate::marketplace::MarketplaceError;
+use crate::storage::StorageError;
+
+#[derive(Error, Debug)]
+pub enum CollectorError {
+    #[error("Failed to read config: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid config: {0}")]
+    Config(#[from] serde_json::Error),
+
+    #[error("Market error: {0}")]
+    Marketplace(#[from] MarketplaceError),
+
+    #[error("Catalog error: {0}")]
+    Catalog(#[from] CatalogError),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+}
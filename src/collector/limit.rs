@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+use crate::listing::NormalizedListing;
+use crate::marketplace::{Capabilities, InventoryAsset, Market, MarketBid, Marketplace, MarketplaceError};
+
+/// A marketplace whose calls wait for a permit from a semaphore shared by all jobs.
+///
+/// A permit is held for the whole call, so a multi-page crawl occupies one permit until its last
+/// page is fetched.
+pub(crate) struct LimitedMarketplace {
+    inner: Arc<dyn Marketplace>,
+    permits: Arc<Semaphore>,
+}
+
+impl LimitedMarketplace {
+    pub(crate) fn new(inner: Arc<dyn Marketplace>, permits: Arc<Semaphore>) -> Self {
+        Self { inner, permits }
+    }
+
+    async fn permit(&self) -> tokio::sync::SemaphorePermit<'_> {
+        self.permits.acquire().await.expect("collector semaphores are never closed")
+    }
+}
+
+#[async_trait]
+impl Marketplace for LimitedMarketplace {
+    fn market(&self) -> Market {
+        self.inner.market()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn list_offers(
+        &self,
+        market_hash_name: &str,
+        limit: u32,
+    ) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let _permit = self.permit().await;
+        self.inner.list_offers(market_hash_name, limit).await
+    }
+
+    async fn lowest_ask(&self, market_hash_name: &str) -> Result<Option<NormalizedListing>, MarketplaceError> {
+        let _permit = self.permit().await;
+        self.inner.lowest_ask(market_hash_name).await
+    }
+
+    async fn highest_bid(&self, market_hash_name: &str) -> Result<Option<MarketBid>, MarketplaceError> {
+        let _permit = self.permit().await;
+        self.inner.highest_bid(market_hash_name).await
+    }
+
+    async fn my_inventory(&self) -> Result<Vec<InventoryAsset>, MarketplaceError> {
+        let _permit = self.permit().await;
+        self.inner.my_inventory().await
+    }
+
+    async fn my_listings(&self) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let _permit = self.permit().await;
+        self.inner.my_listings().await
+    }
+
+    async fn crawl(&self, max_items: Option<usize>) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let _permit = self.permit().await;
+        self.inner.crawl(max_items).await
+    }
+}
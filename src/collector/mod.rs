@@ -0,0 +1,211 @@
+//! Scheduled data collection.
+//!
+//! A [`Collector`] runs the jobs of a [`CollectorConfig`] against a set of markets, each job on
+//! its own interval, and writes what they gather into a [`Sink`]. Requests to a market are
+//! limited by a semaphore shared by all jobs. On shutdown, running crawls are cancelled, since
+//! they can take hours; other jobs finish their current run and stop.
+//!
+//! Every run is traced in a `collector_job` span, and the work it does on each market in a
+//! `crawl` or `inventory_sync` span, so the requests of one run can be told apart.
+
+pub mod config;
+pub mod error;
+mod limit;
+pub mod sink;
+
+use chrono::Utc;
+use tracing::{error, info, info_span, warn, Instrument};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{watch, Semaphore};
+use tokio::time::MissedTickBehavior;
+
+use crate::aggregator::Aggregator;
+use crate::identity::ItemIdentity;
+use crate::marketplace::{Marketplace, Operation};
+
+pub use config::{CollectorConfig, JobConfig, JobKind};
+pub use error::CollectorError;
+pub use sink::{LogSink, Sink, StorageSink};
+
+/// Runs collection jobs until shut down.
+pub struct Collector {
+    jobs: Vec<JobConfig>,
+    markets: Vec<Arc<dyn Marketplace>>,
+    sink: Arc<dyn Sink>,
+}
+
+impl Collector {
+    /// Creates a collector for the jobs in `config`, limiting each market to its configured
+    /// number of calls at once.
+    pub fn new(config: &CollectorConfig, markets: Vec<Arc<dyn Marketplace>>, sink: Arc<dyn Sink>) -> Self {
+        let markets = markets
+            .into_iter()
+            .map(|market| {
+                let permits = Arc::new(Semaphore::new(config.market_calls_for(market.market())));
+                Arc::new(limit::LimitedMarketplace::new(market, permits)) as Arc<dyn Marketplace>
+            })
+            .collect();
+        Self {
+            jobs: config.jobs.clone(),
+            markets,
+            sink,
+        }
+    }
+
+    /// Runs every job until `shutdown` completes, then cancels running crawls and waits for the
+    /// other running jobs to finish.
+    pub async fn run(self, shutdown: impl Future<Output = ()>) {
+        let (stop, stopped) = watch::channel(false);
+        let collector = Arc::new(self);
+
+        let handles = collector
+            .jobs
+            .iter()
+            .cloned()
+            .map(|job| {
+                let collector = Arc::clone(&collector);
+                let stopped = stopped.clone();
+                tokio::spawn(async move { collector.schedule(job, stopped).await })
+            })
+            .collect::<Vec<_>>();
+        info!("Collector started with {} jobs", handles.len());
+
+        shutdown.await;
+        info!("Shutting down collector");
+        let _ = stop.send(true);
+        for handle in handles {
+            if let Err(e) = handle.await {
+                error!("Collector job panicked: {}", e);
+            }
+        }
+        info!("Collector stopped");
+    }
+
+    /// Runs `job` on its interval until `stopped` is set.
+    async fn schedule(&self, job: JobConfig, mut stopped: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(job.interval());
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = stopped.changed() => return,
+            }
+            if *stopped.borrow() {
+                return;
+            }
+            let span = info_span!("collector_job", job = %job.name);
+            let run = async {
+                info!("Running job {}", job.name);
+                if let Err(e) = self.run_job(&job).await {
+                    error!("Job {} failed: {}", job.name, e);
+                }
+            }
+            .instrument(span);
+            if matches!(job.kind, JobKind::FullCrawl { .. }) {
+                // A crawl only writes once it completes, so cancelling it loses nothing stored.
+                tokio::select! {
+                    _ = run => {}
+                    _ = stopped.changed() => {
+                        warn!("Cancelled job {} on shutdown", job.name);
+                        return;
+                    }
+                }
+            } else {
+                run.await;
+            }
+        }
+    }
+
+    /// Runs `job` once.
+    pub async fn run_job(&self, job: &JobConfig) -> Result<(), CollectorError> {
+        let markets = self
+            .markets
+            .iter()
+            .filter(|market| job.targets(market.market()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        match &job.kind {
+            JobKind::FullCrawl { max_items } => {
+                let max_items = *max_items;
+                let crawls = markets
+                    .iter()
+                    .filter(|market| market.capabilities().supports(Operation::Crawl))
+                    .map(|market| {
+                        let span = info_span!("crawl", market = %market.market(), max_items);
+                        async move { (market.market(), market.crawl(max_items).await) }.instrument(span)
+                    })
+                    .collect::<Vec<_>>();
+                for (market, result) in futures::future::join_all(crawls).await {
+                    match result {
+                        Ok(listings) => self.sink.write_listings(Utc::now(), &listings).await?,
+                        Err(e) => warn!("Crawling {} failed: {}", market, e),
+                    }
+                }
+            }
+            JobKind::HotItems { items, sample_size } => {
+                let identities = items
+                    .iter()
+                    .filter_map(|name| match ItemIdentity::parse(name) {
+                        Ok(identity) => Some(identity),
+                        Err(e) => {
+                            warn!("Skipping hot item {:?}: {}", name, e);
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let aggregates = Aggregator::new(markets)
+                    .with_sample_size(*sample_size)
+                    .aggregate(&identities)
+                    .await;
+                for failure in aggregates.iter().flat_map(|aggregate| &aggregate.failures) {
+                    warn!("{} failed on {}: {}", failure.operation, failure.market, failure.error);
+                }
+                self.sink.write_aggregates(Utc::now(), &aggregates).await?;
+            }
+            JobKind::InventorySync => {
+                let syncs = markets
+                    .iter()
+                    .filter(|market| market.capabilities().supports(Operation::Inventory))
+                    .map(|market| {
+                        let span = info_span!("inventory_sync", market = %market.market());
+                        async move { (market.market(), market.my_inventory().await) }.instrument(span)
+                    })
+                    .collect::<Vec<_>>();
+                for (market, result) in futures::future::join_all(syncs).await {
+                    match result {
+                        Ok(assets) => self.sink.write_inventory(Utc::now(), market, &assets).await?,
+                        Err(e) => warn!("Syncing {} inventory failed: {}", market, e),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+}
+
+/// Completes on SIGTERM or Ctrl-C.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::info;
+use std::sync::Arc;
+
+use super::CollectorError;
+use crate::aggregator::ItemAggregate;
+use crate::listing::NormalizedListing;
+use crate::marketplace::{InventoryAsset, Market};
+use crate::storage::Storage;
+
+/// Destination of the data gathered by collector jobs.
+#[async_trait]
+pub trait Sink: Send + Sync {
+    async fn write_listings(
+        &self,
+        captured_at: DateTime<Utc>,
+        listings: &[NormalizedListing],
+    ) -> Result<(), CollectorError>;
+
+    async fn write_aggregates(
+        &self,
+        captured_at: DateTime<Utc>,
+        aggregates: &[ItemAggregate],
+    ) -> Result<(), CollectorError>;
+
+    async fn write_inventory(
+        &self,
+        captured_at: DateTime<Utc>,
+        market: Market,
+        assets: &[InventoryAsset],
+    ) -> Result<(), CollectorError>;
+}
+
+/// Logs a summary of everything written to it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogSink;
+
+#[async_trait]
+impl Sink for LogSink {
+    async fn write_listings(
+        &self,
+        captured_at: DateTime<Utc>,
+        listings: &[NormalizedListing],
+    ) -> Result<(), CollectorError> {
+        info!("{}: collected {} listings", captured_at, listings.len());
+        Ok(())
+    }
+
+    async fn write_aggregates(
+        &self,
+        captured_at: DateTime<Utc>,
+        aggregates: &[ItemAggregate],
+    ) -> Result<(), CollectorError> {
+        for aggregate in aggregates {
+            let best_ask = aggregate.overall.best_ask.map(|ask| format!("{} on {}", ask.price, ask.market));
+            info!(
+                "{}: {} best ask {}, {} listings",
+                captured_at,
+                aggregate.identity,
+                best_ask.as_deref().unwrap_or("none"),
+                aggregate.overall.listing_count
+            );
+        }
+        Ok(())
+    }
+
+    async fn write_inventory(
+        &self,
+        captured_at: DateTime<Utc>,
+        market: Market,
+        assets: &[InventoryAsset],
+    ) -> Result<(), CollectorError> {
+        info!("{}: {} inventory holds {} items", captured_at, market, assets.len());
+        Ok(())
+    }
+}
+
+/// Writes listings and prices to a [`Storage`] database.
+///
+/// Inventories are not stored.
+#[derive(Clone)]
+pub struct StorageSink {
+    storage: Arc<Storage>,
+}
+
+impl StorageSink {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl Sink for StorageSink {
+    async fn write_listings(
+        &self,
+        captured_at: DateTime<Utc>,
+        listings: &[NormalizedListing],
+    ) -> Result<(), CollectorError> {
+        self.storage.record_listings(captured_at, listings)?;
+        Ok(())
+    }
+
+    async fn write_aggregates(
+        &self,
+        captured_at: DateTime<Utc>,
+        aggregates: &[ItemAggregate],
+    ) -> Result<(), CollectorError> {
+        self.storage.record_aggregates(captured_at, aggregates)?;
+        Ok(())
+    }
+
+    async fn write_inventory(
+        &self,
+        _captured_at: DateTime<Utc>,
+        _market: Market,
+        _assets: &[InventoryAsset],
+    ) -> Result<(), CollectorError> {
+        Ok(())
+    }
+}
@@ -1,17 +1,29 @@
 use dotenv::dotenv;
-use log::debug;
+use tracing::debug;
 use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Method;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 
+use crate::catalog::ItemCatalog;
 use crate::csfloat::error::CSFloatError;
 use crate::csfloat::endpoints::listings::ListingsHandler;
+use crate::http::{redact, request_span, Cassette, EndpointGroup, HttpSettings, Idempotency, MarketApiError, RateLimiter, RetryPolicy, Secret, Transport};
+use crate::marketplace::Market;
 
 pub const API_BASE_URL: &str = "https://csfloat.com/api/v1";
 
 /// The main CSFloat API client.
+/// Clones share the rate limiter of the original client. The API key is left out of its `Debug`
+/// output.
+#[derive(Clone, Debug)]
 pub struct CSFloatClient {
-    pub(crate) api_key: String,
+    pub(crate) api_key: Secret,
     pub(crate) http_client: reqwest::Client,
+    pub(crate) base_url: String,
+    pub(crate) catalog: Option<Arc<ItemCatalog>>,
+    pub(crate) transport: Transport,
 }
 
 impl CSFloatClient {
@@ -19,32 +31,60 @@ impl CSFloatClient {
     /// 
     /// Reads `CSFLOAT_API_KEY` from environment variables.
     pub fn new() -> Result<Self, CSFloatError> {
-        dotenv().ok();
-        let api_key = env::var("CSFLOAT_API_KEY")?;
+        Self::builder().api_key_from_env()?.build()
+    }
 
-        if api_key.is_empty() {
-            return Err(CSFloatError::ApiError(
-                "API key cannot be empty".to_string(),
-            ));
-        }
+    /// Starts configuring a client, e.g. to pass the API key explicitly or to point it at
+    /// another host.
+    pub fn builder() -> CSFloatClientBuilder {
+        CSFloatClientBuilder::default()
+    }
 
-        debug!(
-            "Initializing CSFloat client with API key (first 8 chars): {}...",
-            api_key.chars().take(8).collect::<String>()
-        );
+    /// The API root requests are sent to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
 
-        Ok(CSFloatClient {
-            api_key,
-            http_client: reqwest::Client::builder()
-                .user_agent("CSFloat-API-Client/1.0")
-                .build()?,
-        })
+    /// Records the items this client sees in `catalog`.
+    pub fn with_catalog(mut self, catalog: Arc<ItemCatalog>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    pub fn catalog(&self) -> Option<&Arc<ItemCatalog>> {
+        self.catalog.as_ref()
+    }
+
+    /// Retries failed requests according to `retry`. `POST` requests are only retried when the
+    /// connection failed.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.transport.set_retry_policy(retry);
+        self
+    }
+
+    /// Limits requests with `limiter` instead of the market's default quotas. Pass a clone of
+    /// one limiter to several clients to make them share a quota.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.transport.set_rate_limiter(limiter);
+        self
+    }
+
+    /// The limiter of this client, including how long requests waited on it.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        self.transport.rate_limiter()
+    }
+
+    /// Records this client's traffic to `cassette`, or answers requests from it when it is
+    /// replaying.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.transport.set_cassette(cassette);
+        self
     }
 
     /// Creates the required HTTP headers for an API request.
     pub(crate) fn create_headers(&self) -> Result<HeaderMap, CSFloatError> {
         let mut headers = HeaderMap::new();
-        headers.insert("Authorization", HeaderValue::from_str(&self.api_key)?);
+        headers.insert("Authorization", HeaderValue::from_str(self.api_key.expose())?);
         headers.insert("Content-Type", HeaderValue::from_static("application/json"));
         headers.insert("Accept", HeaderValue::from_static("application/json"));
         Ok(headers)
@@ -55,13 +95,15 @@ impl CSFloatClient {
         &self,
         endpoint: &str,
     ) -> Result<T, CSFloatError> {
-        let url = format!("{}{}", API_BASE_URL, endpoint);
+        let url = format!("{}{}", self.base_url, endpoint);
         debug!("GET request to URL: {}", url);
         
-        let response = self.http_client
-            .get(&url)
-            .headers(self.create_headers()?)
-            .send()
+        let span = request_span(Market::CSFloat, &Method::GET, &url);
+        let response = self
+            .transport
+            .send(span, EndpointGroup::MarketData, Idempotency::Idempotent, || {
+                Ok::<_, CSFloatError>(self.http_client.get(&url).headers(self.create_headers()?))
+            })
             .await?;
 
         let status = response.status();
@@ -69,16 +111,12 @@ impl CSFloatClient {
         
         if !status.is_success() {
             let error_text = response.text().await?;
-            debug!("Error response: {}", error_text);
-            return Err(CSFloatError::ApiError(format!(
-                "API request failed with status {}: {}",
-                status, error_text
-            )));
+            debug!("Error response: {}", redact::body(&error_text));
+            return Err(CSFloatError::ApiError(MarketApiError::from_body(Market::CSFloat, status, &error_text)));
         }
 
-        // Debug the response body before trying to deserialize it
         let text = response.text().await?;
-        debug!("Response body: {}", text);
+        debug!("Response of {} bytes", text.len());
         
         match serde_json::from_str::<T>(&text) {
             Ok(parsed) => Ok(parsed),
@@ -95,29 +133,25 @@ impl CSFloatClient {
         endpoint: &str,
         body: &B,
     ) -> Result<T, CSFloatError> {
-        let url = format!("{}{}", API_BASE_URL, endpoint);
+        let url = format!("{}{}", self.base_url, endpoint);
         
-        let response = self.http_client
-            .post(&url)
-            .headers(self.create_headers()?)
-            .json(body)
-            .send()
+        let span = request_span(Market::CSFloat, &Method::POST, &url);
+        let response = self
+            .transport
+            .send(span, EndpointGroup::Trading, Idempotency::NonIdempotent, || {
+                Ok::<_, CSFloatError>(self.http_client.post(&url).headers(self.create_headers()?).json(body))
+            })
             .await?;
 
         let status = response.status();
         
         if !status.is_success() {
             let error_text = response.text().await?;
-            return Err(CSFloatError::ApiError(format!(
-                "API request failed with status {}: {}",
-                status,
-                error_text
-            )));
+            return Err(CSFloatError::ApiError(MarketApiError::from_body(Market::CSFloat, status, &error_text)));
         }
 
-        // Debug the response body before trying to deserialize it
         let text = response.text().await?;
-        debug!("Response body: {}", text);
+        debug!("Response of {} bytes", text.len());
         
         match serde_json::from_str::<T>(&text) {
             Ok(parsed) => Ok(parsed),
@@ -132,4 +166,84 @@ impl CSFloatClient {
     pub fn listings(&self) -> ListingsHandler<'_> {
         ListingsHandler::new(self)
     }
-} 
\ No newline at end of file
+} 
+
+/// Configures a [`CSFloatClient`].
+#[derive(Debug, Default)]
+pub struct CSFloatClientBuilder {
+    api_key: Option<Secret>,
+    base_url: Option<String>,
+    http: HttpSettings,
+}
+
+impl CSFloatClientBuilder {
+    pub fn api_key(mut self, api_key: impl Into<Secret>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Reads the API key from `CSFLOAT_API_KEY`, loading a `.env` file if there is one.
+    pub fn api_key_from_env(self) -> Result<Self, CSFloatError> {
+        dotenv().ok();
+        let api_key = env::var("CSFLOAT_API_KEY")?;
+        Ok(self.api_key(api_key))
+    }
+
+    /// Sends requests to `base_url` instead of [`API_BASE_URL`], e.g. a mock server. The URL
+    /// includes the API version path.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Timeout of whole requests.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.http.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends requests with `client`. Its own settings are used as is: the timeout, user agent
+    /// and proxy of this builder are ignored.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http.client = Some(client);
+        self
+    }
+
+    pub fn build(self) -> Result<CSFloatClient, CSFloatError> {
+        let api_key = self.api_key.unwrap_or_default();
+        if api_key.is_empty() {
+            return Err(CSFloatError::InvalidCredentials(
+                "API key cannot be empty".to_string(),
+            ));
+        }
+
+        debug!("Initializing CSFloat client");
+
+        Ok(CSFloatClient {
+            api_key,
+            http_client: self.http.build("CSFloat-API-Client/1.0", HeaderMap::new())?,
+            base_url: self
+                .base_url
+                .unwrap_or_else(|| API_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            catalog: None,
+            transport: Transport::new(RetryPolicy::default(), RateLimiter::for_market(Market::CSFloat)),
+        })
+    }
+}
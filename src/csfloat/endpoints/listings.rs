@@ -2,9 +2,10 @@ use serde::{Deserialize, Serialize};
 use crate::csfloat::client::CSFloatClient;
 use crate::csfloat::error::CSFloatError;
 use crate::csfloat::models::{Listing, ListingResponse};
+use crate::money::Money;
 
 /// Query parameters for listing search
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ListingsQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page: Option<i32>,
@@ -30,10 +31,10 @@ pub struct ListingsQuery {
     pub user_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub collection: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none", rename = "min_price")]
-    pub min_price: Option<i64>,
-    #[serde(skip_serializing_if = "Option::is_none", rename = "max_price")]
-    pub max_price: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "min_price", default, with = "crate::money::wire::usd_cents_opt")]
+    pub min_price: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "max_price", default, with = "crate::money::wire::usd_cents_opt")]
+    pub max_price: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "market_hash_name")]
     pub market_hash_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "type")]
@@ -49,11 +50,12 @@ pub struct CreateListingRequest {
     pub asset_id: i64,
     #[serde(rename = "type")]
     pub type_: String,
-    pub price: i64,
+    #[serde(with = "crate::money::wire::usd_cents")]
+    pub price: Money,
     #[serde(skip_serializing_if = "Option::is_none", rename = "max_offer_discount")]
     pub max_offer_discount: Option<i64>,
-    #[serde(skip_serializing_if = "Option::is_none", rename = "reserve_price")]
-    pub reserve_price: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "reserve_price", default, with = "crate::money::wire::usd_cents_opt")]
+    pub reserve_price: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "duration_days")]
     pub duration_days: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -81,17 +83,30 @@ impl<'a> ListingsHandler<'a> {
             "/listings".to_string()
         };
 
-        self.client.get(&endpoint).await
+        let response: ListingResponse = self.client.get(&endpoint).await?;
+        for listing in &response.data {
+            self.record(listing);
+        }
+        Ok(response)
     }
 
     /// Get a specific listing by ID
     pub async fn get_listing(&self, id: &str) -> Result<Listing, CSFloatError> {
         let endpoint = format!("/listings/{}", id);
-        self.client.get(&endpoint).await
+        let listing: Listing = self.client.get(&endpoint).await?;
+        self.record(&listing);
+        Ok(listing)
     }
 
     /// Create a new listing
     pub async fn create_listing(&self, request: CreateListingRequest) -> Result<Listing, CSFloatError> {
         self.client.post("/listings", &request).await
     }
+
+    /// Records the listed item in the client's catalog, if it has one.
+    fn record(&self, listing: &Listing) {
+        if let (Some(catalog), Some(def_index)) = (&self.client.catalog, listing.item.def_index) {
+            catalog.record_csfloat(&listing.item.market_hash_name, def_index, listing.item.paint_index);
+        }
+    }
 } 
\ No newline at end of file
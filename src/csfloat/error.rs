@@ -1,9 +1,14 @@
 use thiserror::Error;
 
+use crate::http::{CassetteError, MarketApiError};
+
 #[derive(Error, Debug)]
 pub enum CSFloatError {
     #[error("API error: {0}")]
-    ApiError(String),
+    ApiError(MarketApiError),
+
+    #[error("Invalid credentials: {0}")]
+    InvalidCredentials(String),
 
     #[error("HTTP error: {0}")]
     HttpError(#[from] reqwest::Error),
@@ -19,4 +24,7 @@ pub enum CSFloatError {
     
     #[error("URL encoding error: {0}")]
     UrlEncodingError(#[from] serde_urlencoded::ser::Error),
-} 
\ No newline at end of file
+
+    #[error("Cassette error: {0}")]
+    CassetteError(#[from] CassetteError),
+}
@@ -9,6 +9,6 @@ pub mod endpoints;
 pub use models::*;
 
 pub use models::{
-    Listing, ListingResponse, Price, Item, Seller, Sticker,
+    Listing, ListingResponse, Item, Seller, Sticker,
     ListingType, ListingState,
 }; 
\ No newline at end of file
@@ -1,18 +1,21 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::money::Money;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Listing {
     pub id: String,
     pub created_at: String,
     #[serde(rename = "type")]
     pub type_: ListingType,
-    pub price: i64,
+    #[serde(with = "crate::money::wire::usd_cents")]
+    pub price: Money,
     pub state: ListingState,
     pub seller: Seller,
     pub item: Item,
     pub is_seller: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub min_offer_price: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default, with = "crate::money::wire::usd_cents_opt")]
+    pub min_offer_price: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_offer_discount: Option<i64>,
     pub is_watchlisted: bool,
@@ -30,22 +33,18 @@ pub struct ListingResponse {
     pub cursor: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Price {
-    pub amount: i64,
-    pub currency: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Reference {
-    pub base_price: Option<i64>,
-    pub predicted_price: Option<i64>,
+    #[serde(default, with = "crate::money::wire::usd_cents_opt")]
+    pub base_price: Option<Money>,
+    #[serde(default, with = "crate::money::wire::usd_cents_opt")]
+    pub predicted_price: Option<Money>,
     pub quantity: Option<i64>,
     pub last_updated: Option<String>,
     pub float_factor: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Item {
     pub asset_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -76,8 +75,8 @@ pub struct Item {
     pub inspect_link: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub has_screenshot: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub scm: Option<Price>,
+    #[serde(skip_serializing_if = "Option::is_none", default, with = "crate::money::wire::csfloat_price_opt")]
+    pub scm: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub item_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -98,7 +97,7 @@ pub struct Item {
     pub type_name: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Seller {
     pub avatar: Option<String>,
     pub flags: i32,
@@ -112,7 +111,7 @@ pub struct Seller {
     pub obfuscated_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SellerStatistics {
     pub median_trade_time: i32,
     pub total_failed_trades: i32,
@@ -121,7 +120,7 @@ pub struct SellerStatistics {
     pub total_avoided_trades: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Sticker {
     #[serde(rename = "stickerId")]
     pub sticker_id: i32,
@@ -130,26 +129,28 @@ pub struct Sticker {
     pub wear: Option<f64>,
     pub icon_url: String,
     pub name: String,
-    pub scm: Option<Price>,
+    #[serde(default, with = "crate::money::wire::csfloat_price_opt")]
+    pub scm: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reference: Option<StickerReference>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StickerReference {
-    pub price: Option<i64>,
+    #[serde(default, with = "crate::money::wire::usd_cents_opt")]
+    pub price: Option<Money>,
     pub quantity: Option<i64>,
     pub updated_at: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ListingType {
     BuyNow,
     Auction,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ListingState {
     Listed,
@@ -1,13 +1,23 @@
-// use chrono::Utc; // No longer needed here
+use chrono::Utc;
 use dotenv::dotenv;
-use log::debug;
+use tracing::{debug, error};
 use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 use ed25519_dalek::{Signer, SigningKey};
 use hex;
+use zeroize::Zeroizing;
 // use serde::{Deserialize, Serialize}; // No longer needed here
 
+use crate::catalog::ItemCatalog;
 use crate::dmarket::error::DMarketError;
+use crate::dmarket::models::{ApiError, ApiResponse};
+use crate::http::{redact, request_span, Cassette, EndpointGroup, HttpSettings, Idempotency, MarketApiError, RateLimiter, RetryPolicy, Secret, Transport};
+use crate::marketplace::Market;
 // use crate::dmarket::models::*; // No longer needed here
 
 // Import handlers
@@ -21,12 +31,43 @@ use crate::dmarket::endpoints::{
 
 pub const API_BASE_URL: &str = "https://api.dmarket.com";
 
+/// The rate limit group of a request path.
+fn endpoint_group(path: &str) -> EndpointGroup {
+    if path.starts_with("/account/") || path.starts_with("/exchange/v1/user/") {
+        EndpointGroup::Account
+    } else if path.starts_with("/trading/")
+        || path.starts_with("/target-predictor/")
+        || path.starts_with("/offers-search/v1/user/")
+    {
+        EndpointGroup::Trading
+    } else {
+        EndpointGroup::MarketData
+    }
+}
+
+/// Parses a DMarket error body: `{"code", "message"}`, possibly wrapped in an `error` field.
+fn api_error(status: StatusCode, body: &str) -> MarketApiError {
+    if let Ok(error) = serde_json::from_str::<ApiError>(body) {
+        return MarketApiError::new(Market::DMarket, status, Some(error.code), error.message);
+    }
+    if let Ok(ApiResponse { error: Some(error), .. }) = serde_json::from_str::<ApiResponse<serde_json::Value>>(body) {
+        return MarketApiError::new(Market::DMarket, status, Some(error.code), error.message);
+    }
+    MarketApiError::from_body(Market::DMarket, status, body)
+}
+
 /// The main DMarket API client.
 /// It provides access to various API endpoint categories through dedicated handlers.
+/// Clones share the rate limiter of the original client. The keys are wiped from memory when the
+/// last clone is dropped and are left out of its `Debug` output.
+#[derive(Clone, Debug)]
 pub struct DMarketClient {
     pub(crate) signing_key: SigningKey,
-    pub(crate) public_key: String,
+    pub(crate) public_key: Secret,
     pub(crate) http_client: reqwest::Client, // Renamed from client
+    pub(crate) base_url: String,
+    pub(crate) catalog: Option<Arc<ItemCatalog>>,
+    pub(crate) transport: Transport,
 }
 
 impl DMarketClient {
@@ -34,58 +75,139 @@ impl DMarketClient {
     /// 
     /// Reads `DMARKET_PRIVATE_KEY` and `DMARKET_PUBLIC_KEY` from environment variables.
     pub fn new() -> Result<Self, DMarketError> {
-        dotenv().ok();
-        let private_key = env::var("DMARKET_PRIVATE_KEY")?;
-        let public_key = env::var("DMARKET_PUBLIC_KEY")?;
+        Self::builder().credentials_from_env()?.build()
+    }
 
-        if private_key.len() < 32 {
-            return Err(DMarketError::ApiError(
-                "Private key is too short".to_string(),
-            ));
-        }
-        if public_key.len() < 32 {
-            return Err(DMarketError::ApiError(
-                "Public key is too short".to_string(),
-            ));
-        }
+    /// Starts configuring a client, e.g. to pass credentials explicitly or to point it at
+    /// another host.
+    pub fn builder() -> DMarketClientBuilder {
+        DMarketClientBuilder::default()
+    }
 
-        debug!(
-            "Initializing DMarket client with public key (first 8 chars): {}...",
-            public_key.chars().take(8).collect::<String>()
-        );
+    /// The API host requests are sent to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
 
-        let private_bytes = hex::decode(&private_key)?;
-        
-        let key_bytes: [u8; 32] = match private_bytes.len() {
-            32 => private_bytes.as_slice().try_into().map_err(|_| {
-                DMarketError::ApiError("Failed to convert private key to fixed-size array".to_string())
-            })?,
-            64 => {
-                let mut seed = [0u8; 32];
-                seed.copy_from_slice(&private_bytes[0..32]);
-                seed
-            },
-            len => {
-                return Err(DMarketError::ApiError(format!(
-                    "Invalid private key length: {}. Expected 32 or 64 bytes", len
-                )));
-            }
-        };
-        
-        let signing_key = SigningKey::from_bytes(&key_bytes);
+    /// Records the items this client sees in `catalog`.
+    pub fn with_catalog(mut self, catalog: Arc<ItemCatalog>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
 
-        Ok(DMarketClient {
-            signing_key,
-            public_key,
-            http_client: reqwest::Client::builder()
-                .user_agent("DMarket-API-Client/1.0")
-                .build()?,
+    pub fn catalog(&self) -> Option<&Arc<ItemCatalog>> {
+        self.catalog.as_ref()
+    }
+
+    /// Retries failed requests according to `retry`. Trading calls sent with `POST` are only
+    /// retried when the connection failed.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.transport.set_retry_policy(retry);
+        self
+    }
+
+    /// Limits requests with `limiter` instead of the market's default quotas. Pass a clone of
+    /// one limiter to several clients to make them share a quota.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.transport.set_rate_limiter(limiter);
+        self
+    }
+
+    /// The limiter of this client, including how long requests waited on it.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        self.transport.rate_limiter()
+    }
+
+    /// Records this client's traffic to `cassette`, or answers requests from it when it is
+    /// replaying.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.transport.set_cassette(cassette);
+        self
+    }
+
+    /// Sends a signed request to the DMarket API and parses the JSON response.
+    ///
+    /// `query` is URL-encoded and appended to `path`, and `body` is sent as JSON. Both are
+    /// covered by the request signature. Non-success statuses are returned as
+    /// [`DMarketError::ApiError`], classified by DMarket's error code.
+    pub async fn request<B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&B>,
+    ) -> Result<T, DMarketError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let text = self.request_text(method, path, query, body).await?;
+        serde_json::from_str(&text).map_err(|e| {
+            debug!("Failed to parse response of {}: {}", path, e);
+            DMarketError::JsonError(e)
         })
     }
 
+    /// Sends a signed GET request without a body. See [`DMarketClient::request`].
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, DMarketError> {
+        self.request(Method::GET, path, query, None::<&()>).await
+    }
+
+    /// Sends a signed request like [`DMarketClient::request`] and returns the raw response body.
+    pub async fn request_text<B>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&B>,
+    ) -> Result<String, DMarketError>
+    where
+        B: Serialize + ?Sized,
+    {
+        let path = if query.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}?{}", path, serde_urlencoded::to_string(query)?)
+        };
+        let body = body.map(serde_json::to_string).transpose()?.unwrap_or_default();
+
+        let url = format!("{}{}", self.base_url, path);
+        debug!("{} request to URL: {}", method, url);
+        if !body.is_empty() {
+            debug!("Request body: {}", redact::body(&body));
+        }
+
+        // Signed again on every attempt so retries carry a fresh timestamp.
+        let idempotency = Idempotency::from(&method);
+        let span = request_span(Market::DMarket, &method, &url);
+        let response = self
+            .transport
+            .send(span, endpoint_group(&path), idempotency, || {
+                let timestamp = Utc::now().timestamp().to_string();
+                let signature = self.generate_signature(&timestamp, method.as_str(), &path, &body)?;
+                let headers = self.create_headers(&timestamp, &signature)?;
+                debug!("Request headers: {:?}", redact::headers(&headers));
+                let request = self.http_client.request(method.clone(), &url).headers(headers);
+                Ok::<_, DMarketError>(if body.is_empty() { request } else { request.body(body.clone()) })
+            })
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            error!("API returned error status {}: {}", status, redact::body(&text));
+            return Err(DMarketError::ApiError(api_error(status, &text)));
+        }
+        debug!("Response of {} bytes with status {}", text.len(), status);
+        Ok(text)
+    }
+
     /// Generates the Ed25519 signature for an API request.
-    /// This method is `pub(crate)` and intended for use by endpoint handlers.
-    pub(crate) fn generate_signature(
+    fn generate_signature(
         &self,
         timestamp: &str,
         method: &str,
@@ -99,18 +221,15 @@ impl DMarketClient {
             path.to_string()
         };
         let message = format!("{}{}{}{}", method, path, body, timestamp);
-        debug!("Generating signature for message: {}", message);
         let signature = self.signing_key.sign(message.as_bytes());
         let signature = format!("dmar ed25519 {}", hex::encode(signature.to_bytes()));
-        debug!("Generated signature: {}", signature);
         Ok(signature)
     }
 
     /// Creates the required HTTP headers for an API request.
-    /// This method is `pub(crate)` and intended for use by endpoint handlers.
-    pub(crate) fn create_headers(&self, timestamp: &str, signature: &str) -> Result<HeaderMap, DMarketError> {
+    fn create_headers(&self, timestamp: &str, signature: &str) -> Result<HeaderMap, DMarketError> {
         let mut headers = HeaderMap::new();
-        headers.insert("X-Api-Key", HeaderValue::from_str(&self.public_key)?);
+        headers.insert("X-Api-Key", HeaderValue::from_str(self.public_key.expose())?);
         headers.insert("X-Request-Sign", HeaderValue::from_str(signature)?);
         headers.insert("X-Sign-Date", HeaderValue::from_str(timestamp)?);
         headers.insert("Content-Type", HeaderValue::from_static("application/json"));
@@ -145,3 +264,114 @@ impl DMarketClient {
         TargetHandler::new(self)
     }
 }
+
+/// Configures a [`DMarketClient`].
+#[derive(Debug, Default)]
+pub struct DMarketClientBuilder {
+    public_key: Option<Secret>,
+    private_key: Option<Secret>,
+    base_url: Option<String>,
+    http: HttpSettings,
+}
+
+impl DMarketClientBuilder {
+    /// Sets the API key pair: the public key and the hex-encoded Ed25519 private key.
+    pub fn credentials(mut self, public_key: impl Into<Secret>, private_key: impl Into<Secret>) -> Self {
+        self.public_key = Some(public_key.into());
+        self.private_key = Some(private_key.into());
+        self
+    }
+
+    /// Reads the key pair from `DMARKET_PUBLIC_KEY` and `DMARKET_PRIVATE_KEY`, loading a `.env`
+    /// file if there is one.
+    pub fn credentials_from_env(self) -> Result<Self, DMarketError> {
+        dotenv().ok();
+        let private_key = env::var("DMARKET_PRIVATE_KEY")?;
+        let public_key = env::var("DMARKET_PUBLIC_KEY")?;
+        Ok(self.credentials(public_key, private_key))
+    }
+
+    /// Sends requests to `base_url` instead of [`API_BASE_URL`], e.g. a mock server.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Timeout of whole requests.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.http.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends requests with `client`. Its own settings are used as is: the timeout, user agent
+    /// and proxy of this builder are ignored.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http.client = Some(client);
+        self
+    }
+
+    pub fn build(self) -> Result<DMarketClient, DMarketError> {
+        let (Some(public_key), Some(private_key)) = (self.public_key, self.private_key) else {
+            return Err(DMarketError::InvalidCredentials("DMarket credentials are not set".to_string()));
+        };
+
+        if private_key.expose().len() < 32 {
+            return Err(DMarketError::InvalidCredentials(
+                "Private key is too short".to_string(),
+            ));
+        }
+        if public_key.expose().len() < 32 {
+            return Err(DMarketError::InvalidCredentials(
+                "Public key is too short".to_string(),
+            ));
+        }
+
+        debug!("Initializing DMarket client");
+
+        // The decoded key is wiped like the hex one; `SigningKey` wipes its own copy on drop.
+        let private_bytes = Zeroizing::new(hex::decode(private_key.expose())?);
+        
+        let key_bytes: Zeroizing<[u8; 32]> = match private_bytes.len() {
+            32 | 64 => {
+                let mut seed = Zeroizing::new([0u8; 32]);
+                seed.copy_from_slice(&private_bytes[0..32]);
+                seed
+            },
+            len => {
+                return Err(DMarketError::InvalidCredentials(format!(
+                    "Invalid private key length: {}. Expected 32 or 64 bytes", len
+                )));
+            }
+        };
+        
+        let signing_key = SigningKey::from_bytes(&key_bytes);
+
+        Ok(DMarketClient {
+            signing_key,
+            public_key,
+            http_client: self.http.build("DMarket-API-Client/1.0", HeaderMap::new())?,
+            base_url: self
+                .base_url
+                .unwrap_or_else(|| API_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            catalog: None,
+            transport: Transport::new(RetryPolicy::default(), RateLimiter::for_market(Market::DMarket)),
+        })
+    }
+}
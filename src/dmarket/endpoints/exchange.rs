@@ -1,378 +1,321 @@
-use crate::dmarket::client::{DMarketClient, API_BASE_URL};
+use crate::dmarket::client::DMarketClient;
 use crate::dmarket::error::DMarketError;
-use crate::dmarket::models::{Game, MarketItem, MarketItemExtra, MarketItemsResponse, Price};
-use chrono::Utc;
-use log::{debug, error};
-use serde::Deserialize;
+use crate::dmarket::models::{
+    AggregatedPrice, AggregatedPricesFilter, AggregatedPricesRequest, AggregatedPricesResponse, Game, LastSale,
+    LastSalesResponse, MarketItem, MarketItemExtra, MarketItemsResponse,
+};
+use crate::dmarket::pagination::{Page, Paginated};
+use crate::dmarket::query::MarketItemsQuery;
+use crate::dmarket::sales::{LastSalesQuery, SaleType, SalesStats};
+use crate::money::{Currency, Money};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use reqwest::Method;
+use serde::{Deserialize, Deserializer};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
-/// Handles exchange and market-related API endpoints.
-pub struct ExchangeHandler<'a> {
-    client: &'a DMarketClient,
+/// Most titles the aggregated prices endpoint accepts in one request.
+pub const AGGREGATED_PRICES_BATCH_SIZE: usize = 100;
+
+/// A market item as returned by the market items endpoints, before conversion to [`MarketItem`].
+#[derive(Debug, Deserialize)]
+struct DMarketMarketItem {
+    #[serde(rename = "itemId")]
+    item_id: String,
+    title: String,
+    #[serde(rename = "gameId")]
+    game_id: String,
+    #[serde(rename = "classId")]
+    class_id: String,
+    price: ApiPrice,
+    #[serde(flatten)]
+    other: HashMap<String, serde_json::Value>,
 }
 
-impl<'a> ExchangeHandler<'a> {
-    /// Creates a new ExchangeHandler.
-    pub fn new(client: &'a DMarketClient) -> Self {
-        Self { client }
-    }
+#[derive(Debug, Deserialize)]
+struct ApiPrice {
+    #[serde(rename = "USD")]
+    usd: String,
+    #[serde(rename = "DMC")]
+    dmc: Option<String>,
+}
 
-    /// Retrieves a list of items available on the market.
-    pub async fn get_market_items(
-        &self,
-        game_id: &str, // Mandatory
-        currency: &str, // Mandatory
-        limit: u32,
-        offset: u32,
-        order_by: Option<&str>,
-        order_dir: Option<&str>,
-        title: Option<&str>,
-        tree_filters: Option<&str>,
-        price_from: Option<u32>,
-        price_to: Option<u32>,
-        types: Option<&str>,
-        cursor: Option<&str>,
-    ) -> Result<MarketItemsResponse, DMarketError> {
-        let timestamp = Utc::now().timestamp().to_string();
-        let method = "GET";
-        
-        let mut params = vec![
-            ("gameId".to_string(), game_id.to_string()),
-            ("currency".to_string(), currency.to_string()),
-            ("limit".to_string(), limit.to_string()),
-            ("offset".to_string(), offset.to_string()),
-        ];
+/// A page of the market items endpoint.
+#[derive(Debug, Deserialize)]
+struct MarketItemsPage {
+    cursor: Option<String>,
+    #[serde(default)]
+    objects: Vec<DMarketMarketItem>,
+    #[serde(default)]
+    items: Vec<DMarketMarketItem>,
+    total: ApiTotal,
+}
 
-        if let Some(val) = order_by {
-            params.push(("orderBy".to_string(), val.to_string()));
-        }
-        if let Some(val) = order_dir {
-            params.push(("orderDir".to_string(), val.to_string()));
-        }
-        if let Some(val) = title {
-            params.push(("title".to_string(), urlencoding::encode(val).into_owned()));
-        }
-        if let Some(val) = tree_filters {
-            params.push(("treeFilters".to_string(), val.to_string()));
-        }
-        if let Some(val) = price_from {
-            params.push(("priceFrom".to_string(), val.to_string()));
-        }
-        if let Some(val) = price_to {
-            params.push(("priceTo".to_string(), val.to_string()));
-        }
-        if let Some(val) = types {
-            params.push(("types".to_string(), val.to_string()));
-        }
-        if let Some(val) = cursor {
-            params.push(("cursor".to_string(), val.to_string()));
-        }
+/// The `total` of a market items page: a count, or counts per kind of which offers are listed.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ApiTotal {
+    Count(String),
+    Counts {
+        #[serde(default)]
+        offers: i64,
+    },
+}
 
-        let query_string = params
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect::<Vec<String>>()
-            .join("&");
-        
-        let path = format!("/exchange/v1/market/items?{}", query_string);
+impl fmt::Display for ApiTotal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiTotal::Count(total) => f.write_str(total),
+            ApiTotal::Counts { offers } => write!(f, "{}", offers),
+        }
+    }
+}
 
-        debug!("Generating signature for market items request, path: {}", path);
-        let signature = self.client.generate_signature(&timestamp, method, &path, "")?;
-        let headers = self.client.create_headers(&timestamp, &signature)?;
+/// A sale as returned by the last sales endpoint, before conversion to [`LastSale`].
+#[derive(Debug, Deserialize)]
+struct ApiLastSale {
+    #[serde(with = "crate::money::wire::usd_decimal")]
+    price: Money,
+    #[serde(deserialize_with = "unix_seconds")]
+    date: DateTime<Utc>,
+    #[serde(rename = "txOperationType")]
+    sale_type: SaleType,
+    #[serde(rename = "offerAttributes", default)]
+    attributes: ApiSaleAttributes,
+}
 
-        debug!("Making request to DMarket API with headers: {:?}", headers);
-        let url = format!("{}{}", API_BASE_URL, path);
-        debug!("Request URL: {}", url);
+#[derive(Debug, Default, Deserialize)]
+struct ApiSaleAttributes {
+    #[serde(rename = "floatValue")]
+    float_value: Option<f64>,
+    #[serde(rename = "paintSeed")]
+    paint_seed: Option<u32>,
+    phase: Option<String>,
+}
 
-        let response = self.client.http_client.get(&url).headers(headers).send().await?;
+#[derive(Debug, Deserialize)]
+struct ApiLastSales {
+    #[serde(default)]
+    sales: Vec<ApiLastSale>,
+}
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("API returned error status {}: {}", status, error_text);
-            return Err(DMarketError::ApiError(format!(
-                "API returned error status {}: {}",
-                status, error_text
-            )));
-        }
+/// A Unix timestamp in seconds, sent as a string.
+fn unix_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    text.parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp {:?}", text)))
+}
 
-        let response_text = response.text().await?;
-        debug!("Market items response: {}", response_text);
-        
-        #[derive(Debug, Deserialize)]
-        struct DMarketMarketItem {
-            #[serde(rename = "itemId")]
-            item_id: String,
-            title: String,
-            #[serde(rename = "gameId")]
-            game_id: String,
-            #[serde(rename = "classId")]
-            class_id: String,
-            price: ApiPrice,
-            #[serde(flatten)]
-            other: HashMap<String, serde_json::Value>,
+impl From<ApiLastSale> for LastSale {
+    fn from(sale: ApiLastSale) -> Self {
+        LastSale {
+            price: sale.price,
+            date: sale.date,
+            sale_type: sale.sale_type,
+            float_value: sale.attributes.float_value,
+            paint_seed: sale.attributes.paint_seed,
+            phase: sale.attributes.phase,
         }
-        
-        #[derive(Debug, Deserialize)]
-        struct ApiPrice {
-            #[serde(rename = "USD")]
-            usd: String,
-            #[serde(rename = "DMC")]
-            dmc: Option<String>,
-        }
-        
-        #[derive(Debug, Deserialize)]
-        struct DMarketResponse {
-            cursor: Option<String>, // Added cursor as per user example
-            #[serde(default)]
-            objects: Vec<DMarketMarketItem>,
-            #[serde(default)]
-            items: Vec<DMarketMarketItem>, 
-            total: String, // Changed from TotalCounts to String
-        }
-        
-        let dmarket_response = match serde_json::from_str::<DMarketResponse>(&response_text) {
-            Ok(response) => response,
-            Err(e) => {
-                debug!("Failed to parse DMarket market items response: {}. Raw: {}", e, response_text);
-                return Err(DMarketError::JsonError(e));
-            }
-        };
-        
-        let items_to_process = if !dmarket_response.objects.is_empty() {
-            dmarket_response.objects
-        } else {
-            dmarket_response.items // Fallback if objects is empty but items is not
-        };
-        
-        let total_items_str = dmarket_response.total;
-        
-        let market_items = items_to_process
-            .into_iter()
-            .map(|item| MarketItem {
-                item_id: item.item_id,
-                item_type: item.other.get("type").and_then(|v| v.as_str()).unwrap_or("offer").to_string(),
-                title: item.title,
-                description: item.other.get("description").and_then(|v| v.as_str()).map(String::from),
-                slug: item.other.get("slug").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                status: item.other.get("status").and_then(|v| v.as_str()).unwrap_or("active").to_string(),
-                ownersCount: item.other.get("ownersCount").and_then(|v| v.as_i64()).map(|v| v as i32),
-                image: item.other.get("image").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                class_id: item.class_id,
-                game: item.other.get("game").and_then(|v| v.as_str()).unwrap_or("").to_string(), 
-                price: Price {
-                    amount: item.price.usd.clone(),
-                    currency: "USD".to_string(), // Assuming currency is USD from ApiPrice, adjust if needed
-                },
-                suggested_price: item.price.dmc.as_ref().map(|dmc_price| Price { amount: dmc_price.clone(), currency: "DMC".to_string() }),
-                discount: item.other.get("discount").and_then(|v| v.as_f64()),
-                extra: MarketItemExtra { 
-                    gameId: Some(item.game_id.clone()), 
-                    name_color: item.other.get("nameColor").and_then(|v| v.as_str()).map(String::from),
-                    background_color: item.other.get("backgroundColor").and_then(|v| v.as_str()).map(String::from),
-                    category: item.other.get("category").and_then(|v| v.as_str()).map(String::from),
-                    exterior: item.other.get("exterior").and_then(|v| v.as_str()).map(String::from),
-                    category_path: item.other.get("categoryPath").and_then(|v| v.as_str()).map(String::from),
-                    tradable: item.other.get("tradable").and_then(|v| v.as_bool()),
-                    daysBeforeTrade: item.other.get("daysBeforeTrade").and_then(|v| v.as_i64()).map(|v| v as i32),
-                    floatValue: item.other.get("floatValue").and_then(|v| v.as_f64()),
-                },
-                attributes: Vec::new(), // Assuming attributes are not directly in this response or need separate handling
-                locked: item.other.get("locked").and_then(|v| v.as_bool()).unwrap_or(false),
-                createdAt: item.other.get("createdAt").and_then(|v| v.as_i64()).unwrap_or(0),
-                updatedAt: item.other.get("updatedAt").and_then(|v| v.as_i64()).unwrap_or(0),
-                inMarket: item.other.get("inMarket").and_then(|v| v.as_bool()).unwrap_or(true),
-                gameId: item.game_id, // This is the gameId from the item itself
-                withdrawable: item.other.get("withdrawable").and_then(|v| v.as_bool()).unwrap_or(true),
-                tradeLock: item.other.get("tradeLock").and_then(|v| v.as_i64()).map(|v| v as i32),
-                offer_type: item.other.get("offerType").and_then(|v| v.as_str()).map(String::from),
-                asset_id: item.other.get("assetId").and_then(|v| v.as_str()).map(String::from),
-            })
-            .collect();
-        
-        Ok(MarketItemsResponse {
-            objects: market_items,
-            total: total_items_str, // Pass the string total
-        })
     }
+}
 
-    /// Searches for market items based on a query.
-    pub async fn search_market_items(&self, query: &str, currency: &str, limit: u32, offset: u32, game_id: Option<&str>) -> Result<MarketItemsResponse, DMarketError> {
-        let timestamp = Utc::now().timestamp().to_string();
-        let method = "GET";
-        
-        let encoded_query = urlencoding::encode(query);
-        let mut path = format!("/exchange/v1/market/items?currency={}&limit={}&offset={}&title={}", currency, limit, offset, encoded_query);
-        
-        if let Some(game_id) = game_id {
-            path = format!("{}&gameId={}", path, game_id);
-        } else {
-            path = format!("{}&gameId=a411", path); 
-        }
+impl TryFrom<DMarketMarketItem> for MarketItem {
+    type Error = DMarketError;
 
-        debug!("Generating signature for market search request: path='{}'", path);
-        let signature = self.client.generate_signature(&timestamp, method, &path, "")?;
-        let headers = self.client.create_headers(&timestamp, &signature)?;
+    fn try_from(item: DMarketMarketItem) -> Result<Self, Self::Error> {
+        Ok(MarketItem {
+            item_id: item.item_id,
+            item_type: item.other.get("type").and_then(|v| v.as_str()).unwrap_or("offer").to_string(),
+            title: item.title,
+            description: item.other.get("description").and_then(|v| v.as_str()).map(String::from),
+            slug: item.other.get("slug").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            status: item.other.get("status").and_then(|v| v.as_str()).unwrap_or("active").to_string(),
+            owners_count: item.other.get("ownersCount").and_then(|v| v.as_i64()).map(|v| v as i32),
+            image: item.other.get("image").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            class_id: item.class_id,
+            game: item.other.get("game").and_then(|v| v.as_str()).unwrap_or("").to_string(), 
+            price: Money::parse_minor(&item.price.usd, Currency::USD)?,
+            suggested_price: item.price.dmc.as_deref().map(|dmc| Money::parse_minor(dmc, Currency::DMC)).transpose()?,
+            discount: item.other.get("discount").and_then(|v| v.as_f64()),
+            extra: MarketItemExtra { 
+                game_id: Some(item.game_id.clone()), 
+                name_color: item.other.get("nameColor").and_then(|v| v.as_str()).map(String::from),
+                background_color: item.other.get("backgroundColor").and_then(|v| v.as_str()).map(String::from),
+                category: item.other.get("category").and_then(|v| v.as_str()).map(String::from),
+                exterior: item.other.get("exterior").and_then(|v| v.as_str()).map(String::from),
+                category_path: item.other.get("categoryPath").and_then(|v| v.as_str()).map(String::from),
+                tradable: item.other.get("tradable").and_then(|v| v.as_bool()),
+                days_before_trade: item.other.get("daysBeforeTrade").and_then(|v| v.as_i64()).map(|v| v as i32),
+                float_value: item.other.get("floatValue").and_then(|v| v.as_f64()),
+            },
+            attributes: Vec::new(), // Assuming attributes are not directly in this response or need separate handling
+            locked: item.other.get("locked").and_then(|v| v.as_bool()).unwrap_or(false),
+            created_at: item.other.get("createdAt").and_then(|v| v.as_i64()).unwrap_or(0),
+            updated_at: item.other.get("updatedAt").and_then(|v| v.as_i64()).unwrap_or(0),
+            in_market: item.other.get("inMarket").and_then(|v| v.as_bool()).unwrap_or(true),
+            game_id: item.game_id, // This is the gameId from the item itself
+            withdrawable: item.other.get("withdrawable").and_then(|v| v.as_bool()).unwrap_or(true),
+            trade_lock: item.other.get("tradeLock").and_then(|v| v.as_i64()).map(|v| v as i32),
+            offer_type: item.other.get("offerType").and_then(|v| v.as_str()).map(String::from),
+            asset_id: item.other.get("assetId").and_then(|v| v.as_str()).map(String::from),
+        })
+    }
+}
 
-        debug!("Making request to DMarket API with headers: {:?}", headers);
-        let url = format!("{}{}", API_BASE_URL, path);
-        debug!("Request URL: {}", url);
+/// Handles exchange and market-related API endpoints.
+pub struct ExchangeHandler<'a> {
+    client: &'a DMarketClient,
+}
 
-        let response = self.client.http_client.get(&url).headers(headers).send().await?;
+impl<'a> ExchangeHandler<'a> {
+    /// Creates a new ExchangeHandler.
+    pub fn new(client: &'a DMarketClient) -> Self {
+        Self { client }
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("API returned error status {}: {}", status, error_text);
-            return Err(DMarketError::ApiError(format!(
-                "API returned error status {}: {}",
-                status, error_text
-            )));
-        }
+    /// Retrieves the items on the market matching `query`.
+    pub async fn get_market_items(&self, query: &MarketItemsQuery) -> Result<MarketItemsResponse, DMarketError> {
+        let page: MarketItemsPage = self.client.get("/exchange/v1/market/items", &query.to_query()?).await?;
 
-        let response_text = response.text().await?;
-        debug!("Search market items response: {}", response_text);
-        
-        #[derive(Debug, Deserialize)]
-        struct DMarketMarketItem {
-            #[serde(rename = "itemId")]
-            item_id: String,
-            title: String,
-            #[serde(rename = "gameId")]
-            game_id: String,
-            #[serde(rename = "classId")]
-            class_id: String,
-            price: ApiPrice,
-            #[serde(flatten)]
-            other: HashMap<String, serde_json::Value>,
-        }
-        
-        #[derive(Debug, Deserialize)]
-        struct ApiPrice {
-            #[serde(rename = "USD")]
-            usd: String,
-            #[serde(rename = "DMC")]
-            dmc: Option<String>,
-        }
-        
-        #[derive(Debug, Deserialize)]
-        #[allow(non_snake_case)]
-        struct TotalCounts {
-            #[serde(default)]
-            offers: i32,
-            #[serde(default)]
-            targets: i32,
-            #[serde(default)]
-            items: i32,
-            #[serde(default)]
-            completedOffers: i32,
-            #[serde(default)]
-            closedTargets: i32,
-        }
-        
-        #[derive(Debug, Deserialize)]
-        struct DMarketResponse {
-            #[serde(default)]
-            objects: Vec<DMarketMarketItem>,
-            #[serde(default)]
-            items: Vec<DMarketMarketItem>,
-            total: TotalCounts,
-        }
-        
-        let dmarket_response = match serde_json::from_str::<DMarketResponse>(&response_text) {
-            Ok(response) => response,
-            Err(e) => {
-                debug!("Failed to parse DMarket search response: {}. Raw: {}", e, response_text);
-                return Err(DMarketError::JsonError(e));
-            }
-        };
-        
-        let items_to_process = if !dmarket_response.objects.is_empty() {
-            dmarket_response.objects
+        let items_to_process = if !page.objects.is_empty() {
+            page.objects
         } else {
-            dmarket_response.items
+            page.items // Fallback if objects is empty but items is not
         };
-        
-        let total_items = dmarket_response.total.offers;
-        
+
         let market_items = items_to_process
             .into_iter()
-            .map(|item| MarketItem {
-                item_id: item.item_id,
-                item_type: item.other.get("type").and_then(|v| v.as_str()).unwrap_or("offer").to_string(),
-                title: item.title,
-                description: item.other.get("description").and_then(|v| v.as_str()).map(String::from),
-                slug: item.other.get("slug").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                status: item.other.get("status").and_then(|v| v.as_str()).unwrap_or("active").to_string(),
-                ownersCount: item.other.get("ownersCount").and_then(|v| v.as_i64()).map(|v| v as i32),
-                image: item.other.get("image").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                class_id: item.class_id,
-                game: item.other.get("game").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                price: Price {
-                    amount: item.price.usd.clone(),
-                    currency: "USD".to_string(),
-                },
-                suggested_price: item.price.dmc.as_ref().map(|dmc_price| Price { amount: dmc_price.clone(), currency: "DMC".to_string() }),
-                discount: item.other.get("discount").and_then(|v| v.as_f64()),
-                extra: MarketItemExtra { 
-                    gameId: Some(item.game_id.clone()),
-                    name_color: item.other.get("nameColor").and_then(|v| v.as_str()).map(String::from),
-                    background_color: item.other.get("backgroundColor").and_then(|v| v.as_str()).map(String::from),
-                    category: item.other.get("category").and_then(|v| v.as_str()).map(String::from),
-                    exterior: item.other.get("exterior").and_then(|v| v.as_str()).map(String::from),
-                    category_path: item.other.get("categoryPath").and_then(|v| v.as_str()).map(String::from),
-                    tradable: item.other.get("tradable").and_then(|v| v.as_bool()),
-                    daysBeforeTrade: item.other.get("daysBeforeTrade").and_then(|v| v.as_i64()).map(|v| v as i32),
-                    floatValue: item.other.get("floatValue").and_then(|v| v.as_f64()),
-                },
-                attributes: Vec::new(), 
-                locked: item.other.get("locked").and_then(|v| v.as_bool()).unwrap_or(false),
-                createdAt: item.other.get("createdAt").and_then(|v| v.as_i64()).unwrap_or(0),
-                updatedAt: item.other.get("updatedAt").and_then(|v| v.as_i64()).unwrap_or(0),
-                inMarket: item.other.get("inMarket").and_then(|v| v.as_bool()).unwrap_or(true),
-                gameId: item.game_id,
-                withdrawable: item.other.get("withdrawable").and_then(|v| v.as_bool()).unwrap_or(true),
-                tradeLock: item.other.get("tradeLock").and_then(|v| v.as_i64()).map(|v| v as i32),
-                offer_type: item.other.get("offerType").and_then(|v| v.as_str()).map(String::from),
-                asset_id: item.other.get("assetId").and_then(|v| v.as_str()).map(String::from),
-            })
-            .collect();
+            .map(MarketItem::try_from)
+            .collect::<Result<Vec<_>, DMarketError>>()?;
+
+        if let Some(catalog) = &self.client.catalog {
+            for item in &market_items {
+                catalog.record_dmarket(&item.title, &item.class_id);
+            }
+        }
         
         Ok(MarketItemsResponse {
             objects: market_items,
-            total: total_items.to_string(), // Convert i32 to String to match updated MarketItemsResponse
+            total: page.total.to_string(),
+            cursor: page.cursor.filter(|cursor| !cursor.is_empty()),
         })
     }
 
-    /// Retrieves a list of available games.
-    pub async fn get_games(&self) -> Result<Vec<Game>, DMarketError> {
-        let timestamp = Utc::now().timestamp().to_string();
-        let method = "GET";
-        let path = "/exchange/v1/games";
-        
-        debug!("Generating signature for games request");
-        let signature = self.client.generate_signature(&timestamp, method, path, "")?;
-        let headers = self.client.create_headers(&timestamp, &signature)?;
+    /// Streams every item matching `query`, starting at its cursor and requesting pages of its
+    /// limit.
+    pub fn stream_market_items(&self, query: MarketItemsQuery) -> Paginated<MarketItem> {
+        let client = self.client.clone();
+        let start = query.cursor.clone();
+        let stream = Paginated::new(query.limit, move |limit, cursor| {
+            let client = client.clone();
+            let mut query = query.clone().limit(limit);
+            if let Some(cursor) = cursor {
+                query = query.offset(0).cursor(cursor);
+            }
+            Box::pin(async move {
+                let page = client.exchange().get_market_items(&query).await?;
+                Ok(Page::new(page.objects, page.cursor))
+            })
+        });
+        match start {
+            Some(cursor) => stream.resume_from(cursor),
+            None => stream,
+        }
+    }
+
+    /// Retrieves the best offer and order prices of each of `titles` in game `game_id`.
+    ///
+    /// Titles are sent in batches of [`AGGREGATED_PRICES_BATCH_SIZE`]. Titles DMarket knows
+    /// nothing about are missing from the result.
+    pub async fn get_aggregated_prices<S: AsRef<str>>(&self, game_id: &str, titles: &[S]) -> Result<Vec<AggregatedPrice>, DMarketError> {
+        let mut prices = Vec::new();
+        for batch in titles.chunks(AGGREGATED_PRICES_BATCH_SIZE) {
+            let mut request = AggregatedPricesRequest {
+                filter: AggregatedPricesFilter {
+                    game: game_id.to_string(),
+                    titles: batch.iter().map(|title| title.as_ref().to_string()).collect(),
+                },
+                limit: AGGREGATED_PRICES_BATCH_SIZE.to_string(),
+                cursor: String::new(),
+            };
+            loop {
+                let page: AggregatedPricesResponse = self
+                    .client
+                    .request(Method::POST, "/marketplace-api/v1/aggregated-prices", &[], Some(&request))
+                    .await?;
+                // DMarket reports a zero price for titles without offers or orders.
+                prices.extend(page.aggregated_prices.into_iter().map(|mut price| {
+                    price.offer_best_price = price.offer_best_price.filter(|_| price.offer_count > 0);
+                    price.order_best_price = price.order_best_price.filter(|_| price.order_count > 0);
+                    price
+                }));
+                if page.next_cursor.is_empty() {
+                    break;
+                }
+                request.cursor = page.next_cursor;
+            }
+        }
+        Ok(prices)
+    }
 
-        debug!("Making request to DMarket API with headers: {:?}", headers);
-        let url = format!("{}{}", API_BASE_URL, path);
-        debug!("Request URL: {}", url);
+    /// Retrieves the latest sales matching `query`, newest first.
+    pub async fn get_last_sales(&self, query: &LastSalesQuery) -> Result<LastSalesResponse, DMarketError> {
+        let page: ApiLastSales = self.client.get("/trade-aggregator/v1/last-sales", &query.to_query()?).await?;
+        let sales = page.sales.into_iter().map(LastSale::from).collect();
+        Ok(LastSalesResponse { sales })
+    }
 
-        let response = self.client.http_client.get(&url).headers(headers).send().await?;
+    /// Streams every sale matching `query`, newest first, requesting pages of its limit.
+    ///
+    /// DMarket may serve fewer sales than requested before the last page, so the stream only
+    /// ends on an empty page.
+    pub fn stream_last_sales(&self, query: LastSalesQuery) -> Paginated<LastSale> {
+        let client = self.client.clone();
+        // The endpoint pages by offset, which serves as the cursor.
+        let start = query.offset;
+        Paginated::new(query.limit, move |limit, cursor| {
+            let client = client.clone();
+            let offset = cursor.and_then(|cursor| cursor.parse().ok()).unwrap_or(start);
+            let query = query.clone().limit(limit).offset(offset);
+            Box::pin(async move {
+                let page = client.exchange().get_last_sales(&query).await?;
+                let fetched = page.sales.len() as u32;
+                let next = (fetched > 0).then(|| (offset + fetched).to_string());
+                Ok(Page::new(page.sales, next))
+            })
+        })
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("API returned error status {}: {}", status, error_text);
-            return Err(DMarketError::ApiError(format!(
-                "API returned error status {}: {}",
-                status, error_text
-            )));
+    /// Computes statistics over the sales matching `query` made at or after `since`, paging
+    /// back through the history only as far as needed.
+    pub async fn last_sales_stats(&self, query: LastSalesQuery, since: DateTime<Utc>) -> Result<SalesStats, DMarketError> {
+        let mut stream = self.stream_last_sales(query);
+        let mut sales = Vec::new();
+        while let Some(sale) = stream.try_next().await? {
+            if sale.date < since {
+                break;
+            }
+            sales.push(sale);
         }
+        Ok(SalesStats::from_sales(&sales)?)
+    }
 
-        let response_text = response.text().await?;
-        debug!("Games response: {}", response_text);
-        
+    /// Searches for market items whose title contains `query`, in game `game_id` or `"a411"`.
+    pub async fn search_market_items(&self, query: &str, currency: &str, limit: u32, offset: u32, game_id: Option<&str>) -> Result<MarketItemsResponse, DMarketError> {
+        let query = MarketItemsQuery::new(game_id.unwrap_or("a411"))
+            .currency(Currency::from_str(currency)?)
+            .limit(limit)
+            .offset(offset)
+            .title(query);
+        self.get_market_items(&query).await
+    }
+
+    /// Retrieves a list of available games.
+    pub async fn get_games(&self) -> Result<Vec<Game>, DMarketError> {
         #[derive(Debug, Deserialize)]
         struct DMarketApiGameItem {
             id: String,
@@ -402,57 +345,30 @@ impl<'a> ExchangeHandler<'a> {
             total: i32,
         }
         
-        match serde_json::from_str::<DMarketGamesApiResponse>(&response_text) {
-            Ok(api_response) => {
-                let games = api_response.objects
-                    .into_iter()
-                    .map(|api_game| {
-                        Game { 
-                            id: api_game.id,
-                            title: api_game.title,
-                            logo: api_game.logo_image_url.unwrap_or_default(),
-                            slug: api_game.slug.unwrap_or_default(),
-                            status: api_game.other.get("status").and_then(|s| s.as_str()).unwrap_or("active").to_string(),
-                        }
-                    })
-                    .collect();
-                Ok(games)
-            },
-            Err(e) => {
-                error!("Failed to parse games response: {}. Raw text: {}", e, response_text);
-                Err(DMarketError::JsonError(e))
-            }
-        }
+        let api_response: DMarketGamesApiResponse = self.client.get("/exchange/v1/games", &[]).await?;
+        let games = api_response.objects
+            .into_iter()
+            .map(|api_game| {
+                Game { 
+                    id: api_game.id,
+                    title: api_game.title,
+                    logo: api_game.logo_image_url.unwrap_or_default(),
+                    slug: api_game.slug.unwrap_or_default(),
+                    status: api_game.other.get("status").and_then(|s| s.as_str()).unwrap_or("active").to_string(),
+                }
+            })
+            .collect();
+        Ok(games)
     }
 
     /// Retrieves details for a specific market item class.
     pub async fn get_market_item_details(&self, class_id: &str, limit: u32, offset: u32, currency: &str) -> Result<MarketItemsResponse, DMarketError> {
-        let timestamp = Utc::now().timestamp().to_string();
-        let method = "GET";
-        let path = format!("/offers-search/v1/aggregated-class/{}/sell-offers?limit={}&offset={}&currency={}", class_id, limit, offset, currency);
-
-        debug!("Generating signature for market item details request");
-        let signature = self.client.generate_signature(&timestamp, method, &path, "")?;
-        let headers = self.client.create_headers(&timestamp, &signature)?;
-
-        debug!("Making request to DMarket API with headers: {:?}", headers);
-        let url = format!("{}{}", API_BASE_URL, path);
-        debug!("Request URL: {}", url);
-
-        let response = self.client.http_client.get(&url).headers(headers).send().await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("API returned error status {}: {}", status, error_text);
-            return Err(DMarketError::ApiError(format!(
-                "API returned error status {}: {}",
-                status, error_text
-            )));
-        }
-        
-        // If status is success, response is not consumed yet.
-        let item_details = response.json::<MarketItemsResponse>().await?;
-        Ok(item_details)
+        let path = format!("/offers-search/v1/aggregated-class/{}/sell-offers", class_id);
+        let query = [
+            ("limit", limit.to_string()),
+            ("offset", offset.to_string()),
+            ("currency", currency.to_string()),
+        ];
+        self.client.get(&path, &query).await
     }
-} 
\ No newline at end of file
+}
@@ -1,8 +1,42 @@
-use crate::dmarket::client::{DMarketClient, API_BASE_URL};
+use crate::dmarket::client::DMarketClient;
 use crate::dmarket::error::DMarketError;
-use crate::dmarket::models::{CreateTargetRequest, CreateTargetResponse, DeleteTargetRequest, TargetListResponse};
-use chrono::Utc;
-use log::{debug, error};
+use crate::dmarket::models::{BuyOrder, CreateTargetRequest, CreateTargetResponse, DeleteTargetRequest, Target, TargetListResponse};
+use crate::dmarket::order_book::{OrderBook, OrderBookQuery};
+use crate::dmarket::pagination::{Page, Paginated, DEFAULT_PAGE_SIZE};
+use crate::money::Money;
+use reqwest::Method;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A buy order as returned by the targets by title endpoint, before conversion to [`BuyOrder`].
+#[derive(Debug, Deserialize)]
+struct ApiBuyOrder {
+    title: String,
+    #[serde(with = "crate::money::wire::usd_cents")]
+    price: Money,
+    amount: u32,
+    #[serde(default)]
+    attributes: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiBuyOrders {
+    #[serde(default)]
+    orders: Vec<ApiBuyOrder>,
+}
+
+impl From<ApiBuyOrder> for BuyOrder {
+    fn from(mut order: ApiBuyOrder) -> Self {
+        BuyOrder {
+            price: order.price,
+            quantity: order.amount,
+            phase: order.attributes.remove("phase"),
+            float_part_value: order.attributes.remove("floatPartValue"),
+            paint_seed: order.attributes.remove("paintSeed"),
+            title: order.title,
+        }
+    }
+}
 
 /// Handles target-related API endpoints (create, list, delete targets).
 pub struct TargetHandler<'a> {
@@ -17,109 +51,64 @@ impl<'a> TargetHandler<'a> {
 
     /// Creates one or more new targets.
     pub async fn create_target(&self, request: &CreateTargetRequest) -> Result<CreateTargetResponse, DMarketError> {
-        let timestamp = Utc::now().timestamp().to_string();
-        let method = "POST";
-        let path = "/target-predictor/v1/target";
-        let body = serde_json::to_string(request)?;
-
-        debug!("Generating signature for create target request");
-        let signature = self.client.generate_signature(&timestamp, method, path, &body)?;
-        let headers = self.client.create_headers(&timestamp, &signature)?;
-
-        debug!("Making request to DMarket API with headers: {:?}", headers);
-        let url = format!("{}{}", API_BASE_URL, path);
-        debug!("Request URL: {}", url);
-        debug!("Request body: {}", body);
-
-        let response = self.client.http_client.post(&url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("API returned error status {}: {}", status, error_text);
-            return Err(DMarketError::ApiError(format!(
-                "API returned error status {}: {}",
-                status, error_text
-            )));
-        }
-
-        let create_response = response.json::<CreateTargetResponse>().await?;
-        Ok(create_response)
+        self.client
+            .request(Method::POST, "/target-predictor/v1/target", &[], Some(request))
+            .await
     }
 
     /// Retrieves a list of the user's targets.
     pub async fn get_targets(&self, limit: u32, offset: u32) -> Result<TargetListResponse, DMarketError> {
-        let timestamp = Utc::now().timestamp().to_string();
-        let method = "GET";
-        let path = format!("/target-predictor/v1/user/targets?limit={}&offset={}", limit, offset);
-
-        debug!("Generating signature for get targets request");
-        let signature = self.client.generate_signature(&timestamp, method, &path, "")?;
-        let headers = self.client.create_headers(&timestamp, &signature)?;
+        self.targets_page(limit, offset, None).await
+    }
 
-        debug!("Making request to DMarket API with headers: {:?}", headers);
-        let url = format!("{}{}", API_BASE_URL, path);
-        debug!("Request URL: {}", url);
+    /// Retrieves the buy orders of all users for the title of `query`, keeps those matching its
+    /// attributes and aggregates them by price, highest first.
+    pub async fn get_order_book(&self, query: &OrderBookQuery) -> Result<OrderBook, DMarketError> {
+        query.check()?;
+        let path = format!(
+            "/marketplace-api/v1/targets-by-title/{}/{}",
+            query.game_id(),
+            urlencoding::encode(query.title())
+        );
+        let response: ApiBuyOrders = self.client.get(&path, &[]).await?;
+        let orders = response
+            .orders
+            .into_iter()
+            .map(BuyOrder::from)
+            .filter(|order| query.matches(order));
+        Ok(OrderBook::from_orders(query.title(), orders)?)
+    }
 
-        let response = self.client.http_client.get(&url).headers(headers).send().await?;
+    /// Streams all of the user's targets.
+    pub fn stream_targets(&self) -> Paginated<Target> {
+        let client = self.client.clone();
+        Paginated::new(DEFAULT_PAGE_SIZE, move |limit, cursor| {
+            let client = client.clone();
+            Box::pin(async move {
+                let page = client.target().targets_page(limit, 0, cursor.as_deref()).await?;
+                Ok(Page::new(page.objects, page.cursor))
+            })
+        })
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("API returned error status {}: {}", status, error_text);
-            return Err(DMarketError::ApiError(format!(
-                "API returned error status {}: {}",
-                status, error_text
-            )));
+    async fn targets_page(&self, limit: u32, offset: u32, cursor: Option<&str>) -> Result<TargetListResponse, DMarketError> {
+        let mut query = vec![("limit", limit.to_string()), ("offset", offset.to_string())];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor.to_string()));
         }
-
-        let targets = response.json::<TargetListResponse>().await?;
-        Ok(targets)
+        let mut page: TargetListResponse = self.client.get("/target-predictor/v1/user/targets", &query).await?;
+        page.cursor = page.cursor.filter(|cursor| !cursor.is_empty());
+        Ok(page)
     }
 
     /// Deletes one or more targets.
     /// Note: DMarket API for deleting targets uses the HTTP DELETE method.
     pub async fn delete_targets(&self, target_ids: Vec<String>) -> Result<CreateTargetResponse, DMarketError> {
-        let timestamp = Utc::now().timestamp().to_string();
-        let method = "DELETE"; // Correct HTTP method for delete
-        let path = "/target-predictor/v1/target";
-        
-        // DeleteTargetRequest is defined in models.rs
-        let request_payload = DeleteTargetRequest { targets: target_ids };
-        let body = serde_json::to_string(&request_payload)?;
-
-        debug!("Generating signature for delete targets request");
-        let signature = self.client.generate_signature(&timestamp, method, path, &body)?;
-        let headers = self.client.create_headers(&timestamp, &signature)?;
-
-        debug!("Making request to DMarket API with headers: {:?}", headers);
-        let url = format!("{}{}", API_BASE_URL, path);
-        debug!("Request URL: {}", url);
-        debug!("Request body: {}", body);
-
-        let response = self.client.http_client.delete(&url) // Using .delete() method
-            .headers(headers)
-            .body(body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("API returned error status {}: {}", status, error_text);
-            return Err(DMarketError::ApiError(format!(
-                "API returned error status {}: {}",
-                status, error_text
-            )));
-        }
+        let request = DeleteTargetRequest { targets: target_ids };
         // DMarket might return a specific response for delete; CreateTargetResponse is a placeholder if not.
-        // Often, successful DELETE operations might return 204 No Content or a summary response.
         // Assuming CreateTargetResponse is what DMarket returns based on original client structure.
-        let delete_response = response.json::<CreateTargetResponse>().await?;
-        Ok(delete_response)
+        self.client
+            .request(Method::DELETE, "/target-predictor/v1/target", &[], Some(&request))
+            .await
     }
-} 
\ No newline at end of file
+}
@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+use crate::http::{CassetteError, MarketApiError};
+use crate::money::MoneyError;
+
 #[derive(Error, Debug)]
 pub enum DMarketError {
     #[error("Environment error: {0}")]
@@ -11,6 +14,9 @@ pub enum DMarketError {
     #[error("JSON serialization error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("URL encoding error: {0}")]
+    UrlEncodingError(#[from] serde_urlencoded::ser::Error),
+
     #[error("Header error: {0}")]
     HeaderError(#[from] reqwest::header::InvalidHeaderValue),
 
@@ -21,5 +27,17 @@ pub enum DMarketError {
     HexError(#[from] hex::FromHexError),
 
     #[error("API error: {0}")]
-    ApiError(String),
-} 
\ No newline at end of file
+    ApiError(MarketApiError),
+
+    #[error("Invalid credentials: {0}")]
+    InvalidCredentials(String),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("Cassette error: {0}")]
+    CassetteError(#[from] CassetteError),
+
+    #[error("Invalid price: {0}")]
+    MoneyError(#[from] MoneyError),
+}
@@ -4,6 +4,10 @@ pub mod client;
 pub mod error;
 pub mod models;
 pub mod endpoints;
+pub mod order_book;
+pub mod pagination;
+pub mod query;
+pub mod sales;
 
 // For convenience, re-export main client and error types if desired,
 // or users can access them via client::DMarketClient and error::DMarketError.
@@ -15,12 +19,14 @@ pub use models::*;
 
 pub use models::{
     UserProfile, Balance, ApiResponse, ApiError,
-    MarketItemsResponse, MarketItem, Price, MarketItemExtra, MarketItemAttribute,
+    MarketItemsResponse, MarketItem, MarketItemExtra, MarketItemAttribute,
+    AggregatedPricesRequest, AggregatedPricesFilter, AggregatedPricesResponse, AggregatedPrice,
+    LastSalesResponse, LastSale,
     Game,
     InventoryResponse, InventoryItem, InventoryItemExtra,
-    SellOfferRequest, SellOfferItem, SellOfferPrice, SellOfferResponse, SellOfferResponseItem,
+    SellOfferRequest, SellOfferItem, SellOfferResponse, SellOfferResponseItem,
     BuyOfferRequest, BuyOfferResponse, BuyOfferResponseItem,
     ImportRequest, ImportItem, ImportResponse, ImportedItem,
     ExportRequest, ExportResponse, ExportedItem,
-    Target, CreateTargetRequest, CreateTargetResponse, CreateTargetResponseItem, TargetListResponse, DeleteTargetRequest,
+    Target, BuyOrder, CreateTargetRequest, CreateTargetResponse, CreateTargetResponseItem, TargetListResponse, DeleteTargetRequest,
 }; 
\ No newline at end of file
@@ -1,5 +1,9 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::dmarket::sales::SaleType;
+use crate::money::{Currency, Money};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserProfile {
     pub id: String,
@@ -133,16 +137,11 @@ pub struct Storefront {
     pub alias: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Price {
-    pub amount: String,
-    pub currency: String,
-}
-
+/// Balance of one currency on the DMarket account.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Balance {
-    pub currency: String,
-    pub amount: String,
+    pub amount: Money,
+    pub available_to_withdraw: Option<Money>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -166,9 +165,12 @@ pub struct ApiError {
 pub struct MarketItemsResponse {
     pub objects: Vec<MarketItem>,
     pub total: String,
+    /// Where the next page starts; `None` on the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketItem {
     #[serde(rename = "itemId")]
     pub item_id: String,
@@ -178,31 +180,38 @@ pub struct MarketItem {
     pub description: Option<String>,
     pub slug: String,
     pub status: String,
-    pub ownersCount: Option<i32>,
+    #[serde(rename = "ownersCount")]
+    pub owners_count: Option<i32>,
     pub image: String,
     #[serde(rename = "classId")]
     pub class_id: String,
     pub game: String,
-    pub price: Price,
-    #[serde(rename = "suggestedPrice")]
-    pub suggested_price: Option<Price>,
+    #[serde(with = "crate::money::wire::dmarket_price")]
+    pub price: Money,
+    #[serde(rename = "suggestedPrice", default, with = "crate::money::wire::dmarket_price_opt")]
+    pub suggested_price: Option<Money>,
     pub discount: Option<f64>,
     pub extra: MarketItemExtra,
     pub attributes: Vec<MarketItemAttribute>,
     pub locked: bool,
-    pub createdAt: i64,
-    pub updatedAt: i64,
-    pub inMarket: bool,
-    pub gameId: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+    #[serde(rename = "inMarket")]
+    pub in_market: bool,
+    #[serde(rename = "gameId")]
+    pub game_id: String,
     pub withdrawable: bool,
-    pub tradeLock: Option<i32>,
+    #[serde(rename = "tradeLock")]
+    pub trade_lock: Option<i32>,
     #[serde(rename = "offerType")]
     pub offer_type: Option<String>,
     #[serde(rename = "assetId")]
     pub asset_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketItemExtra {
     #[serde(rename = "nameColor")]
     pub name_color: Option<String>,
@@ -213,12 +222,15 @@ pub struct MarketItemExtra {
     #[serde(rename = "categoryPath")]
     pub category_path: Option<String>,
     pub tradable: Option<bool>,
-    pub daysBeforeTrade: Option<i32>,
-    pub floatValue: Option<f64>,
-    pub gameId: Option<String>,
+    #[serde(rename = "daysBeforeTrade")]
+    pub days_before_trade: Option<i32>,
+    #[serde(rename = "floatValue")]
+    pub float_value: Option<f64>,
+    #[serde(rename = "gameId")]
+    pub game_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketItemAttribute {
     pub name: String,
     pub category: String,
@@ -227,6 +239,64 @@ pub struct MarketItemAttribute {
     pub display_value: Option<String>,
 }
 
+// Aggregated price models
+/// Body of the aggregated prices endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AggregatedPricesRequest {
+    pub filter: AggregatedPricesFilter,
+    pub limit: String,
+    pub cursor: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AggregatedPricesFilter {
+    pub game: String,
+    pub titles: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AggregatedPricesResponse {
+    #[serde(rename = "aggregatedPrices", default)]
+    pub aggregated_prices: Vec<AggregatedPrice>,
+    /// Empty on the last page.
+    #[serde(rename = "nextCursor", default)]
+    pub next_cursor: String,
+}
+
+/// The cheapest offer and the highest order (target) of one title, with the number of each.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregatedPrice {
+    pub title: String,
+    /// `None` when the title has no offers.
+    #[serde(rename = "offerBestPrice", default, with = "crate::money::wire::dmarket_price_opt")]
+    pub offer_best_price: Option<Money>,
+    #[serde(rename = "offerCount", default)]
+    pub offer_count: u32,
+    /// `None` when the title has no orders.
+    #[serde(rename = "orderBestPrice", default, with = "crate::money::wire::dmarket_price_opt")]
+    pub order_best_price: Option<Money>,
+    #[serde(rename = "orderCount", default)]
+    pub order_count: u32,
+}
+
+// Sales models
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LastSalesResponse {
+    pub sales: Vec<LastSale>,
+}
+
+/// A completed sale of an item, newest first in [`LastSalesResponse`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastSale {
+    pub price: Money,
+    pub date: DateTime<Utc>,
+    pub sale_type: SaleType,
+    pub float_value: Option<f64>,
+    pub paint_seed: Option<u32>,
+    /// Doppler phase, as DMarket names it, e.g. `"phase-2"`.
+    pub phase: Option<String>,
+}
+
 // Game models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Game {
@@ -242,6 +312,9 @@ pub struct Game {
 pub struct InventoryResponse {
     pub objects: Vec<InventoryItem>,
     pub total: i32,
+    /// Where the next page starts; `None` on the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -255,7 +328,8 @@ pub struct InventoryItem {
     #[serde(rename = "classId")]
     pub class_id: String,
     pub extra: InventoryItemExtra,
-    pub inMarket: bool,
+    #[serde(rename = "inMarket")]
+    pub in_market: bool,
     pub locked: bool,
 }
 
@@ -270,8 +344,10 @@ pub struct InventoryItemExtra {
     #[serde(rename = "backgroundColor")]
     pub background_color: Option<String>,
     pub tradable: Option<bool>,
-    pub daysBeforeTrade: Option<i32>,
-    pub floatValue: Option<f64>,
+    #[serde(rename = "daysBeforeTrade")]
+    pub days_before_trade: Option<i32>,
+    #[serde(rename = "floatValue")]
+    pub float_value: Option<f64>,
 }
 
 // Trading models
@@ -284,104 +360,137 @@ pub struct SellOfferRequest {
 pub struct SellOfferItem {
     #[serde(rename = "assetId")]
     pub asset_id: String,
-    pub price: SellOfferPrice,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SellOfferPrice {
-    pub amount: String,
-    pub currency: String,
+    #[serde(with = "crate::money::wire::dmarket_price")]
+    pub price: Money,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SellOfferResponse {
-    pub Items: Vec<SellOfferResponseItem>,
-    pub HasErrors: bool,
-    pub TotalSucceed: i32,
-    pub TotalFailed: i32,
+    #[serde(rename = "Items")]
+    pub items: Vec<SellOfferResponseItem>,
+    #[serde(rename = "HasErrors")]
+    pub has_errors: bool,
+    #[serde(rename = "TotalSucceed")]
+    pub total_succeed: i32,
+    #[serde(rename = "TotalFailed")]
+    pub total_failed: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SellOfferResponseItem {
-    pub Status: String,
-    pub AssetID: String,
-    pub OfferID: Option<String>,
-    pub ErrorCode: Option<String>,
-    pub ErrorMessage: Option<String>,
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "AssetID")]
+    pub asset_id: String,
+    #[serde(rename = "OfferID")]
+    pub offer_id: Option<String>,
+    #[serde(rename = "ErrorCode")]
+    pub error_code: Option<String>,
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: Option<String>,
 }
 
 // Buy offer models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuyOfferRequest {
     pub offers: Vec<String>, // List of offer IDs
-    pub currency: String,    // USD, DMC, etc.
+    pub currency: Currency,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuyOfferResponse {
-    pub Items: Vec<BuyOfferResponseItem>,
-    pub HasErrors: bool,
-    pub TotalSucceed: i32,
-    pub TotalFailed: i32,
+    #[serde(rename = "Items")]
+    pub items: Vec<BuyOfferResponseItem>,
+    #[serde(rename = "HasErrors")]
+    pub has_errors: bool,
+    #[serde(rename = "TotalSucceed")]
+    pub total_succeed: i32,
+    #[serde(rename = "TotalFailed")]
+    pub total_failed: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuyOfferResponseItem {
-    pub Status: String,
-    pub OfferID: String,
-    pub ErrorCode: Option<String>,
-    pub ErrorMessage: Option<String>,
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "OfferID")]
+    pub offer_id: String,
+    #[serde(rename = "ErrorCode")]
+    pub error_code: Option<String>,
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: Option<String>,
 }
 
 // Import/Export models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ImportRequest {
-    pub gameId: String,
+    #[serde(rename = "gameId")]
+    pub game_id: String,
     pub items: Vec<ImportItem>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ImportItem {
-    pub appId: String,
-    pub contextId: String,
-    pub assetId: String,
+    #[serde(rename = "appId")]
+    pub app_id: String,
+    #[serde(rename = "contextId")]
+    pub context_id: String,
+    #[serde(rename = "assetId")]
+    pub asset_id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ImportResponse {
-    pub OperationID: String,
-    pub SteamTradeID: String,
-    pub SteamTradeState: String,
-    pub Items: Vec<ImportedItem>,
+    #[serde(rename = "OperationID")]
+    pub operation_id: String,
+    #[serde(rename = "SteamTradeID")]
+    pub steam_trade_id: String,
+    #[serde(rename = "SteamTradeState")]
+    pub steam_trade_state: String,
+    #[serde(rename = "Items")]
+    pub items: Vec<ImportedItem>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ImportedItem {
-    pub DMarketAssetID: String,
-    pub SteamAppID: String,
-    pub SteamContextID: String,
-    pub SteamAssetID: String,
+    #[serde(rename = "DMarketAssetID")]
+    pub dmarket_asset_id: String,
+    #[serde(rename = "SteamAppID")]
+    pub steam_app_id: String,
+    #[serde(rename = "SteamContextID")]
+    pub steam_context_id: String,
+    #[serde(rename = "SteamAssetID")]
+    pub steam_asset_id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportRequest {
-    pub gameId: String,
-    pub assetIds: Vec<String>,
+    #[serde(rename = "gameId")]
+    pub game_id: String,
+    #[serde(rename = "assetIds")]
+    pub asset_ids: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportResponse {
-    pub OperationID: String,
-    pub SteamTradeID: String,
-    pub State: String,
-    pub Assets: Vec<ExportedItem>,
-    pub ErrorMessage: String,
+    #[serde(rename = "OperationID")]
+    pub operation_id: String,
+    #[serde(rename = "SteamTradeID")]
+    pub steam_trade_id: String,
+    #[serde(rename = "State")]
+    pub state: String,
+    #[serde(rename = "Assets")]
+    pub assets: Vec<ExportedItem>,
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportedItem {
-    pub DMarketAssetID: String,
-    pub Title: String,
+    #[serde(rename = "DMarketAssetID")]
+    pub dmarket_asset_id: String,
+    #[serde(rename = "Title")]
+    pub title: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -394,7 +503,8 @@ pub struct Target {
     #[serde(rename = "classId")]
     pub class_id: String,
     pub title: Option<String>,
-    pub price: Option<Price>,
+    #[serde(default, with = "crate::money::wire::dmarket_price_opt")]
+    pub price: Option<Money>,
     pub status: Option<String>,
     #[serde(rename = "createdAt")]
     pub created_at: Option<i64>,
@@ -408,6 +518,19 @@ pub struct Target {
     pub paint_seed: Option<String>,
 }
 
+/// A buy order (target) of any user, as listed in the order book of its title.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuyOrder {
+    pub title: String,
+    pub price: Money,
+    /// Number of items requested.
+    pub quantity: u32,
+    /// Attributes the order restricts; `None` when any value is accepted.
+    pub phase: Option<String>,
+    pub float_part_value: Option<String>,
+    pub paint_seed: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTargetRequest {
     pub targets: Vec<Target>,
@@ -415,24 +538,35 @@ pub struct CreateTargetRequest {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTargetResponse {
-    pub Items: Vec<CreateTargetResponseItem>,
-    pub HasErrors: bool,
-    pub TotalSucceed: i32,
-    pub TotalFailed: i32,
+    #[serde(rename = "Items")]
+    pub items: Vec<CreateTargetResponseItem>,
+    #[serde(rename = "HasErrors")]
+    pub has_errors: bool,
+    #[serde(rename = "TotalSucceed")]
+    pub total_succeed: i32,
+    #[serde(rename = "TotalFailed")]
+    pub total_failed: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTargetResponseItem {
-    pub Status: String,
-    pub TargetID: Option<String>,
-    pub ErrorCode: Option<String>,
-    pub ErrorMessage: Option<String>,
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "TargetID")]
+    pub target_id: Option<String>,
+    #[serde(rename = "ErrorCode")]
+    pub error_code: Option<String>,
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TargetListResponse {
     pub objects: Vec<Target>,
     pub total: i32,
+    /// Where the next page starts; `None` on the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,148 @@
+//! Market-wide buy orders (targets) of a title, aggregated into price levels.
+
+use std::collections::BTreeMap;
+
+use crate::dmarket::error::DMarketError;
+use crate::dmarket::models::BuyOrder;
+use crate::dmarket::query::phase_slug;
+use crate::identity::DopplerPhase;
+use crate::money::{Money, MoneyError};
+
+/// Parameters of [`TargetHandler::get_order_book`](crate::dmarket::endpoints::target::TargetHandler::get_order_book).
+///
+/// Attribute filters describe the item to be sold: an order counts when every attribute it
+/// restricts matches, and orders that leave an attribute open match any value of it.
+///
+/// ```
+/// use backend::{DopplerPhase, OrderBookQuery};
+///
+/// let query = OrderBookQuery::new("a8db", "★ Karambit | Doppler (Factory New)")
+///     .phase(DopplerPhase::Phase2)
+///     .float_part_value("FN-0")
+///     .paint_seed(661);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBookQuery {
+    game_id: String,
+    title: String,
+    phase: Option<DopplerPhase>,
+    float_part_value: Option<String>,
+    paint_seed: Option<u32>,
+}
+
+impl OrderBookQuery {
+    /// Every buy order of `title` in game `game_id`.
+    pub fn new(game_id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            game_id: game_id.into(),
+            title: title.into(),
+            phase: None,
+            float_part_value: None,
+            paint_seed: None,
+        }
+    }
+
+    pub fn game_id(&self) -> &str {
+        &self.game_id
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn phase(mut self, phase: DopplerPhase) -> Self {
+        self.phase = Some(phase);
+        self
+    }
+
+    /// The float range of the item as DMarket names it in targets, e.g. `"FN-0"`.
+    pub fn float_part_value(mut self, float_part_value: impl Into<String>) -> Self {
+        self.float_part_value = Some(float_part_value.into());
+        self
+    }
+
+    pub fn paint_seed(mut self, paint_seed: u32) -> Self {
+        self.paint_seed = Some(paint_seed);
+        self
+    }
+
+    pub(crate) fn check(&self) -> Result<(), DMarketError> {
+        if self.game_id.is_empty() {
+            return Err(DMarketError::InvalidQuery("game id cannot be empty".to_string()));
+        }
+        if self.title.is_empty() {
+            return Err(DMarketError::InvalidQuery("title cannot be empty".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Whether `order` would buy an item with the query's attributes.
+    pub fn matches(&self, order: &BuyOrder) -> bool {
+        let phase = match (&order.phase, self.phase) {
+            (Some(wanted), Some(phase)) => *wanted == phase_slug(phase),
+            _ => true,
+        };
+        let float_part_value = match (&order.float_part_value, &self.float_part_value) {
+            (Some(wanted), Some(float_part_value)) => wanted == float_part_value,
+            _ => true,
+        };
+        let paint_seed = match (&order.paint_seed, self.paint_seed) {
+            (Some(wanted), Some(paint_seed)) => wanted.parse() == Ok(paint_seed),
+            _ => true,
+        };
+        phase && float_part_value && paint_seed
+    }
+}
+
+/// The buy orders at one price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BidLevel {
+    pub price: Money,
+    /// Number of items requested at this price.
+    pub quantity: u32,
+    /// Number of orders at this price.
+    pub orders: usize,
+}
+
+/// The buy orders of a title by price level, highest price first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBook {
+    pub title: String,
+    pub levels: Vec<BidLevel>,
+}
+
+impl OrderBook {
+    /// Aggregates `orders` into price levels. Fails unless orders are all priced in the same
+    /// currency.
+    pub fn from_orders(title: impl Into<String>, orders: impl IntoIterator<Item = BuyOrder>) -> Result<Self, MoneyError> {
+        let mut levels = BTreeMap::new();
+        let mut currency = None;
+        for order in orders {
+            let currency = *currency.get_or_insert(order.price.currency());
+            if order.price.currency() != currency {
+                return Err(MoneyError::CurrencyMismatch(currency, order.price.currency()));
+            }
+            let level = levels.entry(order.price.minor_units()).or_insert(BidLevel {
+                price: order.price,
+                quantity: 0,
+                orders: 0,
+            });
+            level.quantity = level.quantity.saturating_add(order.quantity);
+            level.orders += 1;
+        }
+        Ok(Self {
+            title: title.into(),
+            levels: levels.into_values().rev().collect(),
+        })
+    }
+
+    /// The highest price level, if there are any orders.
+    pub fn best_bid(&self) -> Option<&BidLevel> {
+        self.levels.first()
+    }
+
+    /// Number of items requested over all levels.
+    pub fn total_quantity(&self) -> u32 {
+        self.levels.iter().fold(0, |total, level| total.saturating_add(level.quantity))
+    }
+}
@@ -0,0 +1,185 @@
+//! Streams over DMarket's cursor-paginated listings.
+
+use futures::future::BoxFuture;
+use futures::Stream;
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::dmarket::error::DMarketError;
+
+/// Number of items requested per page unless configured otherwise.
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// One page of a listing and the cursor of the page after it.
+pub(crate) struct Page<T> {
+    pub(crate) items: Vec<T>,
+    pub(crate) cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// DMarket sends an empty cursor with the last page.
+    pub(crate) fn new(items: Vec<T>, cursor: Option<String>) -> Self {
+        Self {
+            items,
+            cursor: cursor.filter(|cursor| !cursor.is_empty()),
+        }
+    }
+}
+
+/// Requests the page of `limit` items at `cursor`, or the first page without one.
+type FetchPage<T> = Box<dyn Fn(u32, Option<String>) -> BoxFuture<'static, Result<Page<T>, DMarketError>> + Send + Sync>;
+
+/// The items of a paginated listing, fetched a page at a time as they are consumed.
+///
+/// A page is only requested once every item of the previous one has been taken, so a slow
+/// consumer holds back the requests. The stream ends after the last page, after
+/// [`max_items`](Paginated::max_items) items, or after yielding an error.
+///
+/// To continue a listing later, save [`cursor`](Paginated::cursor) and pass it to
+/// [`resume_from`](Paginated::resume_from) on a new stream. Resuming restarts the page the
+/// stream was in, so items of a partially consumed page are yielded again.
+pub struct Paginated<T> {
+    fetch: FetchPage<T>,
+    page_size: u32,
+    max_items: Option<usize>,
+    yielded: usize,
+    buffer: VecDeque<T>,
+    /// Cursor of the page the buffered items come from.
+    current: Option<String>,
+    /// Cursor of the next page to request.
+    next: Option<String>,
+    finished: bool,
+    in_flight: Option<BoxFuture<'static, Result<Page<T>, DMarketError>>>,
+}
+
+impl<T> Paginated<T> {
+    pub(crate) fn new<F>(page_size: u32, fetch: F) -> Self
+    where
+        F: Fn(u32, Option<String>) -> BoxFuture<'static, Result<Page<T>, DMarketError>> + Send + Sync + 'static,
+    {
+        Self {
+            fetch: Box::new(fetch),
+            page_size: page_size.max(1),
+            max_items: None,
+            yielded: 0,
+            buffer: VecDeque::new(),
+            current: None,
+            next: None,
+            finished: false,
+            in_flight: None,
+        }
+    }
+
+    /// Number of items requested per page.
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Ends the stream after `max_items` items, without requesting more than needed.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Starts at the page of `cursor`, saved from [`Paginated::cursor`] of an earlier stream over
+    /// the same listing.
+    pub fn resume_from(mut self, cursor: impl Into<String>) -> Self {
+        self.next = Some(cursor.into());
+        self
+    }
+
+    /// Where to resume the listing: the cursor of the page the next item comes from. `None`
+    /// before the second page, or once [`is_finished`](Paginated::is_finished).
+    pub fn cursor(&self) -> Option<&str> {
+        if self.buffer.is_empty() {
+            self.next.as_deref()
+        } else {
+            self.current.as_deref()
+        }
+    }
+
+    /// Whether every item of the listing has been yielded, so there is nothing to resume.
+    pub fn is_finished(&self) -> bool {
+        self.finished && self.buffer.is_empty() && self.next.is_none()
+    }
+
+    /// Number of items yielded so far.
+    pub fn yielded(&self) -> usize {
+        self.yielded
+    }
+
+    fn capped(&self) -> bool {
+        self.max_items.is_some_and(|max_items| self.yielded >= max_items)
+    }
+}
+
+// The items are never pinned: they are moved out of the buffer.
+impl<T> Unpin for Paginated<T> {}
+
+impl<T> Stream for Paginated<T> {
+    type Item = Result<T, DMarketError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.capped() {
+                return Poll::Ready(None);
+            }
+            if let Some(item) = this.buffer.pop_front() {
+                this.yielded += 1;
+                return Poll::Ready(Some(Ok(item)));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            let in_flight = match &mut this.in_flight {
+                Some(in_flight) => in_flight,
+                None => {
+                    let remaining = this.max_items.map(|max_items| max_items - this.yielded);
+                    let limit = match remaining {
+                        Some(remaining) => this.page_size.min(u32::try_from(remaining).unwrap_or(u32::MAX)),
+                        None => this.page_size,
+                    };
+                    this.in_flight.insert((this.fetch)(limit, this.next.clone()))
+                }
+            };
+            let result = match in_flight.as_mut().poll(cx) {
+                Poll::Ready(result) => result,
+                Poll::Pending => return Poll::Pending,
+            };
+            this.in_flight = None;
+            match result {
+                Ok(page) => {
+                    this.current = std::mem::replace(&mut this.next, page.cursor);
+                    this.finished = this.next.is_none() || page.items.is_empty();
+                    if page.items.is_empty() {
+                        this.next = None;
+                    }
+                    this.buffer.extend(page.items);
+                }
+                Err(e) => {
+                    // `next` is kept, so the failed page can be resumed.
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Paginated<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Paginated")
+            .field("page_size", &self.page_size)
+            .field("max_items", &self.max_items)
+            .field("yielded", &self.yielded)
+            .field("buffered", &self.buffer.len())
+            .field("cursor", &self.cursor())
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
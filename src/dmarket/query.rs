@@ -0,0 +1,327 @@
+//! Typed parameters of the DMarket market items endpoint.
+
+use std::fmt;
+
+use crate::dmarket::error::DMarketError;
+use crate::identity::{DopplerPhase, Exterior};
+use crate::money::{Currency, Money, MoneyError};
+
+/// DMarket game id of Counter-Strike 2.
+pub const CS2_GAME_ID: &str = "a8db";
+
+const DEFAULT_LIMIT: u32 = 100;
+
+/// The field market items are sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarketItemsOrder {
+    Title,
+    Price,
+    Discount,
+    /// Most recently listed or updated first when descending.
+    Updated,
+}
+
+impl MarketItemsOrder {
+    /// The `orderBy` value DMarket expects.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketItemsOrder::Title => "title",
+            MarketItemsOrder::Price => "price",
+            MarketItemsOrder::Discount => "discount",
+            MarketItemsOrder::Updated => "updated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderDirection {
+    Asc,
+    Desc,
+}
+
+impl OrderDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderDirection::Asc => "asc",
+            OrderDirection::Desc => "desc",
+        }
+    }
+}
+
+/// The item category of DMarket's `category_0` tree filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemCategory {
+    Knife,
+    Gloves,
+    Pistol,
+    Smg,
+    Rifle,
+    SniperRifle,
+    Shotgun,
+    Machinegun,
+    Sticker,
+    Agent,
+    Container,
+    Key,
+    Graffiti,
+    Patch,
+    MusicKit,
+}
+
+impl ItemCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemCategory::Knife => "knife",
+            ItemCategory::Gloves => "gloves",
+            ItemCategory::Pistol => "pistol",
+            ItemCategory::Smg => "smg",
+            ItemCategory::Rifle => "rifle",
+            ItemCategory::SniperRifle => "sniper rifle",
+            ItemCategory::Shotgun => "shotgun",
+            ItemCategory::Machinegun => "machinegun",
+            ItemCategory::Sticker => "sticker",
+            ItemCategory::Agent => "agent",
+            ItemCategory::Container => "container",
+            ItemCategory::Key => "key",
+            ItemCategory::Graffiti => "graffiti",
+            ItemCategory::Patch => "patch",
+            ItemCategory::MusicKit => "music kit",
+        }
+    }
+}
+
+/// Who sells an offer: DMarket itself from its bot inventory, or another user trading over Steam.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OfferType {
+    DMarket,
+    P2P,
+}
+
+impl OfferType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OfferType::DMarket => "dmarket",
+            OfferType::P2P => "p2p",
+        }
+    }
+}
+
+/// A filter of DMarket's `treeFilters` parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TreeFilter {
+    Exterior(Exterior),
+    Category(ItemCategory),
+    Phase(DopplerPhase),
+    /// Float values from `min` to `max`, both between 0 and 1.
+    Float { min: f64, max: f64 },
+}
+
+impl fmt::Display for TreeFilter {
+    /// Formats the filter as DMarket expects it, e.g. `exterior[]=field-tested`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeFilter::Exterior(exterior) => write!(f, "exterior[]={}", exterior.name().to_lowercase()),
+            TreeFilter::Category(category) => write!(f, "category_0[]={}", category.as_str()),
+            TreeFilter::Phase(phase) => write!(f, "phase[]={}", phase_slug(*phase)),
+            TreeFilter::Float { min, max } => write!(f, "floatValueFrom[]={},floatValueTo[]={}", min, max),
+        }
+    }
+}
+
+/// The name DMarket gives `phase` in filters and targets, e.g. `"black-pearl"`.
+pub(crate) fn phase_slug(phase: DopplerPhase) -> String {
+    phase.name().to_lowercase().replace(' ', "-")
+}
+
+/// Rejects float ranges outside 0 to 1 or with `min` above `max`.
+pub(crate) fn check_filters(filters: &[TreeFilter]) -> Result<(), DMarketError> {
+    for filter in filters {
+        if let TreeFilter::Float { min, max } = *filter {
+            if !(0.0..=1.0).contains(&min) || !(0.0..=1.0).contains(&max) || min > max {
+                return Err(DMarketError::InvalidQuery(format!("invalid float range {} to {}", min, max)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parameters of [`ExchangeHandler::get_market_items`](crate::dmarket::endpoints::exchange::ExchangeHandler::get_market_items).
+///
+/// ```
+/// use backend::{Currency, Exterior, Money, MarketItemsOrder, MarketItemsQuery, OrderDirection};
+///
+/// let query = MarketItemsQuery::new("a8db")
+///     .title("AK-47 | Redline")
+///     .exterior(Exterior::FieldTested)
+///     .price_to(Money::from_minor(5000, Currency::USD))
+///     .order_by(MarketItemsOrder::Price, OrderDirection::Asc)
+///     .limit(50);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketItemsQuery {
+    game_id: String,
+    currency: Currency,
+    pub(crate) limit: u32,
+    offset: u32,
+    order: Option<(MarketItemsOrder, OrderDirection)>,
+    title: Option<String>,
+    tree_filters: Vec<TreeFilter>,
+    price_from: Option<Money>,
+    price_to: Option<Money>,
+    offer_types: Vec<OfferType>,
+    pub(crate) cursor: Option<String>,
+}
+
+impl MarketItemsQuery {
+    /// The first 100 items of game `game_id`, priced in USD.
+    pub fn new(game_id: impl Into<String>) -> Self {
+        Self {
+            game_id: game_id.into(),
+            currency: Currency::USD,
+            limit: DEFAULT_LIMIT,
+            offset: 0,
+            order: None,
+            title: None,
+            tree_filters: Vec::new(),
+            price_from: None,
+            price_to: None,
+            offer_types: Vec::new(),
+            cursor: None,
+        }
+    }
+
+    pub fn game_id(&self) -> &str {
+        &self.game_id
+    }
+
+    /// The currency prices are returned and filtered in. Price bounds must use it too.
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn order_by(mut self, order: MarketItemsOrder, direction: OrderDirection) -> Self {
+        self.order = Some((order, direction));
+        self
+    }
+
+    /// Items whose title contains `title`. DMarket matches titles fuzzily.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Adds `filter`. Filters of one kind match any of their values; filters of different
+    /// kinds must all match.
+    pub fn filter(mut self, filter: TreeFilter) -> Self {
+        self.tree_filters.push(filter);
+        self
+    }
+
+    pub fn exterior(self, exterior: Exterior) -> Self {
+        self.filter(TreeFilter::Exterior(exterior))
+    }
+
+    pub fn category(self, category: ItemCategory) -> Self {
+        self.filter(TreeFilter::Category(category))
+    }
+
+    pub fn phase(self, phase: DopplerPhase) -> Self {
+        self.filter(TreeFilter::Phase(phase))
+    }
+
+    /// Items with a float value from `min` to `max`.
+    pub fn float_range(self, min: f64, max: f64) -> Self {
+        self.filter(TreeFilter::Float { min, max })
+    }
+
+    /// Items priced at least `price`.
+    pub fn price_from(mut self, price: Money) -> Self {
+        self.price_from = Some(price);
+        self
+    }
+
+    /// Items priced at most `price`.
+    pub fn price_to(mut self, price: Money) -> Self {
+        self.price_to = Some(price);
+        self
+    }
+
+    /// Adds an offer type to return. All offer types are returned when none is added.
+    pub fn offer_type(mut self, offer_type: OfferType) -> Self {
+        if !self.offer_types.contains(&offer_type) {
+            self.offer_types.push(offer_type);
+        }
+        self
+    }
+
+    /// Continues a listing from the cursor returned with its previous page.
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Checks the query and returns its parameters, to be URL-encoded by the client like every
+    /// other signed query.
+    pub fn to_query(&self) -> Result<Vec<(&'static str, String)>, DMarketError> {
+        if self.game_id.is_empty() {
+            return Err(DMarketError::InvalidQuery("game id cannot be empty".to_string()));
+        }
+        for price in [self.price_from, self.price_to].into_iter().flatten() {
+            if price.currency() != self.currency {
+                return Err(MoneyError::CurrencyMismatch(price.currency(), self.currency).into());
+            }
+            if price.is_negative() {
+                return Err(DMarketError::InvalidQuery(format!("negative price bound {}", price)));
+            }
+        }
+        if let (Some(from), Some(to)) = (self.price_from, self.price_to) {
+            if from > to {
+                return Err(DMarketError::InvalidQuery(format!("price range {} to {} is empty", from, to)));
+            }
+        }
+        check_filters(&self.tree_filters)?;
+
+        let mut query = vec![
+            ("gameId", self.game_id.clone()),
+            ("currency", self.currency.code().to_string()),
+            ("limit", self.limit.to_string()),
+            ("offset", self.offset.to_string()),
+        ];
+        if let Some((order, direction)) = self.order {
+            query.push(("orderBy", order.as_str().to_string()));
+            query.push(("orderDir", direction.as_str().to_string()));
+        }
+        if let Some(title) = &self.title {
+            query.push(("title", title.clone()));
+        }
+        if !self.tree_filters.is_empty() {
+            let filters = self.tree_filters.iter().map(TreeFilter::to_string).collect::<Vec<_>>();
+            query.push(("treeFilters", filters.join(",")));
+        }
+        if let Some(price) = self.price_from {
+            query.push(("priceFrom", price.minor_units().to_string()));
+        }
+        if let Some(price) = self.price_to {
+            query.push(("priceTo", price.minor_units().to_string()));
+        }
+        if !self.offer_types.is_empty() {
+            let types = self.offer_types.iter().map(OfferType::as_str).collect::<Vec<_>>();
+            query.push(("types", types.join(",")));
+        }
+        if let Some(cursor) = &self.cursor {
+            query.push(("cursor", cursor.clone()));
+        }
+        Ok(query)
+    }
+}
@@ -0,0 +1,192 @@
+//! Typed parameters of the DMarket last sales endpoint, and statistics over its sales.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::dmarket::error::DMarketError;
+use crate::dmarket::models::LastSale;
+use crate::dmarket::query::{check_filters, TreeFilter};
+use crate::identity::DopplerPhase;
+use crate::money::{Money, MoneyError};
+
+const DEFAULT_LIMIT: u32 = 100;
+
+/// How an item was sold: by buying a listed offer, or by filling a buy order (target).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SaleType {
+    Offer,
+    Target,
+}
+
+impl SaleType {
+    /// The `txOperationType` value DMarket expects.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SaleType::Offer => "Offer",
+            SaleType::Target => "Target",
+        }
+    }
+}
+
+/// How far back the last sales endpoint looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SalesPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl SalesPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SalesPeriod::Day => "1D",
+            SalesPeriod::Week => "7D",
+            SalesPeriod::Month => "1M",
+        }
+    }
+}
+
+/// Parameters of [`ExchangeHandler::get_last_sales`](crate::dmarket::endpoints::exchange::ExchangeHandler::get_last_sales).
+///
+/// ```
+/// use backend::{DopplerPhase, LastSalesQuery, SaleType, SalesPeriod};
+///
+/// let query = LastSalesQuery::new("a8db", "★ Karambit | Doppler (Factory New)")
+///     .period(SalesPeriod::Week)
+///     .sale_type(SaleType::Offer)
+///     .phase(DopplerPhase::Phase2)
+///     .float_range(0.0, 0.01);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LastSalesQuery {
+    game_id: String,
+    title: String,
+    period: Option<SalesPeriod>,
+    sale_types: Vec<SaleType>,
+    filters: Vec<TreeFilter>,
+    pub(crate) limit: u32,
+    pub(crate) offset: u32,
+}
+
+impl LastSalesQuery {
+    /// The 100 latest sales of `title` in game `game_id`.
+    pub fn new(game_id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            game_id: game_id.into(),
+            title: title.into(),
+            period: None,
+            sale_types: Vec::new(),
+            filters: Vec::new(),
+            limit: DEFAULT_LIMIT,
+            offset: 0,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn period(mut self, period: SalesPeriod) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    /// Adds a sale type to return. Sales of every type are returned when none is added.
+    pub fn sale_type(mut self, sale_type: SaleType) -> Self {
+        if !self.sale_types.contains(&sale_type) {
+            self.sale_types.push(sale_type);
+        }
+        self
+    }
+
+    /// Adds `filter`, applied to the attributes of the sold item.
+    pub fn filter(mut self, filter: TreeFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn phase(self, phase: DopplerPhase) -> Self {
+        self.filter(TreeFilter::Phase(phase))
+    }
+
+    /// Sales of items with a float value from `min` to `max`.
+    pub fn float_range(self, min: f64, max: f64) -> Self {
+        self.filter(TreeFilter::Float { min, max })
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Checks the query and returns its parameters.
+    pub fn to_query(&self) -> Result<Vec<(&'static str, String)>, DMarketError> {
+        if self.game_id.is_empty() {
+            return Err(DMarketError::InvalidQuery("game id cannot be empty".to_string()));
+        }
+        if self.title.is_empty() {
+            return Err(DMarketError::InvalidQuery("title cannot be empty".to_string()));
+        }
+        check_filters(&self.filters)?;
+
+        let mut query = vec![
+            ("gameId", self.game_id.clone()),
+            ("title", self.title.clone()),
+            ("limit", self.limit.to_string()),
+            ("offset", self.offset.to_string()),
+        ];
+        if let Some(period) = self.period {
+            query.push(("period", period.as_str().to_string()));
+        }
+        for sale_type in &self.sale_types {
+            query.push(("txOperationType[]", sale_type.as_str().to_string()));
+        }
+        if !self.filters.is_empty() {
+            let filters = self.filters.iter().map(TreeFilter::to_string).collect::<Vec<_>>();
+            query.push(("filters", filters.join(",")));
+        }
+        Ok(query)
+    }
+}
+
+/// Count, median and mean price of a set of sales.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SalesStats {
+    pub count: usize,
+    /// `None` without sales.
+    pub median: Option<Money>,
+    /// `None` without sales. Every sale is of a single item, so this is also the volume-weighted
+    /// average price.
+    pub mean: Option<Money>,
+}
+
+impl SalesStats {
+    /// Statistics of `sales`, which must all be priced in the same currency.
+    pub fn from_sales<'a>(sales: impl IntoIterator<Item = &'a LastSale>) -> Result<Self, MoneyError> {
+        let mut prices = sales.into_iter().map(|sale| sale.price).collect::<Vec<_>>();
+        prices.sort_by_key(Money::minor_units);
+        let Some(first) = prices.first().copied() else {
+            return Ok(Self { count: 0, median: None, mean: None });
+        };
+
+        let count = prices.len();
+        let total = prices.iter().skip(1).try_fold(first, |total, price| total.checked_add(*price))?;
+        let mean = Money::from_decimal(total.to_decimal() / Decimal::from(count), total.currency())?;
+        let median = if count % 2 == 1 {
+            prices[count / 2]
+        } else {
+            let middle = prices[count / 2 - 1].checked_add(prices[count / 2])?;
+            Money::from_decimal(middle.to_decimal() / Decimal::TWO, middle.currency())?
+        };
+        Ok(Self {
+            count,
+            median: Some(median),
+            mean: Some(mean),
+        })
+    }
+}
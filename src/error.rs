@@ -0,0 +1,116 @@
+//! One error type for code that talks to several markets.
+//!
+//! Each client has its own error type, and [`MarketplaceError`] wraps them without interpreting
+//! them. [`Error`](struct@Error) adds the market the error came from and an [`ErrorCategory`], so callers can
+//! react to "rate limited" or "bad credentials" the same way whichever market failed.
+
+use std::fmt;
+use thiserror::Error;
+
+use crate::buff_market::BuffMarketError;
+use crate::csfloat::error::CSFloatError;
+use crate::dmarket::error::DMarketError;
+use crate::http::MarketApiError;
+use crate::marketplace::{Market, MarketplaceError};
+
+/// What went wrong, independently of the market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The request did not get an answer: connection failures, timeouts, or a replay cassette
+    /// without a matching interaction.
+    Transport,
+    /// Credentials are missing, malformed or rejected.
+    Auth,
+    RateLimited,
+    /// The item, offer or endpoint does not exist, or no longer does.
+    NotFound,
+    /// The request is invalid or cannot be fulfilled as made, e.g. for lack of funds or because
+    /// the market does not support the operation.
+    Validation,
+    /// The market failed or answered with data that could not be understood.
+    Upstream,
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorCategory::Transport => "transport error",
+            ErrorCategory::Auth => "authentication error",
+            ErrorCategory::RateLimited => "rate limited",
+            ErrorCategory::NotFound => "not found",
+            ErrorCategory::Validation => "invalid request",
+            ErrorCategory::Upstream => "upstream error",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An error of any market client, tagged with its market and category.
+#[derive(Error, Debug)]
+#[error("{category}: {source}")]
+pub struct Error {
+    market: Market,
+    category: ErrorCategory,
+    retryable: bool,
+    source: MarketplaceError,
+}
+
+impl Error {
+    pub fn market(&self) -> Market {
+        self.market
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        self.category
+    }
+
+    /// Whether sending the same request again later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+
+    /// The error response of the market, when the failure is one.
+    pub fn api_error(&self) -> Option<&MarketApiError> {
+        self.source.api_error()
+    }
+
+    /// The underlying error of the market client.
+    pub fn inner(&self) -> &MarketplaceError {
+        &self.source
+    }
+
+    pub fn into_inner(self) -> MarketplaceError {
+        self.source
+    }
+}
+
+impl From<MarketplaceError> for Error {
+    fn from(source: MarketplaceError) -> Self {
+        Self {
+            market: source.market(),
+            category: source.category(),
+            retryable: source.is_retryable(),
+            source,
+        }
+    }
+}
+
+impl From<DMarketError> for Error {
+    fn from(error: DMarketError) -> Self {
+        MarketplaceError::from(error).into()
+    }
+}
+
+impl From<CSFloatError> for Error {
+    fn from(error: CSFloatError) -> Self {
+        MarketplaceError::from(error).into()
+    }
+}
+
+impl From<BuffMarketError> for Error {
+    fn from(error: BuffMarketError) -> Self {
+        MarketplaceError::from(error).into()
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,200 @@
+//! Per-market trading fees.
+//!
+//! A [`FeeModel`] holds the seller, buyer and withdrawal fee rates of a market, with optional
+//! overrides per [`ItemKind`]. The defaults approximate each market's public fee schedule;
+//! accounts with discounted fees should load their own rates.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::identity::ItemKind;
+use crate::marketplace::Market;
+use crate::money::{Money, MoneyError};
+
+/// Fee rates as fractions of the price, e.g. `0.05` for 5%.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Taken from the sale price.
+    pub seller_fee: Decimal,
+    /// Added on top of the listed price when buying.
+    pub buyer_fee: Decimal,
+    /// Taken from the proceeds when withdrawing them.
+    pub withdrawal_fee: Decimal,
+}
+
+impl FeeSchedule {
+    pub fn new(seller_fee: Decimal, buyer_fee: Decimal, withdrawal_fee: Decimal) -> Self {
+        Self {
+            seller_fee,
+            buyer_fee,
+            withdrawal_fee,
+        }
+    }
+}
+
+/// The fees of one market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeModel {
+    pub market: Market,
+    pub default: FeeSchedule,
+    #[serde(default)]
+    pub overrides: HashMap<ItemKind, FeeSchedule>,
+}
+
+impl FeeModel {
+    pub fn new(market: Market, default: FeeSchedule) -> Self {
+        Self {
+            market,
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Uses `schedule` for items of `kind`.
+    pub fn with_override(mut self, kind: ItemKind, schedule: FeeSchedule) -> Self {
+        self.overrides.insert(kind, schedule);
+        self
+    }
+
+    /// DMarket: 5% seller fee.
+    pub fn dmarket() -> Self {
+        Self::new(Market::DMarket, FeeSchedule::new(Decimal::new(5, 2), Decimal::ZERO, Decimal::ZERO))
+    }
+
+    /// CSFloat: 2% seller fee and 2.8% on withdrawals.
+    pub fn csfloat() -> Self {
+        Self::new(
+            Market::CSFloat,
+            FeeSchedule::new(Decimal::new(2, 2), Decimal::ZERO, Decimal::new(28, 3)),
+        )
+    }
+
+    /// Buff Market: 2.5% seller fee and 1% on withdrawals.
+    pub fn buff_market() -> Self {
+        Self::new(
+            Market::BuffMarket,
+            FeeSchedule::new(Decimal::new(25, 3), Decimal::ZERO, Decimal::new(1, 2)),
+        )
+    }
+
+    /// The default model of `market`.
+    pub fn for_market(market: Market) -> Self {
+        match market {
+            Market::DMarket => Self::dmarket(),
+            Market::CSFloat => Self::csfloat(),
+            Market::BuffMarket => Self::buff_market(),
+        }
+    }
+
+    /// The schedule applying to items of `kind`.
+    pub fn schedule(&self, kind: ItemKind) -> &FeeSchedule {
+        self.overrides.get(&kind).unwrap_or(&self.default)
+    }
+
+    /// What a buyer pays for an item listed at `price`.
+    pub fn purchase_cost(&self, price: Money, kind: ItemKind) -> Result<Money, MoneyError> {
+        price.mul_decimal(Decimal::ONE + self.schedule(kind).buyer_fee)
+    }
+
+    /// What a seller keeps from a sale at `price` once it is withdrawn.
+    pub fn net_proceeds(&self, price: Money, kind: ItemKind) -> Result<Money, MoneyError> {
+        let schedule = self.schedule(kind);
+        price
+            .mul_decimal(Decimal::ONE - schedule.seller_fee)?
+            .mul_decimal(Decimal::ONE - schedule.withdrawal_fee)
+    }
+}
+
+/// Fee models for every market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeTable {
+    models: HashMap<Market, FeeModel>,
+}
+
+impl FeeTable {
+    /// Sets the model for its market.
+    pub fn with_model(mut self, model: FeeModel) -> Self {
+        self.models.insert(model.market, model);
+        self
+    }
+
+    /// The model of `market`, falling back to its default.
+    pub fn model(&self, market: Market) -> Cow<'_, FeeModel> {
+        match self.models.get(&market) {
+            Some(model) => Cow::Borrowed(model),
+            None => Cow::Owned(FeeModel::for_market(market)),
+        }
+    }
+}
+
+impl Default for FeeTable {
+    fn default() -> Self {
+        Self {
+            models: HashMap::new(),
+        }
+        .with_model(FeeModel::dmarket())
+        .with_model(FeeModel::csfloat())
+        .with_model(FeeModel::buff_market())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Currency;
+
+    fn usd(cents: i64) -> Money {
+        Money::from_minor(cents, Currency::USD)
+    }
+
+    #[test]
+    fn takes_the_dmarket_seller_fee() {
+        let model = FeeModel::dmarket();
+        assert_eq!(model.net_proceeds(usd(10000), ItemKind::Weapon), Ok(usd(9500)));
+        // $12.34 * 0.95 = $11.723.
+        assert_eq!(model.net_proceeds(usd(1234), ItemKind::Weapon), Ok(usd(1172)));
+        assert_eq!(model.purchase_cost(usd(1234), ItemKind::Weapon), Ok(usd(1234)));
+    }
+
+    #[test]
+    fn takes_the_csfloat_sale_and_withdrawal_fees() {
+        let model = FeeModel::csfloat();
+        // $100 * 0.98 = $98, then $98 * 0.972 = $95.256.
+        assert_eq!(model.net_proceeds(usd(10000), ItemKind::Weapon), Ok(usd(9526)));
+        // $12.50 * 0.98 = $12.25, then $12.25 * 0.972 = $11.907.
+        assert_eq!(model.net_proceeds(usd(1250), ItemKind::Knife), Ok(usd(1191)));
+    }
+
+    #[test]
+    fn takes_the_buff_sale_and_withdrawal_fees() {
+        let model = FeeModel::buff_market();
+        let yuan = |fen| Money::from_minor(fen, Currency::CNY);
+        // ¥100 * 0.975 = ¥97.50, then ¥97.50 * 0.99 = ¥96.525, rounded half away from zero.
+        assert_eq!(model.net_proceeds(yuan(10000), ItemKind::Weapon), Ok(yuan(9653)));
+        // ¥82 * 0.975 = ¥79.95, then ¥79.95 * 0.99 = ¥79.1505.
+        assert_eq!(model.net_proceeds(yuan(8200), ItemKind::Weapon), Ok(yuan(7915)));
+    }
+
+    #[test]
+    fn applies_overrides_by_item_kind() {
+        let stickers = FeeSchedule::new(Decimal::new(10, 2), Decimal::new(2, 2), Decimal::ZERO);
+        let model = FeeModel::dmarket().with_override(ItemKind::Sticker, stickers);
+        assert_eq!(model.schedule(ItemKind::Sticker), &stickers);
+        assert_eq!(model.purchase_cost(usd(1000), ItemKind::Sticker), Ok(usd(1020)));
+        assert_eq!(model.net_proceeds(usd(1000), ItemKind::Sticker), Ok(usd(900)));
+        assert_eq!(model.net_proceeds(usd(1000), ItemKind::Weapon), Ok(usd(950)));
+    }
+
+    #[test]
+    fn falls_back_to_default_models() {
+        let discounted = FeeModel::new(Market::DMarket, FeeSchedule::new(Decimal::new(2, 2), Decimal::ZERO, Decimal::ZERO));
+        let table = FeeTable::default().with_model(discounted.clone());
+        assert_eq!(*table.model(Market::DMarket), discounted);
+        assert_eq!(*table.model(Market::CSFloat), FeeModel::csfloat());
+
+        let empty = FeeTable { models: HashMap::new() };
+        assert_eq!(*empty.model(Market::BuffMarket), FeeModel::buff_market());
+    }
+}
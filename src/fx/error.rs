@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+use crate::money::{Currency, MoneyError};
+
+#[derive(Error, Debug)]
+pub enum FxError {
+    #[error("No exchange rate from {from} to {to}")]
+    MissingRate { from: Currency, to: Currency },
+
+    #[error("Invalid exchange rate for {0}")]
+    InvalidRate(Currency),
+
+    #[error("Failed to read rate file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse rates: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Conversion error: {0}")]
+    Money(#[from] MoneyError),
+
+    #[error("Rate provider error: {0}")]
+    Provider(String),
+}
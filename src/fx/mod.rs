@@ -0,0 +1,107 @@
+//! Currency conversion between the currencies quoted by the markets.
+//!
+//! A [`RateTable`] is a timestamped snapshot of exchange rates against a base currency, obtained
+//! from a [`RateProvider`]. Convert amounts with [`Money::convert_to`](crate::money::Money::convert_to).
+
+pub mod error;
+pub mod provider;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::money::Currency;
+
+pub use error::FxError;
+pub use provider::{CachedRateProvider, RateProvider, StaticRateProvider};
+
+/// Exchange rates against a base currency, valid as of a point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateTable {
+    pub base: Currency,
+    pub as_of: DateTime<Utc>,
+    /// Units of each currency worth one unit of `base`.
+    pub rates: HashMap<Currency, Decimal>,
+}
+
+impl RateTable {
+    /// Creates a table holding only the base currency.
+    pub fn new(base: Currency, as_of: DateTime<Utc>) -> Self {
+        Self {
+            base,
+            as_of,
+            rates: HashMap::new(),
+        }
+    }
+
+    /// Sets how many units of `currency` one unit of the base currency is worth.
+    pub fn with_rate(mut self, currency: Currency, per_base: Decimal) -> Self {
+        self.rates.insert(currency, per_base);
+        self
+    }
+
+    /// Units of `to` worth one unit of `from`, crossing through the base currency if needed.
+    pub fn rate(&self, from: Currency, to: Currency) -> Result<Decimal, FxError> {
+        if from == to {
+            return Ok(Decimal::ONE);
+        }
+        let from_per_base = self.per_base(from)?;
+        let to_per_base = self.per_base(to)?;
+        to_per_base
+            .checked_div(from_per_base)
+            .ok_or(FxError::InvalidRate(from))
+    }
+
+    fn per_base(&self, currency: Currency) -> Result<Decimal, FxError> {
+        if currency == self.base {
+            return Ok(Decimal::ONE);
+        }
+        match self.rates.get(&currency) {
+            Some(rate) if rate.is_sign_positive() && !rate.is_zero() => Ok(*rate),
+            Some(_) => Err(FxError::InvalidRate(currency)),
+            None => Err(FxError::MissingRate {
+                from: self.base,
+                to: currency,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> RateTable {
+        RateTable::new(Currency::USD, Utc::now())
+            .with_rate(Currency::CNY, Decimal::new(72, 1))
+            .with_rate(Currency::EUR, Decimal::new(9, 1))
+    }
+
+    #[test]
+    fn reads_direct_and_inverse_rates() {
+        assert_eq!(table().rate(Currency::USD, Currency::CNY).unwrap(), Decimal::new(72, 1));
+        assert_eq!(table().rate(Currency::CNY, Currency::USD).unwrap(), Decimal::ONE / Decimal::new(72, 1));
+        assert_eq!(table().rate(Currency::CNY, Currency::CNY).unwrap(), Decimal::ONE);
+    }
+
+    #[test]
+    fn crosses_through_the_base_currency() {
+        assert_eq!(table().rate(Currency::EUR, Currency::CNY).unwrap(), Decimal::new(8, 0));
+        assert_eq!(table().rate(Currency::CNY, Currency::EUR).unwrap(), Decimal::new(125, 3));
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_rates() {
+        assert!(matches!(
+            table().rate(Currency::DMC, Currency::CNY),
+            Err(FxError::MissingRate { from: Currency::USD, to: Currency::DMC })
+        ));
+        assert!(matches!(
+            table().rate(Currency::USD, Currency::DMC),
+            Err(FxError::MissingRate { from: Currency::USD, to: Currency::DMC })
+        ));
+        let table = table().with_rate(Currency::DMC, Decimal::ZERO);
+        assert!(matches!(table.rate(Currency::DMC, Currency::USD), Err(FxError::InvalidRate(Currency::DMC))));
+    }
+}
@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::debug;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use super::{FxError, RateTable};
+
+/// A source of exchange rates.
+#[async_trait]
+pub trait RateProvider: Send + Sync {
+    /// Returns the current rate table.
+    async fn fetch_rates(&self) -> Result<RateTable, FxError>;
+}
+
+/// Serves a fixed rate table, for offline use.
+#[derive(Debug, Clone)]
+pub struct StaticRateProvider {
+    table: RateTable,
+}
+
+impl StaticRateProvider {
+    pub fn new(table: RateTable) -> Self {
+        Self { table }
+    }
+
+    /// Loads a rate table from a JSON file such as
+    /// `{"base": "USD", "as_of": "2025-01-01T00:00:00Z", "rates": {"CNY": "7.1", "DMC": "1"}}`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FxError> {
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        let table = serde_json::from_str::<RateTable>(&contents)?;
+        debug!("Loaded {} exchange rates from {}", table.rates.len(), path.as_ref().display());
+        Ok(Self::new(table))
+    }
+}
+
+#[async_trait]
+impl RateProvider for StaticRateProvider {
+    async fn fetch_rates(&self) -> Result<RateTable, FxError> {
+        Ok(self.table.clone())
+    }
+}
+
+/// A rate table together with the time it was fetched.
+#[derive(Debug, Clone)]
+struct CachedRates {
+    table: RateTable,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Caches the rates of another provider for a fixed time-to-live.
+pub struct CachedRateProvider<P> {
+    inner: P,
+    ttl: Duration,
+    cache: Mutex<Option<CachedRates>>,
+}
+
+impl<P: RateProvider> CachedRateProvider<P> {
+    pub fn new(inner: P, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// When the cached rates were fetched, if any are cached.
+    pub async fn fetched_at(&self) -> Option<DateTime<Utc>> {
+        self.cache.lock().await.as_ref().map(|cached| cached.fetched_at)
+    }
+
+    /// Drops the cached rates so the next call fetches fresh ones.
+    pub async fn invalidate(&self) {
+        *self.cache.lock().await = None;
+    }
+}
+
+#[async_trait]
+impl<P: RateProvider> RateProvider for CachedRateProvider<P> {
+    async fn fetch_rates(&self) -> Result<RateTable, FxError> {
+        let mut cache = self.cache.lock().await;
+        let now = Utc::now();
+        if let Some(cached) = cache.as_ref() {
+            let age = (now - cached.fetched_at).to_std().unwrap_or_default();
+            if age < self.ttl {
+                return Ok(cached.table.clone());
+            }
+        }
+
+        let table = self.inner.fetch_rates().await?;
+        *cache = Some(CachedRates {
+            table: table.clone(),
+            fetched_at: now,
+        });
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Currency;
+    use rust_decimal::Decimal;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves a table whose CNY rate is the number of fetches so far.
+    #[derive(Default)]
+    struct CountingProvider {
+        fetches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl RateProvider for CountingProvider {
+        async fn fetch_rates(&self) -> Result<RateTable, FxError> {
+            let fetches = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(RateTable::new(Currency::USD, Utc::now()).with_rate(Currency::CNY, Decimal::from(fetches)))
+        }
+    }
+
+    async fn cny_rate(provider: &impl RateProvider) -> Decimal {
+        provider.fetch_rates().await.unwrap().rate(Currency::USD, Currency::CNY).unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_cached_rates_until_they_expire() {
+        let provider = CachedRateProvider::new(CountingProvider::default(), Duration::from_millis(50));
+        assert_eq!(provider.fetched_at().await, None);
+
+        assert_eq!(cny_rate(&provider).await, Decimal::from(1));
+        let fetched_at = provider.fetched_at().await.unwrap();
+        assert_eq!(cny_rate(&provider).await, Decimal::from(1));
+        assert_eq!(provider.fetched_at().await, Some(fetched_at));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(cny_rate(&provider).await, Decimal::from(2));
+        assert!(provider.fetched_at().await.unwrap() > fetched_at);
+        assert_eq!(provider.inner.fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn refetches_after_invalidation() {
+        let provider = CachedRateProvider::new(CountingProvider::default(), Duration::from_secs(3600));
+        assert_eq!(cny_rate(&provider).await, Decimal::from(1));
+        provider.invalidate().await;
+        assert_eq!(provider.fetched_at().await, None);
+        assert_eq!(cny_rate(&provider).await, Decimal::from(2));
+        assert_eq!(cny_rate(&provider).await, Decimal::from(2));
+    }
+}
@@ -0,0 +1,155 @@
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::fmt;
+
+use crate::marketplace::Market;
+
+/// What an error response of a market means for the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiErrorKind {
+    RateLimited,
+    /// The credentials are missing, invalid or not allowed to make the request.
+    Unauthorized,
+    NotFound,
+    /// The account cannot pay for the purchase or target.
+    InsufficientFunds,
+    /// The item was sold or withdrawn before the request reached the market.
+    ItemUnavailable,
+    /// The market rejected the parameters of the request.
+    InvalidRequest,
+    ServerError,
+    Other,
+}
+
+impl ApiErrorKind {
+    /// The kind implied by an HTTP status alone.
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => ApiErrorKind::RateLimited,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ApiErrorKind::Unauthorized,
+            StatusCode::NOT_FOUND => ApiErrorKind::NotFound,
+            StatusCode::PAYMENT_REQUIRED => ApiErrorKind::InsufficientFunds,
+            StatusCode::CONFLICT | StatusCode::GONE => ApiErrorKind::ItemUnavailable,
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => ApiErrorKind::InvalidRequest,
+            status if status.is_server_error() => ApiErrorKind::ServerError,
+            _ => ApiErrorKind::Other,
+        }
+    }
+
+    /// The kind named by a market error code such as DMarket's `"InsufficientFunds"` or Buff's
+    /// `"Login Required"`, if it is recognized.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let matches = |keywords: &[&str]| keywords.iter().any(|keyword| code.contains(keyword));
+
+        // Checked in order: "serviceunavailable" is not about an item, "offernotfound" is.
+        let kind = if matches(&["toomanyrequests", "ratelimit", "frequent", "throttl"]) {
+            ApiErrorKind::RateLimited
+        } else if matches(&["serviceunavailable", "internal", "systemerror", "servererror", "timeout"]) {
+            ApiErrorKind::ServerError
+        } else if matches(&["unauthorized", "loginrequired", "forbidden", "signature", "apikey", "authenticat", "permission"]) {
+            ApiErrorKind::Unauthorized
+        } else if matches(&["insufficient", "notenough", "balance", "funds"]) {
+            ApiErrorKind::InsufficientFunds
+        } else if matches(&["sold", "unavailable", "notavailable", "offernotfound", "alreadybought", "itemlocked"]) {
+            ApiErrorKind::ItemUnavailable
+        } else if matches(&["notfound"]) {
+            ApiErrorKind::NotFound
+        } else if matches(&["invalid", "badrequest", "validation", "argument"]) {
+            ApiErrorKind::InvalidRequest
+        } else {
+            return None;
+        };
+        Some(kind)
+    }
+
+    /// Whether sending the same request again later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ApiErrorKind::RateLimited | ApiErrorKind::ServerError)
+    }
+}
+
+impl fmt::Display for ApiErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ApiErrorKind::RateLimited => "rate limited",
+            ApiErrorKind::Unauthorized => "unauthorized",
+            ApiErrorKind::NotFound => "not found",
+            ApiErrorKind::InsufficientFunds => "insufficient funds",
+            ApiErrorKind::ItemUnavailable => "item unavailable",
+            ApiErrorKind::InvalidRequest => "invalid request",
+            ApiErrorKind::ServerError => "server error",
+            ApiErrorKind::Other => "error",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An error response of a market API.
+///
+/// Buff reports most errors with a `200 OK` status and a non-`"OK"` code, so `status` alone does
+/// not tell whether a request failed; `kind` does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketApiError {
+    pub market: Market,
+    pub status: StatusCode,
+    pub kind: ApiErrorKind,
+    /// The market's own error code, when the response carries one.
+    pub code: Option<String>,
+    pub message: String,
+    /// Whether sending the same request again later may succeed.
+    pub retryable: bool,
+}
+
+impl MarketApiError {
+    /// Classifies an error by its market `code` when recognized, and by its status otherwise.
+    pub fn new(market: Market, status: StatusCode, code: Option<String>, message: impl Into<String>) -> Self {
+        let kind = code
+            .as_deref()
+            .and_then(ApiErrorKind::from_code)
+            .unwrap_or_else(|| ApiErrorKind::from_status(status));
+        Self {
+            market,
+            status,
+            kind,
+            code,
+            message: message.into(),
+            retryable: kind.is_retryable(),
+        }
+    }
+
+    /// Parses a JSON error body with a `code` and a `message`, `msg` or `error` field, as the
+    /// markets send them. Other bodies become the message as is.
+    pub fn from_body(market: Market, status: StatusCode, body: &str) -> Self {
+        let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(body) else {
+            return Self::new(market, status, None, body.trim());
+        };
+        let text = |name: &str| match fields.get(name) {
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(Value::Number(number)) => Some(number.to_string()),
+            _ => None,
+        };
+        let code = text("code");
+        let message = text("message")
+            .or_else(|| text("msg"))
+            .or_else(|| text("error"))
+            .unwrap_or_else(|| body.trim().to_string());
+        Self::new(market, status, code, message)
+    }
+}
+
+impl fmt::Display for MarketApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} returned {}", self.market, self.status)?;
+        if let Some(code) = &self.code {
+            write!(f, " ({})", code)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for MarketApiError {}
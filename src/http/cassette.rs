@@ -0,0 +1,360 @@
+use tracing::debug;
+use reqwest::header::HeaderMap;
+use reqwest::{Request, RequestBuilder, Response, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+
+use super::redact::{self, SENSITIVE_FIELDS, SENSITIVE_HEADERS};
+
+pub use super::redact::REDACTED;
+
+/// Query parameters that change on every request, such as Buff's `_` timestamp.
+const DEFAULT_IGNORED_QUERY_PARAMS: [&str; 1] = ["_"];
+
+#[derive(Error, Debug)]
+pub enum CassetteError {
+    #[error("Cannot access cassette {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("Invalid cassette {path}: {source}")]
+    Format { path: PathBuf, source: serde_json::Error },
+
+    #[error("No recorded interaction left for {method} {target}")]
+    NoMatch { method: String, target: String },
+
+    #[error("Invalid recorded response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Whether a [`Cassette`] records live traffic or replays it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// One request and the response the server gave to it, as stored on a cassette.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Default)]
+struct Tape {
+    interactions: Vec<Interaction>,
+    played: Vec<bool>,
+}
+
+impl std::fmt::Debug for Tape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tape")
+            .field("interactions", &self.interactions.len())
+            .field("played", &self.played.iter().filter(|played| **played).count())
+            .finish()
+    }
+}
+
+/// Records HTTP interactions to a JSON file, or replays them instead of contacting the server.
+///
+/// Attach a cassette to a client with its `with_cassette` method. When recording, every response
+/// is written to the file as soon as it arrives, with credentials, signatures and account details
+/// replaced by [`REDACTED`]. When replaying, each request is answered by the first unplayed
+/// interaction with the same method, path, query and body, so requests repeated during recording,
+/// such as retries, are answered in the order they were recorded. Rate limits and retry delays
+/// are skipped during replay.
+///
+/// Clones share the same file and interactions, so one cassette can serve several clients.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    mode: CassetteMode,
+    path: Arc<PathBuf>,
+    redacted_headers: Vec<String>,
+    redacted_fields: Vec<String>,
+    ignored_query_params: Vec<String>,
+    tape: Arc<Mutex<Tape>>,
+}
+
+impl Cassette {
+    /// Records the traffic of the clients using this cassette to `path`, replacing the file.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(CassetteMode::Record, path.into(), Tape::default())
+    }
+
+    /// Replays the interactions recorded in `path`.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, CassetteError> {
+        let path = path.into();
+        let text = fs::read_to_string(&path).map_err(|source| CassetteError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let file: CassetteFile = serde_json::from_str(&text).map_err(|source| CassetteError::Format {
+            path: path.clone(),
+            source,
+        })?;
+        let tape = Tape {
+            played: vec![false; file.interactions.len()],
+            interactions: file.interactions,
+        };
+        Ok(Self::new(CassetteMode::Replay, path, tape))
+    }
+
+    fn new(mode: CassetteMode, path: PathBuf, tape: Tape) -> Self {
+        Self {
+            mode,
+            path: Arc::new(path),
+            redacted_headers: SENSITIVE_HEADERS.iter().map(|name| name.to_string()).collect(),
+            redacted_fields: SENSITIVE_FIELDS.iter().map(|name| name.to_string()).collect(),
+            ignored_query_params: DEFAULT_IGNORED_QUERY_PARAMS.iter().map(|name| name.to_string()).collect(),
+            tape: Arc::new(Mutex::new(tape)),
+        }
+    }
+
+    /// Also redacts the header `name`, compared case-insensitively.
+    pub fn redact_header(mut self, name: impl Into<String>) -> Self {
+        self.redacted_headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Also redacts JSON fields and query parameters called `name`, at any depth of a body.
+    pub fn redact_field(mut self, name: impl Into<String>) -> Self {
+        self.redacted_fields.push(name.into());
+        self
+    }
+
+    /// Ignores the query parameter `name` when matching requests during replay.
+    pub fn ignore_query_param(mut self, name: impl Into<String>) -> Self {
+        self.ignored_query_params.push(name.into());
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The interactions recorded so far, or loaded for replay.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.tape().interactions.clone()
+    }
+
+    /// Number of loaded interactions that have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.tape().played.iter().filter(|played| !**played).count()
+    }
+
+    fn tape(&self) -> MutexGuard<'_, Tape> {
+        self.tape.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Sends `request` to the server and records the response, or answers it from the tape.
+    ///
+    /// The outer error is a failure of the cassette itself; the inner result is what the server
+    /// answered, or would have answered.
+    pub(crate) async fn send(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Result<Response, reqwest::Error>, CassetteError> {
+        let (client, request) = request.build_split();
+        let request = match request {
+            Ok(request) => request,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        match self.mode {
+            CassetteMode::Replay => self.play(&request).map(Ok),
+            CassetteMode::Record => {
+                let recorded = self.recorded_request(&request);
+                let response = match client.execute(request).await {
+                    Ok(response) => response,
+                    Err(e) => return Ok(Err(e)),
+                };
+                let status = response.status();
+                let version = response.version();
+                let headers = response.headers().clone();
+                let body = match response.bytes().await {
+                    Ok(body) => body,
+                    Err(e) => return Ok(Err(e)),
+                };
+
+                let interaction = Interaction {
+                    request: recorded,
+                    response: RecordedResponse {
+                        status: status.as_u16(),
+                        headers: self.redact_headers(&headers),
+                        body: self.redact_body(&String::from_utf8_lossy(&body)),
+                    },
+                };
+                self.append(interaction)?;
+
+                let mut rebuilt = http::Response::builder().status(status).version(version);
+                if let Some(rebuilt_headers) = rebuilt.headers_mut() {
+                    *rebuilt_headers = headers;
+                }
+                let rebuilt = rebuilt
+                    .body(body.to_vec())
+                    .map_err(|e| CassetteError::InvalidResponse(e.to_string()))?;
+                Ok(Ok(Response::from(rebuilt)))
+            }
+        }
+    }
+
+    fn play(&self, request: &Request) -> Result<Response, CassetteError> {
+        let recorded = self.recorded_request(request);
+        let key = self.match_key(&recorded);
+
+        let mut tape = self.tape();
+        let index = (0..tape.interactions.len())
+            .find(|&index| !tape.played[index] && self.match_key(&tape.interactions[index].request) == key)
+            .ok_or_else(|| CassetteError::NoMatch {
+                method: recorded.method.clone(),
+                target: key.1.clone(),
+            })?;
+        tape.played[index] = true;
+        let recorded = &tape.interactions[index].response;
+        debug!("Replaying {} {} with status {}", key.0, key.1, recorded.status);
+
+        let mut response = http::Response::builder().status(recorded.status);
+        for (name, value) in &recorded.headers {
+            response = response.header(name, value);
+        }
+        let response = response
+            .body(recorded.body.clone().into_bytes())
+            .map_err(|e| CassetteError::InvalidResponse(e.to_string()))?;
+        Ok(Response::from(response))
+    }
+
+    fn append(&self, interaction: Interaction) -> Result<(), CassetteError> {
+        let mut tape = self.tape();
+        tape.interactions.push(interaction);
+        tape.played.push(false);
+
+        let io_error = |source| CassetteError::Io {
+            path: self.path.to_path_buf(),
+            source,
+        };
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let file = CassetteFile {
+            interactions: tape.interactions.clone(),
+        };
+        let text = serde_json::to_string_pretty(&file).map_err(|source| CassetteError::Format {
+            path: self.path.to_path_buf(),
+            source,
+        })?;
+        fs::write(self.path.as_path(), text).map_err(io_error)
+    }
+
+    fn recorded_request(&self, request: &Request) -> RecordedRequest {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| self.redact_body(&String::from_utf8_lossy(body)));
+        RecordedRequest {
+            method: request.method().to_string(),
+            url: self.redact_url(request.url()),
+            headers: self.redact_headers(request.headers()),
+            body,
+        }
+    }
+
+    /// The method, the path with its query and the body: what replayed requests are matched on.
+    fn match_key(&self, request: &RecordedRequest) -> (String, String, Option<String>) {
+        let target = match Url::parse(&request.url) {
+            Ok(url) => {
+                let query = url
+                    .query_pairs()
+                    .filter(|(name, _)| !self.ignored_query_params.iter().any(|ignored| ignored == name))
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect::<Vec<_>>();
+                if query.is_empty() {
+                    url.path().to_string()
+                } else {
+                    format!("{}?{}", url.path(), query.join("&"))
+                }
+            }
+            Err(_) => request.url.clone(),
+        };
+        (request.method.clone(), target, request.body.clone())
+    }
+
+    fn redact_headers(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.redacted_headers.iter().any(|redacted| name.as_str().eq_ignore_ascii_case(redacted)) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+
+    fn redact_url(&self, url: &Url) -> String {
+        if !url.query_pairs().any(|(name, _)| self.is_redacted_field(&name)) {
+            return url.to_string();
+        }
+        let pairs = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if self.is_redacted_field(&name) { REDACTED.into() } else { value };
+                (name.into_owned(), value.into_owned())
+            })
+            .collect::<Vec<_>>();
+        let mut url = url.clone();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+        url.to_string()
+    }
+
+    /// Redacts the fields of a JSON body. Bodies without such fields, including ones that are
+    /// not JSON, are kept byte for byte.
+    fn redact_body(&self, body: &str) -> String {
+        let Ok(mut json) = serde_json::from_str::<Value>(body) else {
+            return body.to_string();
+        };
+        if redact::redact_fields(&mut json, &|name| self.is_redacted_field(name)) {
+            json.to_string()
+        } else {
+            body.to_string()
+        }
+    }
+
+    fn is_redacted_field(&self, name: &str) -> bool {
+        self.redacted_fields.iter().any(|field| field == name)
+    }
+}
@@ -0,0 +1,28 @@
+//! HTTP behavior shared by the market clients.
+//!
+//! Each client sends its requests through a [`Transport`], which waits on a per-endpoint-group
+//! [`RateLimiter`] and retries transient failures according to a [`RetryPolicy`]. A [`Cassette`]
+//! can be attached to record the traffic to a file or to replay it offline. Credentials are held
+//! as [`Secret`]s and reach logs only through the [`redact`] helpers.
+//!
+//! Every call runs in a `market_request` tracing span recording the market, endpoint, method and
+//! pagination cursor, then the status, latency and number of retries. Without a `tracing`
+//! subscriber, spans and events are forwarded to the `log` crate.
+
+mod api_error;
+pub mod cassette;
+pub mod rate_limit;
+pub mod redact;
+pub mod retry;
+mod settings;
+pub mod transport;
+
+pub use api_error::{ApiErrorKind, MarketApiError};
+pub use cassette::{Cassette, CassetteError, CassetteMode, Interaction, RecordedRequest, RecordedResponse};
+pub use rate_limit::{EndpointGroup, Quota, RateLimiter, WaitStats};
+pub use redact::Secret;
+pub use retry::{Idempotency, RetryPolicy};
+pub use transport::Transport;
+
+pub(crate) use settings::HttpSettings;
+pub(crate) use transport::request_span;
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::marketplace::Market;
+
+/// A group of endpoints sharing a request quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EndpointGroup {
+    /// Public market data: listings, prices, games.
+    MarketData,
+    /// Buying, selling and managing offers and targets.
+    Trading,
+    /// The user's profile, balance and inventory.
+    Account,
+}
+
+impl EndpointGroup {
+    pub const ALL: [EndpointGroup; 3] = [EndpointGroup::MarketData, EndpointGroup::Trading, EndpointGroup::Account];
+}
+
+impl fmt::Display for EndpointGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EndpointGroup::MarketData => "market data",
+            EndpointGroup::Trading => "trading",
+            EndpointGroup::Account => "account",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Allows `burst` requests at once, refilled at `requests` per `per`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub requests: u32,
+    pub per: Duration,
+    pub burst: u32,
+}
+
+impl Quota {
+    /// `requests` per second, with bursts of as many.
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self {
+            requests: requests.max(1),
+            per,
+            burst: requests.max(1),
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Tokens added per second.
+    fn rate(&self) -> f64 {
+        self.requests as f64 / self.per.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Time spent waiting on one endpoint group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WaitStats {
+    /// Requests that went through the limiter.
+    pub requests: u64,
+    /// Requests that had to wait for a token.
+    pub delayed: u64,
+    pub total_wait: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    quota: Quota,
+    state: Mutex<BucketState>,
+    requests: AtomicU64,
+    delayed: AtomicU64,
+    wait_nanos: AtomicU64,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            state: Mutex::new(BucketState {
+                tokens: quota.burst as f64,
+                refilled_at: Instant::now(),
+            }),
+            requests: AtomicU64::new(0),
+            delayed: AtomicU64::new(0),
+            wait_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Takes a token, returning how long to wait before it is available. Tokens may go
+    /// negative, which queues callers in the order they arrived.
+    fn reserve(&self) -> Duration {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let rate = self.quota.rate();
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rate).min(self.quota.burst as f64);
+        state.refilled_at = now;
+        state.tokens -= 1.0;
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / rate)
+        }
+    }
+
+    fn stats(&self) -> WaitStats {
+        WaitStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            delayed: self.delayed.load(Ordering::Relaxed),
+            total_wait: Duration::from_nanos(self.wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Token-bucket rate limiter with one bucket per [`EndpointGroup`].
+///
+/// Clones share their buckets, so a limiter given to a client is shared by every clone of the
+/// client and every task using it. Groups without a quota are not limited.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    buckets: Arc<HashMap<EndpointGroup, Bucket>>,
+}
+
+impl RateLimiter {
+    /// A limiter that never waits.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// A limiter applying `quotas` to their groups.
+    pub fn new(quotas: impl IntoIterator<Item = (EndpointGroup, Quota)>) -> Self {
+        Self {
+            buckets: Arc::new(
+                quotas
+                    .into_iter()
+                    .map(|(group, quota)| (group, Bucket::new(quota)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Conservative default quotas of `market`.
+    pub fn for_market(market: Market) -> Self {
+        match market {
+            Market::DMarket => Self::new([
+                (EndpointGroup::MarketData, Quota::per_second(10)),
+                (EndpointGroup::Trading, Quota::per_second(5)),
+                (EndpointGroup::Account, Quota::per_second(5)),
+            ]),
+            Market::CSFloat => Self::new([
+                (EndpointGroup::MarketData, Quota::per_second(5)),
+                (EndpointGroup::Trading, Quota::per_second(2)),
+                (EndpointGroup::Account, Quota::per_second(2)),
+            ]),
+            // Buff bans aggressive crawlers; two requests per second matches the crawl delay it
+            // has always used.
+            Market::BuffMarket => Self::new([
+                (EndpointGroup::MarketData, Quota::per_second(2).with_burst(1)),
+                (EndpointGroup::Trading, Quota::per_second(1)),
+                (EndpointGroup::Account, Quota::per_second(1)),
+            ]),
+        }
+    }
+
+    /// The quota of `group`, if it is limited.
+    pub fn quota(&self, group: EndpointGroup) -> Option<Quota> {
+        self.buckets.get(&group).map(|bucket| bucket.quota)
+    }
+
+    /// Waits until a request to `group` is allowed.
+    pub async fn acquire(&self, group: EndpointGroup) {
+        let Some(bucket) = self.buckets.get(&group) else {
+            return;
+        };
+        bucket.requests.fetch_add(1, Ordering::Relaxed);
+        let wait = bucket.reserve();
+        if !wait.is_zero() {
+            bucket.delayed.fetch_add(1, Ordering::Relaxed);
+            bucket
+                .wait_nanos
+                .fetch_add(wait.as_nanos().min(u64::MAX as u128) as u64, Ordering::Relaxed);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Waiting statistics of `group`, zero when it is not limited.
+    pub fn stats(&self, group: EndpointGroup) -> WaitStats {
+        self.buckets.get(&group).map(Bucket::stats).unwrap_or_default()
+    }
+
+    /// Waiting statistics of every limited group.
+    pub fn all_stats(&self) -> HashMap<EndpointGroup, WaitStats> {
+        self.buckets
+            .iter()
+            .map(|(group, bucket)| (*group, bucket.stats()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(quota: Quota) -> RateLimiter {
+        RateLimiter::new([(EndpointGroup::MarketData, quota)])
+    }
+
+    /// Acquires a token of `group` and returns how long it took.
+    async fn timed_acquire(limiter: &RateLimiter, group: EndpointGroup) -> Duration {
+        let start = Instant::now();
+        limiter.acquire(group).await;
+        start.elapsed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn spaces_requests_at_the_quota_rate() {
+        let limiter = limiter(Quota::per_second(2).with_burst(1));
+
+        assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::ZERO);
+        assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::from_millis(500));
+        assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::from_millis(500));
+
+        // Idle time refills the bucket.
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn allows_bursts_up_to_the_bucket_size() {
+        let limiter = limiter(Quota::new(1, Duration::from_secs(2)).with_burst(3));
+        for _ in 0..3 {
+            assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::ZERO);
+        }
+        assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::from_secs(2));
+
+        // The bucket never holds more than the burst, however long it sits idle.
+        tokio::time::advance(Duration::from_secs(60)).await;
+        for _ in 0..3 {
+            assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::ZERO);
+        }
+        assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn queues_concurrent_callers_in_order() {
+        let limiter = limiter(Quota::per_second(4).with_burst(1));
+        let start = Instant::now();
+        let waits = futures::future::join_all((0..4).map(|_| {
+            let limiter = limiter.clone();
+            async move {
+                limiter.acquire(EndpointGroup::MarketData).await;
+                start.elapsed()
+            }
+        }))
+        .await;
+        assert_eq!(waits, [0, 250, 500, 750].map(Duration::from_millis));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shares_buckets_between_clones() {
+        let limiter = limiter(Quota::per_second(1));
+        let clone = limiter.clone();
+
+        assert_eq!(timed_acquire(&limiter, EndpointGroup::MarketData).await, Duration::ZERO);
+        assert_eq!(timed_acquire(&clone, EndpointGroup::MarketData).await, Duration::from_secs(1));
+        let stats = WaitStats {
+            requests: 2,
+            delayed: 1,
+            total_wait: Duration::from_secs(1),
+        };
+        assert_eq!((limiter.stats(EndpointGroup::MarketData), clone.stats(EndpointGroup::MarketData)), (stats, stats));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn accounts_waiting_time_per_group() {
+        let limiter = RateLimiter::new([
+            (EndpointGroup::MarketData, Quota::per_second(2).with_burst(1)),
+            (EndpointGroup::Trading, Quota::per_second(1)),
+        ]);
+        for _ in 0..3 {
+            limiter.acquire(EndpointGroup::MarketData).await;
+        }
+        limiter.acquire(EndpointGroup::Trading).await;
+        limiter.acquire(EndpointGroup::Account).await;
+
+        assert_eq!(
+            limiter.stats(EndpointGroup::MarketData),
+            WaitStats {
+                requests: 3,
+                delayed: 2,
+                total_wait: Duration::from_secs(1),
+            }
+        );
+        assert_eq!(
+            limiter.stats(EndpointGroup::Trading),
+            WaitStats {
+                requests: 1,
+                delayed: 0,
+                total_wait: Duration::ZERO,
+            }
+        );
+        // Groups without a quota are neither limited nor counted.
+        assert_eq!(limiter.stats(EndpointGroup::Account), WaitStats::default());
+        assert_eq!(limiter.all_stats().len(), 2);
+    }
+}
@@ -0,0 +1,139 @@
+//! Keeps credentials out of logs, `Debug` output and recorded cassettes.
+
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// Replaces redacted header values, query values and body fields.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers that carry credentials or signatures on one of the markets.
+pub(crate) const SENSITIVE_HEADERS: [&str; 6] = [
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-request-sign",
+    "x-csrftoken",
+];
+/// JSON fields and query parameters that identify the account or hold keys.
+pub(crate) const SENSITIVE_FIELDS: [&str; 6] = ["apiKey", "publicKey", "tradingApiToken", "email", "token", "csrf_token"];
+
+/// A credential such as an API key, a private key or a session cookie.
+///
+/// The value is wiped from memory when dropped, and `Debug` and `Display` print [`REDACTED`]
+/// instead of it, so a secret can sit in a struct that derives `Debug` or end up in a log line
+/// without leaking.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(Zeroizing::new(value.into()))
+    }
+
+    /// The value itself, for the request headers and signatures that need it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Whether the value of header `name` is a credential or a signature.
+pub fn is_sensitive_header(name: &str) -> bool {
+    SENSITIVE_HEADERS.iter().any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+}
+
+/// Formats `headers` for logs, with the values of [sensitive](is_sensitive_header) headers
+/// redacted.
+pub fn headers(headers: &HeaderMap) -> RedactedHeaders<'_> {
+    RedactedHeaders(headers)
+}
+
+/// Formats a response or request body for logs, with the sensitive fields of JSON bodies
+/// redacted.
+pub fn body(body: &str) -> RedactedBody<'_> {
+    RedactedBody(body)
+}
+
+/// See [`headers`].
+pub struct RedactedHeaders<'a>(&'a HeaderMap);
+
+impl fmt::Debug for RedactedHeaders<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for (name, value) in self.0 {
+            if is_sensitive_header(name.as_str()) {
+                map.entry(&name.as_str(), &REDACTED);
+            } else {
+                map.entry(&name.as_str(), &String::from_utf8_lossy(value.as_bytes()));
+            }
+        }
+        map.finish()
+    }
+}
+
+/// See [`body`].
+pub struct RedactedBody<'a>(&'a str);
+
+impl fmt::Display for RedactedBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Ok(mut json) = serde_json::from_str::<Value>(self.0) else {
+            return f.write_str(self.0);
+        };
+        if redact_fields(&mut json, &|name| SENSITIVE_FIELDS.contains(&name)) {
+            write!(f, "{}", json)
+        } else {
+            f.write_str(self.0)
+        }
+    }
+}
+
+/// Redacts the fields of `value` named `is_sensitive` in place and returns whether any was
+/// found.
+pub(crate) fn redact_fields(value: &mut Value, is_sensitive: &dyn Fn(&str) -> bool) -> bool {
+    match value {
+        Value::Object(fields) => {
+            let mut redacted = false;
+            for (name, field) in fields.iter_mut() {
+                if is_sensitive(name) {
+                    *field = Value::String(REDACTED.to_string());
+                    redacted = true;
+                } else {
+                    redacted |= redact_fields(field, is_sensitive);
+                }
+            }
+            redacted
+        }
+        Value::Array(items) => items.iter_mut().fold(false, |redacted, item| redact_fields(item, is_sensitive) | redacted),
+        _ => false,
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use tracing::warn;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, Response, StatusCode};
+use std::time::Duration;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Whether a request can safely be sent more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idempotency {
+    /// Repeating the request has the same effect as sending it once.
+    Idempotent,
+    /// Repeating the request may repeat its effect, e.g. buying an item twice. Such requests are
+    /// only retried when the connection failed, before anything was sent.
+    NonIdempotent,
+}
+
+impl From<&Method> for Idempotency {
+    fn from(method: &Method) -> Self {
+        if method.is_idempotent() {
+            Idempotency::Idempotent
+        } else {
+            Idempotency::NonIdempotent
+        }
+    }
+}
+
+/// How requests are retried after transient failures.
+///
+/// The delay before retry `n` is `initial_backoff * 2^(n - 1)`, capped at `max_backoff`, with
+/// up to half of it randomly taken off when `jitter` is set. A `Retry-After` header on the
+/// response replaces the computed delay; if it asks for more than `max_backoff`, the response is
+/// returned as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub jitter: bool,
+    /// Statuses retried for idempotent requests.
+    pub retryable_statuses: Vec<StatusCode>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            jitter: true,
+            retryable_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff.max(initial_backoff);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_retryable_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// The delay before retrying after failed attempt number `attempt`, starting at 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        if self.jitter {
+            delay.mul_f64(1.0 - fastrand::f64() / 2.0)
+        } else {
+            delay
+        }
+    }
+
+    /// How long to wait before retrying after attempt number `attempt` ended with `result`, or
+    /// `None` if it should not be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        idempotency: Idempotency,
+        attempt: u32,
+        result: &Result<Response, reqwest::Error>,
+    ) -> Option<Duration> {
+        let retry_after = match result {
+            Ok(response) if self.retries_status(idempotency, response.status()) => retry_after(response.headers()),
+            Err(e) if retries_error(idempotency, e) => None,
+            _ => return None,
+        };
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let delay = retry_after.unwrap_or_else(|| self.backoff(attempt));
+        if delay > self.max_backoff {
+            warn!("Not retrying, server asked to wait {:?}", delay);
+            return None;
+        }
+        Some(delay)
+    }
+
+    fn retries_status(&self, idempotency: Idempotency, status: StatusCode) -> bool {
+        idempotency == Idempotency::Idempotent && self.retryable_statuses.contains(&status)
+    }
+}
+
+/// Whether a failed send is worth retrying. Only connection failures are retried for
+/// non-idempotent requests, since the request may otherwise have reached the server.
+fn retries_error(idempotency: Idempotency, error: &reqwest::Error) -> bool {
+    match idempotency {
+        Idempotency::Idempotent => error.is_connect() || error.is_timeout() || error.is_request(),
+        Idempotency::NonIdempotent => error.is_connect(),
+    }
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or_default())
+}
@@ -0,0 +1,35 @@
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Proxy};
+use std::time::Duration;
+
+/// HTTP options collected by the client builders.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpSettings {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) proxy: Option<Proxy>,
+    pub(crate) client: Option<Client>,
+}
+
+impl HttpSettings {
+    /// The caller's client if one was given, otherwise a new one built from the settings.
+    pub(crate) fn build(self, default_user_agent: &str, default_headers: HeaderMap) -> reqwest::Result<Client> {
+        if let Some(client) = self.client {
+            return Ok(client);
+        }
+        let mut builder = Client::builder()
+            .user_agent(self.user_agent.as_deref().unwrap_or(default_user_agent))
+            .default_headers(default_headers);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        builder.build()
+    }
+}
@@ -0,0 +1,154 @@
+use reqwest::{Method, RequestBuilder, Response, Url};
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::{warn, Instrument, Span};
+
+use super::{Cassette, CassetteError, EndpointGroup, Idempotency, RateLimiter, RetryPolicy};
+use crate::marketplace::Market;
+
+/// Query parameters that locate a page of a paginated listing.
+const PAGINATION_PARAMS: [&str; 2] = ["cursor", "page_num"];
+
+/// The span of one client call to `url`. [`Transport::send`] records its `status`, `latency_ms`
+/// and `retries`, or the `error` it failed with.
+pub(crate) fn request_span(market: Market, method: &Method, url: &str) -> Span {
+    let (endpoint, cursor) = match Url::parse(url) {
+        Ok(url) => {
+            let cursor = url
+                .query_pairs()
+                .find(|(name, _)| PAGINATION_PARAMS.contains(&name.as_ref()))
+                .map(|(_, value)| value.into_owned());
+            (url.path().to_string(), cursor)
+        }
+        Err(_) => (url.split('?').next().unwrap_or(url).to_string(), None),
+    };
+    tracing::debug_span!(
+        "market_request",
+        market = %market,
+        endpoint = %endpoint,
+        method = %method,
+        cursor = cursor.as_deref(),
+        status = Empty,
+        latency_ms = Empty,
+        retries = Empty,
+        error = Empty,
+    )
+}
+
+/// Sends a client's requests, waiting on its [`RateLimiter`] before every attempt and retrying
+/// according to its [`RetryPolicy`]. With a [`Cassette`], requests are recorded or replayed.
+#[derive(Debug, Clone)]
+pub struct Transport {
+    retry: RetryPolicy,
+    limiter: RateLimiter,
+    cassette: Option<Cassette>,
+}
+
+impl Transport {
+    pub fn new(retry: RetryPolicy, limiter: RateLimiter) -> Self {
+        Self {
+            retry,
+            limiter,
+            cassette: None,
+        }
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_ref()
+    }
+
+    pub(crate) fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    pub(crate) fn set_rate_limiter(&mut self, limiter: RateLimiter) {
+        self.limiter = limiter;
+    }
+
+    pub(crate) fn set_cassette(&mut self, cassette: Cassette) {
+        self.cassette = Some(cassette);
+    }
+
+    /// Sends the request built by `build` until it succeeds, fails permanently or runs out of
+    /// attempts. `build` is called again for every attempt, so it can refresh timestamps and
+    /// signatures. Replayed requests skip the rate limiter and retry delays.
+    ///
+    /// The attempts run in `span`, see [`request_span`].
+    pub(crate) async fn send<F, E>(
+        &self,
+        span: Span,
+        group: EndpointGroup,
+        idempotency: Idempotency,
+        build: F,
+    ) -> Result<Response, E>
+    where
+        F: FnMut() -> Result<RequestBuilder, E>,
+        E: From<reqwest::Error> + From<CassetteError>,
+    {
+        let started = Instant::now();
+        let mut attempts = 0;
+        let result = self
+            .send_attempts(&span, group, idempotency, build, &mut attempts)
+            .instrument(span.clone())
+            .await;
+        span.record("retries", attempts.saturating_sub(1));
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        result
+    }
+
+    async fn send_attempts<F, E>(
+        &self,
+        span: &Span,
+        group: EndpointGroup,
+        idempotency: Idempotency,
+        mut build: F,
+        attempt: &mut u32,
+    ) -> Result<Response, E>
+    where
+        F: FnMut() -> Result<RequestBuilder, E>,
+        E: From<reqwest::Error> + From<CassetteError>,
+    {
+        let replaying = self.cassette.as_ref().is_some_and(Cassette::is_replaying);
+        loop {
+            *attempt += 1;
+            if !replaying {
+                self.limiter.acquire(group).await;
+            }
+            let result = match &self.cassette {
+                Some(cassette) => cassette.send(build()?).await?,
+                None => build()?.send().await,
+            };
+            if let Ok(response) = &result {
+                span.record("status", response.status().as_u16());
+            }
+            let Some(delay) = self.retry.retry_delay(idempotency, *attempt, &result) else {
+                if let Err(e) = &result {
+                    span.record("error", tracing::field::display(e));
+                }
+                return Ok(result?);
+            };
+            let reason = match &result {
+                Ok(response) => response.status().to_string(),
+                Err(e) => e.to_string(),
+            };
+            warn!(
+                "Request failed ({}), retrying in {:?} (attempt {}/{})",
+                reason,
+                delay,
+                *attempt + 1,
+                self.retry.max_attempts
+            );
+            if !replaying {
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! Structured item identities parsed from `market_hash_name` strings.
+//!
+//! Every market keys items by the Steam `market_hash_name` (DMarket calls it `title`), e.g.
+//! `"StatTrak™ AK-47 | Redline (Field-Tested)"`. [`ItemIdentity`] splits such a name into its
+//! parts, and [`ItemIdentity::market_hash_name`] formats it back canonically, so names coming from
+//! different markets can be joined reliably.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+const STAR: &str = "★";
+const STATTRAK: &str = "StatTrak™";
+const SOUVENIR: &str = "Souvenir";
+
+/// Weapons that can carry a skin.
+const WEAPONS: &[&str] = &[
+    "AK-47", "AUG", "AWP", "CZ75-Auto", "Desert Eagle", "Dual Berettas", "FAMAS", "Five-SeveN",
+    "G3SG1", "Galil AR", "Glock-18", "M249", "M4A1-S", "M4A4", "MAC-10", "MAG-7", "MP5-SD", "MP7",
+    "MP9", "Negev", "Nova", "P2000", "P250", "P90", "PP-Bizon", "R8 Revolver", "Sawed-Off",
+    "SCAR-20", "SG 553", "SSG 08", "Tec-9", "UMP-45", "USP-S", "XM1014", "Zeus x27",
+];
+
+/// Factions that appear after the `|` in agent names.
+const AGENT_FACTIONS: &[&str] = &[
+    "The Professionals", "Elite Crew", "Phoenix", "Sabre", "Sabre Footsoldier", "Guerrilla Warfare",
+    "FBI", "FBI SWAT", "FBI HRT", "FBI Sniper", "SWAT", "NSWC SEAL", "SEAL Frogmen", "KSK", "SAS",
+    "NZSAS", "USAF TACP", "TACP Cavalry", "Gendarmerie Nationale", "Brazilian 1st Battalion",
+];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IdentityError {
+    #[error("Empty item name")]
+    Empty,
+
+    #[error("Unknown {kind}: {value}")]
+    Unknown { kind: &'static str, value: String },
+}
+
+/// Broad category of an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ItemKind {
+    Weapon,
+    Knife,
+    Gloves,
+    Sticker,
+    Agent,
+    /// Cases, capsules, packages and keys.
+    Container,
+    Graffiti,
+    Patch,
+    MusicKit,
+    Other,
+}
+
+/// Wear tier of a skin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Exterior {
+    FactoryNew,
+    MinimalWear,
+    FieldTested,
+    WellWorn,
+    BattleScarred,
+}
+
+impl Exterior {
+    pub const ALL: [Exterior; 5] = [
+        Exterior::FactoryNew,
+        Exterior::MinimalWear,
+        Exterior::FieldTested,
+        Exterior::WellWorn,
+        Exterior::BattleScarred,
+    ];
+
+    /// The name used in `market_hash_name`, e.g. `"Field-Tested"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Exterior::FactoryNew => "Factory New",
+            Exterior::MinimalWear => "Minimal Wear",
+            Exterior::FieldTested => "Field-Tested",
+            Exterior::WellWorn => "Well-Worn",
+            Exterior::BattleScarred => "Battle-Scarred",
+        }
+    }
+}
+
+impl fmt::Display for Exterior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Exterior {
+    type Err = IdentityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Exterior::ALL
+            .into_iter()
+            .find(|exterior| exterior.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| IdentityError::Unknown {
+                kind: "exterior",
+                value: s.to_string(),
+            })
+    }
+}
+
+/// Pattern phase of Doppler and Gamma Doppler finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DopplerPhase {
+    Phase1,
+    Phase2,
+    Phase3,
+    Phase4,
+    Ruby,
+    Sapphire,
+    BlackPearl,
+    Emerald,
+}
+
+impl DopplerPhase {
+    pub const ALL: [DopplerPhase; 8] = [
+        DopplerPhase::Phase1,
+        DopplerPhase::Phase2,
+        DopplerPhase::Phase3,
+        DopplerPhase::Phase4,
+        DopplerPhase::Ruby,
+        DopplerPhase::Sapphire,
+        DopplerPhase::BlackPearl,
+        DopplerPhase::Emerald,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DopplerPhase::Phase1 => "Phase 1",
+            DopplerPhase::Phase2 => "Phase 2",
+            DopplerPhase::Phase3 => "Phase 3",
+            DopplerPhase::Phase4 => "Phase 4",
+            DopplerPhase::Ruby => "Ruby",
+            DopplerPhase::Sapphire => "Sapphire",
+            DopplerPhase::BlackPearl => "Black Pearl",
+            DopplerPhase::Emerald => "Emerald",
+        }
+    }
+}
+
+impl fmt::Display for DopplerPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DopplerPhase {
+    type Err = IdentityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().replace(['-', '_'], " ");
+        DopplerPhase::ALL
+            .into_iter()
+            .find(|phase| {
+                phase.name().eq_ignore_ascii_case(&normalized)
+                    || phase.name().replace(' ', "").eq_ignore_ascii_case(&normalized.replace(' ', ""))
+            })
+            .ok_or_else(|| IdentityError::Unknown {
+                kind: "Doppler phase",
+                value: s.to_string(),
+            })
+    }
+}
+
+/// The parts of an item's `market_hash_name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ItemIdentity {
+    pub kind: ItemKind,
+    /// The part before the first `|`: the weapon (`"AK-47"`), knife or glove type, agent name, or
+    /// the item type (`"Sticker"`, `"Sealed Graffiti"`).
+    pub base: String,
+    /// The part after the first `|` without the exterior: the skin, sticker or faction name.
+    pub name: Option<String>,
+    pub exterior: Option<Exterior>,
+    pub stattrak: bool,
+    pub souvenir: bool,
+    /// The `★` marker of knives and gloves.
+    pub star: bool,
+    /// Doppler phase, which markets report outside of `market_hash_name`.
+    pub phase: Option<DopplerPhase>,
+}
+
+impl ItemIdentity {
+    /// Parses a `market_hash_name` or DMarket title.
+    ///
+    /// A trailing `" - Phase 2"`-style suffix, as used by some markets, is read as the Doppler
+    /// phase.
+    pub fn parse(name: &str) -> Result<Self, IdentityError> {
+        let mut rest = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if rest.is_empty() {
+            return Err(IdentityError::Empty);
+        }
+
+        let mut phase = None;
+        if let Some((head, suffix)) = rest.rsplit_once(" - ") {
+            if let Ok(parsed) = suffix.parse::<DopplerPhase>() {
+                phase = Some(parsed);
+                rest = head.to_string();
+            }
+        }
+
+        let mut rest = rest.as_str();
+        let star = strip_word(&mut rest, STAR);
+        let stattrak = strip_word(&mut rest, STATTRAK) || strip_word(&mut rest, "StatTrak");
+        let souvenir = strip_word(&mut rest, SOUVENIR);
+
+        let (base, name) = match rest.split_once(" | ") {
+            Some((base, name)) => (base.trim(), Some(name.trim())),
+            None => (rest.trim(), None),
+        };
+
+        // The exterior is the trailing parenthesised part, when it names a wear tier.
+        let mut exterior = None;
+        let mut name = name.map(str::to_string);
+        if let Some(full) = name.as_deref() {
+            if let Some((skin, tail)) = full.rsplit_once(" (") {
+                if let Some(parsed) = tail.strip_suffix(')').and_then(|tail| tail.parse().ok()) {
+                    exterior = Some(parsed);
+                    name = Some(skin.to_string());
+                }
+            }
+        }
+
+        let kind = classify(star, base, name.as_deref(), exterior);
+
+        Ok(ItemIdentity {
+            kind,
+            base: base.to_string(),
+            name,
+            exterior,
+            stattrak,
+            souvenir,
+            star,
+            phase,
+        })
+    }
+
+    /// Returns the identity with `phase` set.
+    pub fn with_phase(mut self, phase: Option<DopplerPhase>) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Formats the canonical Steam `market_hash_name`, which never includes the phase.
+    pub fn market_hash_name(&self) -> String {
+        let mut out = String::new();
+        if self.star {
+            out.push_str(STAR);
+            out.push(' ');
+        }
+        if self.stattrak {
+            out.push_str(STATTRAK);
+            out.push(' ');
+        }
+        if self.souvenir {
+            out.push_str(SOUVENIR);
+            out.push(' ');
+        }
+        out.push_str(&self.base);
+        if let Some(name) = &self.name {
+            out.push_str(" | ");
+            out.push_str(name);
+        }
+        if let Some(exterior) = self.exterior {
+            out.push_str(" (");
+            out.push_str(exterior.name());
+            out.push(')');
+        }
+        out
+    }
+}
+
+/// Formats the `market_hash_name` followed by `" - <phase>"` when a phase is set.
+impl fmt::Display for ItemIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.market_hash_name())?;
+        if let Some(phase) = self.phase {
+            write!(f, " - {}", phase)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ItemIdentity {
+    type Err = IdentityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ItemIdentity::parse(s)
+    }
+}
+
+/// Removes `word` and the following space from the start of `rest`, returning whether it was there.
+fn strip_word(rest: &mut &str, word: &str) -> bool {
+    match rest.strip_prefix(word) {
+        Some(stripped) if stripped.is_empty() || stripped.starts_with(' ') => {
+            *rest = stripped.trim_start();
+            true
+        }
+        _ => false,
+    }
+}
+
+fn classify(star: bool, base: &str, name: Option<&str>, exterior: Option<Exterior>) -> ItemKind {
+    if star {
+        return if base.ends_with("Gloves") || base.ends_with("Hand Wraps") {
+            ItemKind::Gloves
+        } else {
+            ItemKind::Knife
+        };
+    }
+    match base {
+        "Sticker" => return ItemKind::Sticker,
+        "Sealed Graffiti" | "Graffiti" => return ItemKind::Graffiti,
+        "Patch" => return ItemKind::Patch,
+        "Music Kit" => return ItemKind::MusicKit,
+        _ => {}
+    }
+    if WEAPONS.contains(&base) || exterior.is_some() {
+        return ItemKind::Weapon;
+    }
+    if name.is_some_and(|name| AGENT_FACTIONS.contains(&name)) {
+        return ItemKind::Agent;
+    }
+    if name.is_none()
+        && ["Case", "Capsule", "Package", "Key", "Case Key"]
+            .iter()
+            .any(|suffix| base.ends_with(suffix))
+    {
+        return ItemKind::Container;
+    }
+    ItemKind::Other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `name`, checks it formats back unchanged and returns the identity.
+    fn round_trip(name: &str) -> ItemIdentity {
+        let identity = ItemIdentity::parse(name).unwrap();
+        assert_eq!(identity.to_string(), name);
+        identity
+    }
+
+    #[test]
+    fn round_trips_stattrak_and_souvenir_weapons() {
+        let identity = round_trip("StatTrak™ AK-47 | Redline (Field-Tested)");
+        assert_eq!(identity.kind, ItemKind::Weapon);
+        assert_eq!(identity.base, "AK-47");
+        assert_eq!(identity.name.as_deref(), Some("Redline"));
+        assert_eq!(identity.exterior, Some(Exterior::FieldTested));
+        assert!(identity.stattrak && !identity.souvenir && !identity.star);
+
+        let identity = round_trip("Souvenir AWP | Dragon Lore (Minimal Wear)");
+        assert_eq!(identity.kind, ItemKind::Weapon);
+        assert!(identity.souvenir && !identity.stattrak);
+        assert_eq!(identity.exterior, Some(Exterior::MinimalWear));
+
+        // Steam names without the trademark sign format back with it.
+        let identity = ItemIdentity::parse("StatTrak M4A4 | Howl (Factory New)").unwrap();
+        assert_eq!(identity.market_hash_name(), "StatTrak™ M4A4 | Howl (Factory New)");
+    }
+
+    #[test]
+    fn round_trips_knives_and_gloves() {
+        let identity = round_trip("★ StatTrak™ Karambit | Fade (Factory New)");
+        assert_eq!(identity.kind, ItemKind::Knife);
+        assert_eq!(identity.base, "Karambit");
+        assert!(identity.star && identity.stattrak);
+
+        let identity = round_trip("★ Karambit");
+        assert_eq!(identity.kind, ItemKind::Knife);
+        assert_eq!((identity.name, identity.exterior), (None, None));
+
+        let identity = round_trip("★ Sport Gloves | Vice (Well-Worn)");
+        assert_eq!(identity.kind, ItemKind::Gloves);
+        assert_eq!(identity.exterior, Some(Exterior::WellWorn));
+    }
+
+    #[test]
+    fn round_trips_doppler_phases() {
+        let identity = round_trip("★ Karambit | Doppler (Factory New) - Phase 2");
+        assert_eq!(identity.phase, Some(DopplerPhase::Phase2));
+        assert_eq!(identity.name.as_deref(), Some("Doppler"));
+        assert_eq!(identity.market_hash_name(), "★ Karambit | Doppler (Factory New)");
+
+        let identity = round_trip("★ Butterfly Knife | Gamma Doppler (Minimal Wear) - Emerald");
+        assert_eq!(identity.phase, Some(DopplerPhase::Emerald));
+        assert_eq!(identity.name.as_deref(), Some("Gamma Doppler"));
+
+        let identity = ItemIdentity::parse("★ Bayonet | Doppler (Factory New) - black-pearl").unwrap();
+        assert_eq!(identity.phase, Some(DopplerPhase::BlackPearl));
+        assert_eq!(identity.to_string(), "★ Bayonet | Doppler (Factory New) - Black Pearl");
+    }
+
+    #[test]
+    fn round_trips_stickers_agents_and_containers() {
+        let identity = round_trip("Sticker | Natus Vincere (Holo) | Katowice 2014");
+        assert_eq!(identity.kind, ItemKind::Sticker);
+        assert_eq!(identity.name.as_deref(), Some("Natus Vincere (Holo) | Katowice 2014"));
+        assert_eq!(identity.exterior, None);
+
+        let identity = round_trip("Sir Bloody Miami Darryl | The Professionals");
+        assert_eq!(identity.kind, ItemKind::Agent);
+        assert_eq!(identity.base, "Sir Bloody Miami Darryl");
+
+        assert_eq!(round_trip("Revolution Case").kind, ItemKind::Container);
+        assert_eq!(round_trip("Sealed Graffiti | Lambda (Bazooka Pink)").kind, ItemKind::Graffiti);
+        assert_eq!(round_trip("Music Kit | Darude, Moments CS:GO").kind, ItemKind::MusicKit);
+    }
+
+    #[test]
+    fn normalizes_whitespace_and_rejects_empty_names() {
+        let identity = ItemIdentity::parse("  AK-47 |  Redline   (Field-Tested) ").unwrap();
+        assert_eq!(identity.market_hash_name(), "AK-47 | Redline (Field-Tested)");
+        assert_eq!(ItemIdentity::parse("   "), Err(IdentityError::Empty));
+    }
+}
@@ -1,27 +1,64 @@
 pub mod dmarket;
 pub mod csfloat;
 pub mod buff_market;
+pub mod aggregator;
+pub mod arbitrage;
+pub mod catalog;
+pub mod collector;
+pub mod error;
+pub mod fees;
+pub mod fx;
+pub mod http;
+pub mod identity;
+pub mod listing;
+pub mod marketplace;
+pub mod money;
+pub mod storage;
 
-pub use crate::dmarket::client::DMarketClient;
+pub use crate::aggregator::{Aggregator, BestPrice, ItemAggregate, MarketFailure, MarketQuote, OverallQuote};
+pub use crate::arbitrage::{ArbitrageOpportunity, ArbitrageScanner, Exit, Ranking};
+pub use crate::catalog::{CSFloatIds, CatalogError, ItemCatalog, MarketIds};
+pub use crate::collector::{Collector, CollectorConfig, CollectorError, JobConfig, JobKind, LogSink, Sink, StorageSink};
+pub use crate::error::{Error, ErrorCategory, Result};
+pub use crate::fees::{FeeModel, FeeSchedule, FeeTable};
+pub use crate::http::{
+    ApiErrorKind, Cassette, CassetteError, CassetteMode, EndpointGroup, Idempotency, MarketApiError, Quota,
+    RateLimiter, RetryPolicy, Secret, Transport, WaitStats,
+};
+pub use crate::identity::{DopplerPhase, Exterior, IdentityError, ItemIdentity, ItemKind};
+pub use crate::listing::{NormalizedListing, NormalizedSticker, RawListing, SellerInfo, Tradability};
+pub use crate::marketplace::{
+    Capabilities, InventoryAsset, Market, MarketBid, Marketplace, MarketplaceError, Operation,
+};
+pub use crate::money::{Currency, Money, MoneyError};
+pub use crate::storage::{ListingSnapshot, PricePoint, Storage, StorageError};
+
+pub use crate::dmarket::client::{DMarketClient, DMarketClientBuilder};
 pub use crate::dmarket::error::DMarketError;
+pub use crate::dmarket::order_book::{BidLevel, OrderBook, OrderBookQuery};
+pub use crate::dmarket::pagination::Paginated;
+pub use crate::dmarket::query::{ItemCategory, MarketItemsOrder, MarketItemsQuery, OfferType, OrderDirection, TreeFilter};
+pub use crate::dmarket::sales::{LastSalesQuery, SaleType, SalesPeriod, SalesStats};
 pub use crate::dmarket::models::{
     UserProfile, Balance, ApiResponse, ApiError,
-    MarketItemsResponse, MarketItem, Price, MarketItemExtra, MarketItemAttribute,
+    MarketItemsResponse, MarketItem, MarketItemExtra, MarketItemAttribute,
+    AggregatedPricesRequest, AggregatedPricesFilter, AggregatedPricesResponse, AggregatedPrice,
+    LastSalesResponse, LastSale,
     Game,
     InventoryResponse, InventoryItem, InventoryItemExtra,
-    SellOfferRequest, SellOfferItem, SellOfferPrice, SellOfferResponse, SellOfferResponseItem,
+    SellOfferRequest, SellOfferItem, SellOfferResponse, SellOfferResponseItem,
     BuyOfferRequest, BuyOfferResponse, BuyOfferResponseItem,
     ImportRequest, ImportItem, ImportResponse, ImportedItem,
     ExportRequest, ExportResponse, ExportedItem,
-    Target, CreateTargetRequest, CreateTargetResponse, CreateTargetResponseItem, TargetListResponse, DeleteTargetRequest,
+    Target, BuyOrder, CreateTargetRequest, CreateTargetResponse, CreateTargetResponseItem, TargetListResponse, DeleteTargetRequest,
 };
 
-pub use crate::csfloat::client::CSFloatClient;
+pub use crate::csfloat::client::{CSFloatClient, CSFloatClientBuilder};
 pub use crate::csfloat::error::CSFloatError;
 pub use crate::csfloat::models::{Listing, ListingResponse};
 pub use crate::csfloat::endpoints::listings::{ListingsQuery, CreateListingRequest};
 
-pub use crate::buff_market::BuffMarketClient;
+pub use crate::buff_market::{BuffMarketClient, BuffMarketClientBuilder};
 pub use crate::buff_market::BuffMarketError;
 // Potentially re-export models from buff_market too, if needed directly by consumers of the library
 // pub use crate::buff_market::models::*; 
\ No newline at end of file
@@ -0,0 +1,345 @@
+//! A market-independent view of an item offered for sale.
+//!
+//! [`NormalizedListing`] unifies `dmarket::MarketItem`, `csfloat::Listing` and
+//! `buff_market::MarketGoodsItem`. The original model is kept in [`NormalizedListing::raw`], so
+//! fields without a normalized counterpart are still available.
+
+use serde::{Deserialize, Serialize};
+
+use crate::buff_market::MarketGoodsItem;
+use crate::csfloat::models::Listing;
+use crate::dmarket::models::MarketItem;
+use crate::identity::{IdentityError, ItemIdentity};
+use crate::marketplace::Market;
+use crate::money::Money;
+
+/// An item for sale on any market, with prices and item details in one shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedListing {
+    pub market: Market,
+    /// The market's own identifier for the offer or goods entry.
+    pub external_id: String,
+    pub market_hash_name: String,
+    pub price: Money,
+    pub float_value: Option<f64>,
+    pub paint_seed: Option<i32>,
+    pub stickers: Vec<NormalizedSticker>,
+    pub tradability: Tradability,
+    pub seller: Option<SellerInfo>,
+    /// The payload this listing was converted from.
+    pub raw: RawListing,
+}
+
+/// A sticker applied to a listed item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizedSticker {
+    pub name: String,
+    pub slot: Option<i32>,
+    pub wear: Option<f64>,
+}
+
+/// Whether a listed item can be traded right away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tradability {
+    Tradable,
+    /// Trade locked, with the remaining number of days when the market reports it.
+    Locked { days_remaining: Option<u32> },
+    Unknown,
+}
+
+/// Public information about the seller of a listing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SellerInfo {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub online: Option<bool>,
+    pub total_trades: Option<i32>,
+}
+
+/// The market-specific payload a [`NormalizedListing`] was built from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RawListing {
+    DMarket(MarketItem),
+    CSFloat(Listing),
+    BuffMarket(MarketGoodsItem),
+}
+
+impl NormalizedListing {
+    /// Parses the listing's `market_hash_name`, taking the Doppler phase from DMarket's `phase`
+    /// attribute when present.
+    pub fn identity(&self) -> Result<ItemIdentity, IdentityError> {
+        let identity = ItemIdentity::parse(&self.market_hash_name)?;
+        let phase = match &self.raw {
+            RawListing::DMarket(item) => item
+                .attributes
+                .iter()
+                .find(|attribute| attribute.name == "phase")
+                .and_then(|attribute| attribute.value.parse().ok()),
+            _ => None,
+        };
+        Ok(match phase {
+            Some(phase) => identity.with_phase(Some(phase)),
+            None => identity,
+        })
+    }
+
+    /// Number of offers the listing stands for: a Buff goods entry summarizes every offer of its
+    /// item, while the other markets list offers one by one.
+    pub fn offer_count(&self) -> usize {
+        match &self.raw {
+            RawListing::BuffMarket(item) => item.sell_num.and_then(|num| usize::try_from(num).ok()).unwrap_or(1),
+            _ => 1,
+        }
+    }
+}
+
+impl From<MarketItem> for NormalizedListing {
+    fn from(item: MarketItem) -> Self {
+        let paint_seed = item
+            .attributes
+            .iter()
+            .find(|attribute| attribute.name == "paintSeed")
+            .and_then(|attribute| attribute.value.parse().ok());
+
+        let days_locked = item.trade_lock.or(item.extra.days_before_trade).unwrap_or(0);
+        let tradability = if days_locked > 0 {
+            Tradability::Locked { days_remaining: u32::try_from(days_locked).ok() }
+        } else {
+            match item.extra.tradable {
+                Some(true) => Tradability::Tradable,
+                Some(false) => Tradability::Locked { days_remaining: None },
+                None => Tradability::Unknown,
+            }
+        };
+
+        NormalizedListing {
+            market: Market::DMarket,
+            external_id: item.item_id.clone(),
+            market_hash_name: item.title.clone(),
+            price: item.price,
+            float_value: item.extra.float_value,
+            paint_seed,
+            stickers: Vec::new(),
+            tradability,
+            seller: None,
+            raw: RawListing::DMarket(item),
+        }
+    }
+}
+
+impl From<Listing> for NormalizedListing {
+    fn from(listing: Listing) -> Self {
+        let stickers = listing
+            .item
+            .stickers
+            .iter()
+            .map(|sticker| NormalizedSticker {
+                name: sticker.name.clone(),
+                slot: Some(sticker.slot),
+                wear: sticker.wear,
+            })
+            .collect();
+
+        let tradability = match listing.item.tradable {
+            Some(0) => Tradability::Locked { days_remaining: None },
+            Some(_) => Tradability::Tradable,
+            None => Tradability::Unknown,
+        };
+
+        let seller = SellerInfo {
+            id: listing.seller.steam_id.clone().or_else(|| listing.seller.obfuscated_id.clone()),
+            name: listing.seller.username.clone(),
+            online: Some(listing.seller.online),
+            total_trades: Some(listing.seller.statistics.total_trades),
+        };
+
+        NormalizedListing {
+            market: Market::CSFloat,
+            external_id: listing.id.clone(),
+            market_hash_name: listing.item.market_hash_name.clone(),
+            price: listing.price,
+            float_value: listing.item.float_value,
+            paint_seed: listing.item.paint_seed,
+            stickers,
+            tradability,
+            seller: Some(seller),
+            raw: RawListing::CSFloat(listing),
+        }
+    }
+}
+
+impl From<MarketGoodsItem> for NormalizedListing {
+    fn from(item: MarketGoodsItem) -> Self {
+        // Goods entries aggregate every offer of an item, so per-item details are unknown.
+        NormalizedListing {
+            market: Market::BuffMarket,
+            external_id: item.goods_internal_id.to_string(),
+            market_hash_name: item.market_hash_name.clone(),
+            price: item.sell_min_price,
+            float_value: None,
+            paint_seed: None,
+            stickers: Vec::new(),
+            tradability: Tradability::Unknown,
+            seller: None,
+            raw: RawListing::BuffMarket(item),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::DopplerPhase;
+    use crate::money::Currency;
+    use serde_json::{json, Value};
+
+    const CSFLOAT_LISTING: &str = include_str!("../tests/fixtures/csfloat/listing.json");
+    const BUFF_GOODS_ITEM: &str = include_str!("../tests/fixtures/buff/goods_item.json");
+
+    fn dmarket_item(extra: Value, trade_lock: Option<i32>, attributes: Value) -> NormalizedListing {
+        let item: MarketItem = serde_json::from_value(json!({
+            "itemId": "c8f0b6d2-6a3e-4b8e-9e1f-000000000000",
+            "type": "dmarket",
+            "title": "★ Karambit | Doppler (Factory New)",
+            "slug": "",
+            "status": "active",
+            "image": "",
+            "classId": "a8db:class0",
+            "game": "csgo",
+            "gameId": "a8db",
+            "price": {"amount": "120000", "currency": "USD"},
+            "extra": extra,
+            "attributes": attributes,
+            "locked": false,
+            "createdAt": 1714000000,
+            "updatedAt": 1714000000,
+            "inMarket": true,
+            "withdrawable": true,
+            "tradeLock": trade_lock
+        }))
+        .unwrap();
+        item.into()
+    }
+
+    fn csfloat_listing(edit: impl FnOnce(&mut Value)) -> NormalizedListing {
+        let mut listing: Value = serde_json::from_str(CSFLOAT_LISTING).unwrap();
+        edit(&mut listing);
+        serde_json::from_value::<Listing>(listing).unwrap().into()
+    }
+
+    #[test]
+    fn dmarket_tradability() {
+        let tradability = |extra, trade_lock| dmarket_item(extra, trade_lock, json!([])).tradability;
+        assert_eq!(tradability(json!({"tradable": true}), Some(3)), Tradability::Locked { days_remaining: Some(3) });
+        assert_eq!(
+            tradability(json!({"tradable": false, "daysBeforeTrade": 5}), None),
+            Tradability::Locked { days_remaining: Some(5) }
+        );
+        // `tradeLock` wins over `daysBeforeTrade`, even when it is zero.
+        assert_eq!(tradability(json!({"tradable": true, "daysBeforeTrade": 5}), Some(0)), Tradability::Tradable);
+        assert_eq!(tradability(json!({"tradable": true}), None), Tradability::Tradable);
+        assert_eq!(tradability(json!({"tradable": false}), Some(0)), Tradability::Locked { days_remaining: None });
+        assert_eq!(tradability(json!({}), None), Tradability::Unknown);
+    }
+
+    #[test]
+    fn dmarket_attributes() {
+        let listing = dmarket_item(
+            json!({"floatValue": 0.012}),
+            None,
+            json!([
+                {"name": "paintSeed", "category": "attributes", "value": "412"},
+                {"name": "phase", "category": "attributes", "value": "phase-2"}
+            ]),
+        );
+        assert_eq!(listing.market, Market::DMarket);
+        assert_eq!(listing.price, Money::from_minor(120000, Currency::USD));
+        assert_eq!((listing.float_value, listing.paint_seed), (Some(0.012), Some(412)));
+        let identity = listing.identity().unwrap();
+        assert_eq!(identity.phase, Some(DopplerPhase::Phase2));
+        assert_eq!(identity.market_hash_name(), "★ Karambit | Doppler (Factory New)");
+        assert_eq!(listing.offer_count(), 1);
+
+        let plain = dmarket_item(json!({}), None, json!([{"name": "paintSeed", "category": "attributes", "value": "n/a"}]));
+        assert_eq!(plain.paint_seed, None);
+        assert_eq!(plain.identity().unwrap().phase, None);
+    }
+
+    #[test]
+    fn csfloat_tradability() {
+        let tradability = |tradable: Option<i32>| {
+            csfloat_listing(|listing| match tradable {
+                Some(tradable) => listing["item"]["tradable"] = json!(tradable),
+                None => {
+                    listing["item"].as_object_mut().unwrap().remove("tradable");
+                }
+            })
+            .tradability
+        };
+        assert_eq!(tradability(Some(0)), Tradability::Locked { days_remaining: None });
+        assert_eq!(tradability(Some(1)), Tradability::Tradable);
+        assert_eq!(tradability(None), Tradability::Unknown);
+    }
+
+    #[test]
+    fn csfloat_details() {
+        let listing = csfloat_listing(|listing| {
+            listing["item"]["stickers"] = json!([
+                {"stickerId": 4981, "slot": 2, "wear": 0.15, "icon_url": "", "name": "Sticker | Natus Vincere (Holo) | Stockholm 2021"},
+                {"stickerId": 5012, "slot": 0, "icon_url": "", "name": "Sticker | Crown (Foil)"}
+            ]);
+        });
+        assert_eq!(listing.market, Market::CSFloat);
+        assert_eq!(listing.external_id, "700000000000000000");
+        assert_eq!(listing.price, Money::from_minor(1250, Currency::USD));
+        assert_eq!((listing.float_value, listing.paint_seed), (Some(0.2534), Some(661)));
+        assert_eq!(
+            listing.stickers,
+            [
+                NormalizedSticker {
+                    name: "Sticker | Natus Vincere (Holo) | Stockholm 2021".to_string(),
+                    slot: Some(2),
+                    wear: Some(0.15),
+                },
+                NormalizedSticker { name: "Sticker | Crown (Foil)".to_string(), slot: Some(0), wear: None },
+            ]
+        );
+        assert_eq!(
+            listing.seller,
+            Some(SellerInfo {
+                id: Some("76561198000000001".to_string()),
+                name: Some("mock-seller".to_string()),
+                online: Some(true),
+                total_trades: Some(57),
+            })
+        );
+        assert_eq!(listing.offer_count(), 1);
+    }
+
+    #[test]
+    fn csfloat_seller_falls_back_to_obfuscated_id() {
+        let listing = csfloat_listing(|listing| listing["seller"]["steam_id"] = Value::Null);
+        assert_eq!(listing.seller.unwrap().id.as_deref(), Some("1234567890"));
+
+        let listing = csfloat_listing(|listing| {
+            listing["seller"]["steam_id"] = Value::Null;
+            listing["seller"]["obfuscated_id"] = Value::Null;
+        });
+        assert_eq!(listing.seller.unwrap().id, None);
+    }
+
+    #[test]
+    fn buff_goods_entry() {
+        let item: MarketGoodsItem = serde_json::from_str(BUFF_GOODS_ITEM).unwrap();
+        let listing = NormalizedListing::from(item.clone());
+        assert_eq!(listing.market, Market::BuffMarket);
+        assert_eq!(listing.external_id, "33815");
+        assert_eq!(listing.price, Money::from_minor(8650, Currency::CNY));
+        assert_eq!(listing.tradability, Tradability::Unknown);
+        assert_eq!(listing.seller, None);
+        assert_eq!(listing.offer_count(), 1289);
+
+        let unknown_count = NormalizedListing::from(MarketGoodsItem { sell_num: None, ..item });
+        assert_eq!(unknown_count.offer_count(), 1);
+    }
+}
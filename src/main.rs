@@ -2,8 +2,7 @@ use log::{error, info, debug};
 use backend::BuffMarketClient;
 use backend::CSFloatClient;
 use backend::DMarketClient;
-
-mod dmarket;
+use backend::{MarketItemsOrder, MarketItemsQuery, OrderDirection};
 
 #[tokio::main]
 async fn main() {
@@ -35,7 +34,7 @@ async fn main() {
                 Ok(balances) => {
                     info!("Successfully retrieved account balance:");
                     for balance in balances {
-                        info!("{}: {}", balance.currency, balance.amount);
+                        info!("{}: {}", balance.amount.currency(), balance.amount);
                     }
                 }
                 Err(e) => {
@@ -45,24 +44,14 @@ async fn main() {
             
             // Get market items (CS2 items)
             info!("Retrieving market items for CS2...");
-            match client.exchange().get_market_items(
-                "a8db", // game_id (CS2)
-                "USD",  // currency
-                5,      // limit
-                0,      // offset
-                Some("title"), // order_by
-                Some("desc"),  // order_dir
-                None,   // title (for filtering by specific title)
-                None,   // tree_filters
-                None,   // price_from
-                None,   // price_to
-                None,   // types
-                None    // cursor
-            ).await {
+            let query = MarketItemsQuery::new("a8db") // CS2
+                .order_by(MarketItemsOrder::Title, OrderDirection::Desc)
+                .limit(5);
+            match client.exchange().get_market_items(&query).await {
                 Ok(market_items) => {
                     info!("Successfully retrieved {} market items out of {}", market_items.objects.len(), market_items.total);
                     for item in market_items.objects {
-                        info!("Item: {} - {} {}", item.title, item.price.amount, item.price.currency);
+                        info!("Item: {} - {}", item.title, item.price);
                     }
                 }
                 Err(e) => {
@@ -77,7 +66,7 @@ async fn main() {
                 Ok(search_results) => {
                     info!("Successfully searched for market items: found {} items", search_results.objects.len());
                     for item in search_results.objects {
-                        info!("Found: {} - {} {}", item.title, item.price.amount, item.price.currency);
+                        info!("Found: {} - {}", item.title, item.price);
                     }
                 }
                 Err(e) => {
@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+
+use super::{Capabilities, Market, MarketBid, Marketplace, MarketplaceError};
+use crate::buff_market::{BuffMarketClient, BuffMarketError, MarketGoodsItem};
+use crate::listing::NormalizedListing;
+
+const GAME: &str = "csgo";
+const SEARCH_PAGE_SIZE: i32 = 20;
+const CRAWL_PAGE_SIZE: i32 = 80;
+
+impl BuffMarketClient {
+    /// Finds the goods entry whose `market_hash_name` matches exactly.
+    async fn find_goods(&self, market_hash_name: &str) -> Result<Option<MarketGoodsItem>, MarketplaceError> {
+        let response = self
+            .search_market_listings(GAME, market_hash_name, 1, SEARCH_PAGE_SIZE)
+            .await?;
+        Ok(response
+            .data
+            .and_then(|data| data.items.into_iter().find(|item| item.market_hash_name == market_hash_name)))
+    }
+}
+
+#[async_trait]
+impl Marketplace for BuffMarketClient {
+    fn market(&self) -> Market {
+        Market::BuffMarket
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            lowest_ask: true,
+            highest_bid: true,
+            crawl: true,
+            ..Capabilities::default()
+        }
+    }
+
+    async fn lowest_ask(&self, market_hash_name: &str) -> Result<Option<NormalizedListing>, MarketplaceError> {
+        let Some(item) = self.find_goods(market_hash_name).await? else {
+            return Ok(None);
+        };
+        if item.sell_num == Some(0) {
+            return Ok(None);
+        }
+        Ok(Some(NormalizedListing::from(item)))
+    }
+
+    async fn crawl(&self, max_items: Option<usize>) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let max_items = max_items.unwrap_or(usize::MAX);
+        let mut listings = Vec::new();
+        let mut page_num = 1;
+        while listings.len() < max_items {
+            let response = self.get_market_listings(GAME, page_num, CRAWL_PAGE_SIZE).await?;
+            let Some(data) = response.data else {
+                if response.code == "OK" {
+                    break;
+                }
+                return Err(BuffMarketError::MissingData(format!(
+                    "No data found for game {} on page {}. API msg: {:?}",
+                    GAME, page_num, response.msg
+                ))
+                .into());
+            };
+            if data.items.is_empty() {
+                break;
+            }
+
+            let remaining = max_items - listings.len();
+            listings.extend(
+                data.items
+                    .into_iter()
+                    .filter(|item| item.sell_num != Some(0))
+                    .take(remaining)
+                    .map(NormalizedListing::from),
+            );
+            if page_num >= data.total_page {
+                break;
+            }
+            page_num += 1;
+        }
+        Ok(listings)
+    }
+
+    async fn highest_bid(&self, market_hash_name: &str) -> Result<Option<MarketBid>, MarketplaceError> {
+        let Some(item) = self.find_goods(market_hash_name).await? else {
+            return Ok(None);
+        };
+        let Some(price) = item.buy_max_price else {
+            return Ok(None);
+        };
+        Ok(Some(MarketBid {
+            market: Market::BuffMarket,
+            market_hash_name: item.market_hash_name,
+            price,
+            quantity: item.buy_num.and_then(|num| u32::try_from(num).ok()),
+        }))
+    }
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+
+use super::{Capabilities, Market, Marketplace, MarketplaceError};
+use crate::csfloat::client::CSFloatClient;
+use crate::csfloat::endpoints::listings::ListingsQuery;
+use crate::listing::NormalizedListing;
+
+#[async_trait]
+impl Marketplace for CSFloatClient {
+    fn market(&self) -> Market {
+        Market::CSFloat
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            list_offers: true,
+            lowest_ask: true,
+            ..Capabilities::default()
+        }
+    }
+
+    async fn list_offers(
+        &self,
+        market_hash_name: &str,
+        limit: u32,
+    ) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let query = ListingsQuery {
+            limit: Some(limit as i32),
+            sort_by: Some("lowest_price".to_string()),
+            market_hash_name: Some(market_hash_name.to_string()),
+            ..ListingsQuery::default()
+        };
+        let response = self.listings().get_listings(Some(query)).await?;
+
+        Ok(response.data.into_iter().map(NormalizedListing::from).collect())
+    }
+}
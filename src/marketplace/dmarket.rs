@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use futures::TryStreamExt;
+
+use super::{Capabilities, InventoryAsset, Market, MarketBid, Marketplace, MarketplaceError};
+use crate::dmarket::client::DMarketClient;
+use crate::dmarket::pagination::DEFAULT_PAGE_SIZE;
+use crate::dmarket::query::{MarketItemsOrder, MarketItemsQuery, OrderDirection, CS2_GAME_ID};
+use crate::listing::NormalizedListing;
+
+#[async_trait]
+impl Marketplace for DMarketClient {
+    fn market(&self) -> Market {
+        Market::DMarket
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            list_offers: true,
+            lowest_ask: true,
+            highest_bid: true,
+            inventory: true,
+            my_listings: true,
+            crawl: true,
+        }
+    }
+
+    async fn list_offers(
+        &self,
+        market_hash_name: &str,
+        limit: u32,
+    ) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let response = self
+            .exchange()
+            .get_market_items(
+                &MarketItemsQuery::new(CS2_GAME_ID)
+                    .title(market_hash_name)
+                    .order_by(MarketItemsOrder::Price, OrderDirection::Asc)
+                    .limit(limit),
+            )
+            .await?;
+
+        // The title filter is a fuzzy search, so keep exact matches only.
+        Ok(response
+            .objects
+            .into_iter()
+            .filter(|item| item.title == market_hash_name)
+            .map(NormalizedListing::from)
+            .collect())
+    }
+
+    async fn highest_bid(&self, market_hash_name: &str) -> Result<Option<MarketBid>, MarketplaceError> {
+        let prices = self.exchange().get_aggregated_prices(CS2_GAME_ID, &[market_hash_name]).await?;
+        Ok(prices
+            .into_iter()
+            .find(|price| price.title == market_hash_name)
+            .and_then(|price| {
+                Some(MarketBid {
+                    market: Market::DMarket,
+                    price: price.order_best_price?,
+                    quantity: Some(price.order_count),
+                    market_hash_name: price.title,
+                })
+            }))
+    }
+
+    async fn crawl(&self, max_items: Option<usize>) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let query = MarketItemsQuery::new(CS2_GAME_ID)
+            .order_by(MarketItemsOrder::Price, OrderDirection::Asc)
+            .limit(DEFAULT_PAGE_SIZE);
+        let mut items = self.exchange().stream_market_items(query);
+        if let Some(max_items) = max_items {
+            items = items.max_items(max_items);
+        }
+        Ok(items.map_ok(NormalizedListing::from).try_collect().await?)
+    }
+
+    async fn my_inventory(&self) -> Result<Vec<InventoryAsset>, MarketplaceError> {
+        let assets = self
+            .inventory()
+            .stream_inventory(Some(CS2_GAME_ID))
+            .map_ok(|item| InventoryAsset {
+                market: Market::DMarket,
+                asset_id: item.item_id,
+                market_hash_name: item.title,
+                tradable: item.extra.tradable,
+            })
+            .try_collect()
+            .await?;
+        Ok(assets)
+    }
+
+    async fn my_listings(&self) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let offers = self
+            .trading()
+            .stream_user_sell_offers(Some(CS2_GAME_ID), None)
+            .map_ok(NormalizedListing::from)
+            .try_collect()
+            .await?;
+        Ok(offers)
+    }
+}
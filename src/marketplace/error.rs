@@ -0,0 +1,130 @@
+use thiserror::Error;
+
+use super::{Market, Operation};
+use crate::buff_market::BuffMarketError;
+use crate::csfloat::error::CSFloatError;
+use crate::dmarket::error::DMarketError;
+use crate::error::ErrorCategory;
+use crate::http::{ApiErrorKind, MarketApiError};
+
+#[derive(Error, Debug)]
+pub enum MarketplaceError {
+    #[error("DMarket error: {0}")]
+    DMarket(#[from] DMarketError),
+
+    #[error("CSFloat error: {0}")]
+    CSFloat(#[from] CSFloatError),
+
+    #[error("Buff Market error: {0}")]
+    BuffMarket(#[from] BuffMarketError),
+
+    #[error("{market} does not support {operation}")]
+    Unsupported { market: Market, operation: Operation },
+
+    #[error("Invalid data from {market}: {message}")]
+    InvalidData { market: Market, message: String },
+}
+
+
+impl MarketplaceError {
+    /// The market the error came from.
+    pub fn market(&self) -> Market {
+        match self {
+            MarketplaceError::DMarket(_) => Market::DMarket,
+            MarketplaceError::CSFloat(_) => Market::CSFloat,
+            MarketplaceError::BuffMarket(_) => Market::BuffMarket,
+            MarketplaceError::Unsupported { market, .. } | MarketplaceError::InvalidData { market, .. } => *market,
+        }
+    }
+
+    /// What kind of failure this is, independently of the market.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            MarketplaceError::DMarket(error) => match error {
+                DMarketError::ApiError(error) => api_category(error),
+                DMarketError::RequestError(error) => reqwest_category(error),
+                DMarketError::CassetteError(_) => ErrorCategory::Transport,
+                DMarketError::EnvError(_)
+                | DMarketError::HmacError(_)
+                | DMarketError::HexError(_)
+                | DMarketError::HeaderError(_)
+                | DMarketError::InvalidCredentials(_) => ErrorCategory::Auth,
+                DMarketError::UrlEncodingError(_) | DMarketError::InvalidQuery(_) => ErrorCategory::Validation,
+                DMarketError::JsonError(_) | DMarketError::MoneyError(_) => ErrorCategory::Upstream,
+            },
+            MarketplaceError::CSFloat(error) => match error {
+                CSFloatError::ApiError(error) => api_category(error),
+                CSFloatError::HttpError(error) => reqwest_category(error),
+                CSFloatError::CassetteError(_) => ErrorCategory::Transport,
+                CSFloatError::EnvVarError(_)
+                | CSFloatError::InvalidHeaderValue(_)
+                | CSFloatError::InvalidCredentials(_) => ErrorCategory::Auth,
+                CSFloatError::UrlEncodingError(_) => ErrorCategory::Validation,
+                CSFloatError::SerializationError(_) => ErrorCategory::Upstream,
+            },
+            MarketplaceError::BuffMarket(error) => match error {
+                BuffMarketError::ApiError(error) => api_category(error),
+                BuffMarketError::HttpRequest(error) => reqwest_category(error),
+                BuffMarketError::Cassette(_) => ErrorCategory::Transport,
+                BuffMarketError::EnvError(_) => ErrorCategory::Auth,
+                BuffMarketError::InvalidInput(_) => ErrorCategory::Validation,
+                BuffMarketError::JsonParse(_) | BuffMarketError::MissingData(_) | BuffMarketError::Unknown => {
+                    ErrorCategory::Upstream
+                }
+            },
+            MarketplaceError::Unsupported { .. } => ErrorCategory::Validation,
+            MarketplaceError::InvalidData { .. } => ErrorCategory::Upstream,
+        }
+    }
+
+    /// Whether sending the same request again later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        if let Some(error) = self.api_error() {
+            return error.retryable;
+        }
+        match self {
+            MarketplaceError::DMarket(DMarketError::RequestError(error))
+            | MarketplaceError::CSFloat(CSFloatError::HttpError(error))
+            | MarketplaceError::BuffMarket(BuffMarketError::HttpRequest(error)) => {
+                reqwest_category(error) == ErrorCategory::Transport
+            }
+            _ => false,
+        }
+    }
+
+    /// The error response of the market, when the failure is one.
+    pub fn api_error(&self) -> Option<&MarketApiError> {
+        match self {
+            MarketplaceError::DMarket(DMarketError::ApiError(error))
+            | MarketplaceError::CSFloat(CSFloatError::ApiError(error))
+            | MarketplaceError::BuffMarket(BuffMarketError::ApiError(error)) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+fn api_category(error: &MarketApiError) -> ErrorCategory {
+    kind_category(error.kind)
+}
+
+fn kind_category(kind: ApiErrorKind) -> ErrorCategory {
+    match kind {
+        ApiErrorKind::RateLimited => ErrorCategory::RateLimited,
+        ApiErrorKind::Unauthorized => ErrorCategory::Auth,
+        ApiErrorKind::NotFound | ApiErrorKind::ItemUnavailable => ErrorCategory::NotFound,
+        ApiErrorKind::InsufficientFunds | ApiErrorKind::InvalidRequest => ErrorCategory::Validation,
+        ApiErrorKind::ServerError | ApiErrorKind::Other => ErrorCategory::Upstream,
+    }
+}
+
+/// Errors reading or decoding a response come from the market; the others are transport errors.
+fn reqwest_category(error: &reqwest::Error) -> ErrorCategory {
+    if error.is_decode() {
+        return ErrorCategory::Upstream;
+    }
+    match error.status() {
+        Some(status) => kind_category(ApiErrorKind::from_status(status)),
+        None if error.is_builder() => ErrorCategory::Validation,
+        None => ErrorCategory::Transport,
+    }
+}
@@ -0,0 +1,168 @@
+//! Market-agnostic access to the supported marketplaces.
+//!
+//! Every client implements [`Marketplace`], so workflows such as "find the lowest ask for an
+//! item" can be written once and run against DMarket, CSFloat and Buff alike. Use
+//! [`Marketplace::capabilities`] to check which operations a market supports before calling them.
+
+mod buff_market;
+mod csfloat;
+mod dmarket;
+pub mod error;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::listing::NormalizedListing;
+use crate::money::Money;
+
+pub use error::MarketplaceError;
+
+/// Number of offers fetched by the default [`Marketplace::lowest_ask`] implementation.
+const LOWEST_ASK_SAMPLE: u32 = 20;
+
+/// Identifies one of the supported marketplaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Market {
+    DMarket,
+    CSFloat,
+    BuffMarket,
+}
+
+impl fmt::Display for Market {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Market::DMarket => "DMarket",
+            Market::CSFloat => "CSFloat",
+            Market::BuffMarket => "Buff Market",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An operation exposed by the [`Marketplace`] trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    ListOffers,
+    LowestAsk,
+    HighestBid,
+    Inventory,
+    MyListings,
+    Crawl,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Operation::ListOffers => "listing offers",
+            Operation::LowestAsk => "lowest ask",
+            Operation::HighestBid => "highest bid",
+            Operation::Inventory => "inventory",
+            Operation::MyListings => "own listings",
+            Operation::Crawl => "crawling the market",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Describes which [`Marketplace`] operations a market supports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub list_offers: bool,
+    pub lowest_ask: bool,
+    pub highest_bid: bool,
+    pub inventory: bool,
+    pub my_listings: bool,
+    pub crawl: bool,
+}
+
+impl Capabilities {
+    /// Returns whether `operation` is supported.
+    pub fn supports(&self, operation: Operation) -> bool {
+        match operation {
+            Operation::ListOffers => self.list_offers,
+            Operation::LowestAsk => self.lowest_ask,
+            Operation::HighestBid => self.highest_bid,
+            Operation::Inventory => self.inventory,
+            Operation::MyListings => self.my_listings,
+            Operation::Crawl => self.crawl,
+        }
+    }
+}
+
+/// The best buy order for an item on a market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketBid {
+    pub market: Market,
+    pub market_hash_name: String,
+    pub price: Money,
+    /// Number of items requested at this price, when the market reports it.
+    pub quantity: Option<u32>,
+}
+
+/// An item held in the user's inventory on a market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryAsset {
+    pub market: Market,
+    pub asset_id: String,
+    pub market_hash_name: String,
+    pub tradable: Option<bool>,
+}
+
+/// Common operations offered by every supported marketplace.
+///
+/// Operations a market does not support return [`MarketplaceError::Unsupported`].
+#[async_trait]
+pub trait Marketplace: Send + Sync {
+    /// The market this client talks to.
+    fn market(&self) -> Market;
+
+    /// The operations supported by this market.
+    fn capabilities(&self) -> Capabilities;
+
+    /// Lists up to `limit` offers for the item named `market_hash_name`.
+    async fn list_offers(
+        &self,
+        market_hash_name: &str,
+        limit: u32,
+    ) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let _ = (market_hash_name, limit);
+        Err(self.unsupported(Operation::ListOffers))
+    }
+
+    /// Returns the cheapest offer for the item, if any.
+    async fn lowest_ask(&self, market_hash_name: &str) -> Result<Option<NormalizedListing>, MarketplaceError> {
+        let offers = self.list_offers(market_hash_name, LOWEST_ASK_SAMPLE).await?;
+        Ok(offers.into_iter().min_by_key(|offer| offer.price.minor_units()))
+    }
+
+    /// Returns the highest buy order for the item, if any.
+    async fn highest_bid(&self, market_hash_name: &str) -> Result<Option<MarketBid>, MarketplaceError> {
+        let _ = market_hash_name;
+        Err(self.unsupported(Operation::HighestBid))
+    }
+
+    /// Lists the items in the user's inventory.
+    async fn my_inventory(&self) -> Result<Vec<InventoryAsset>, MarketplaceError> {
+        Err(self.unsupported(Operation::Inventory))
+    }
+
+    /// Lists the user's own active offers.
+    async fn my_listings(&self) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        Err(self.unsupported(Operation::MyListings))
+    }
+
+    /// Lists offers across the whole market, up to `max_items` when given.
+    async fn crawl(&self, max_items: Option<usize>) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        let _ = max_items;
+        Err(self.unsupported(Operation::Crawl))
+    }
+
+    /// Builds the error returned for operations this market does not support.
+    fn unsupported(&self, operation: Operation) -> MarketplaceError {
+        MarketplaceError::Unsupported {
+            market: self.market(),
+            operation,
+        }
+    }
+}
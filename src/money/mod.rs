@@ -0,0 +1,357 @@
+//! Typed monetary amounts.
+//!
+//! Prices are kept in minor units (cents, fen) next to their [`Currency`], so amounts from
+//! different markets can't be mixed up silently. Each market encodes prices differently on the
+//! wire; the adapters in [`wire`] translate those encodings into [`Money`].
+
+pub mod wire;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::fx::{FxError, RateTable};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MoneyError {
+    #[error("Unknown currency: {0}")]
+    UnknownCurrency(String),
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Currency mismatch: {0} vs {1}")]
+    CurrencyMismatch(Currency, Currency),
+
+    #[error("Amount overflow")]
+    Overflow,
+}
+
+/// Currencies quoted by the supported markets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Currency {
+    USD,
+    EUR,
+    CNY,
+    /// DMarket's own DMC token.
+    DMC,
+}
+
+impl Currency {
+    /// The ISO-style currency code, e.g. `"USD"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::USD => "USD",
+            Currency::EUR => "EUR",
+            Currency::CNY => "CNY",
+            Currency::DMC => "DMC",
+        }
+    }
+
+    /// Number of decimal digits represented by the minor unit.
+    pub fn minor_unit_digits(&self) -> u32 {
+        2
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "USD" => Ok(Currency::USD),
+            "EUR" => Ok(Currency::EUR),
+            "CNY" | "RMB" => Ok(Currency::CNY),
+            "DMC" => Ok(Currency::DMC),
+            _ => Err(MoneyError::UnknownCurrency(s.to_string())),
+        }
+    }
+}
+
+/// An amount of money in a given currency.
+///
+/// Serializes as `{"amount": "12.50", "currency": "USD"}` with the amount in major units. Use the
+/// [`wire`] adapters for market payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    minor_units: i64,
+    currency: Currency,
+}
+
+impl Money {
+    /// Creates an amount from minor units, e.g. `Money::from_minor(1250, Currency::USD)` is $12.50.
+    pub fn from_minor(minor_units: i64, currency: Currency) -> Self {
+        Self { minor_units, currency }
+    }
+
+    /// A zero amount of `currency`.
+    pub fn zero(currency: Currency) -> Self {
+        Self::from_minor(0, currency)
+    }
+
+    /// Creates an amount from a decimal in major units, rounding half away from zero to the
+    /// nearest minor unit.
+    pub fn from_decimal(amount: Decimal, currency: Currency) -> Result<Self, MoneyError> {
+        let minor = amount
+            .checked_mul(Decimal::from(10_i64.pow(currency.minor_unit_digits())))
+            .ok_or(MoneyError::Overflow)?
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero);
+        let minor_units = i64::try_from(minor).map_err(|_| MoneyError::Overflow)?;
+        Ok(Self::from_minor(minor_units, currency))
+    }
+
+    /// Parses an amount given in minor units, such as DMarket's `"1250"`.
+    pub fn parse_minor(amount: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let minor_units = amount
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| MoneyError::InvalidAmount(amount.to_string()))?;
+        Ok(Self::from_minor(minor_units, currency))
+    }
+
+    /// Parses a decimal amount in major units, such as Buff's `"12.5"`.
+    ///
+    /// Only plain decimals such as `-12.50` are accepted, not exponents like `1e3`. Amounts with
+    /// more precision than the currency's minor unit are rejected rather than rounded.
+    pub fn parse_decimal(amount: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let invalid = || MoneyError::InvalidAmount(amount.to_string());
+        let text = amount.trim();
+        let unsigned = text.strip_prefix('-').unwrap_or(text);
+        let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, "0"));
+        let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+        if !is_digits(whole) || !is_digits(fraction) {
+            return Err(invalid());
+        }
+        let decimal = Decimal::from_str(text).map_err(|_| invalid())?;
+        if decimal.normalize().scale() > currency.minor_unit_digits() {
+            return Err(invalid());
+        }
+        Self::from_decimal(decimal, currency)
+    }
+
+    /// The amount in minor units.
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// The amount in major units, e.g. `12.50` for 1250 cents.
+    pub fn to_decimal(&self) -> Decimal {
+        Decimal::new(self.minor_units, self.currency.minor_unit_digits())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor_units == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.minor_units < 0
+    }
+
+    /// Adds two amounts of the same currency.
+    pub fn checked_add(&self, other: Money) -> Result<Money, MoneyError> {
+        self.ensure_same_currency(&other)?;
+        let minor_units = self
+            .minor_units
+            .checked_add(other.minor_units)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self::from_minor(minor_units, self.currency))
+    }
+
+    /// Subtracts an amount of the same currency.
+    pub fn checked_sub(&self, other: Money) -> Result<Money, MoneyError> {
+        self.ensure_same_currency(&other)?;
+        let minor_units = self
+            .minor_units
+            .checked_sub(other.minor_units)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self::from_minor(minor_units, self.currency))
+    }
+
+    /// Multiplies the amount by `factor`, e.g. a fee rate, rounding to the nearest minor unit.
+    pub fn mul_decimal(&self, factor: Decimal) -> Result<Money, MoneyError> {
+        let product = self.to_decimal().checked_mul(factor).ok_or(MoneyError::Overflow)?;
+        Self::from_decimal(product, self.currency)
+    }
+
+    /// Converts the amount into `currency` using `rates`, rounding to the nearest minor unit.
+    pub fn convert_to(&self, currency: Currency, rates: &RateTable) -> Result<Money, FxError> {
+        if self.currency == currency {
+            return Ok(*self);
+        }
+        let rate = rates.rate(self.currency, currency)?;
+        let converted = self.to_decimal().checked_mul(rate).ok_or(MoneyError::Overflow)?;
+        Ok(Money::from_decimal(converted, currency)?)
+    }
+
+    /// Ratio of this amount to `other`, both in the same currency.
+    pub fn ratio_to(&self, other: Money) -> Result<Decimal, MoneyError> {
+        self.ensure_same_currency(&other)?;
+        if other.is_zero() {
+            return Err(MoneyError::InvalidAmount("division by zero".to_string()));
+        }
+        Ok(Decimal::from(self.minor_units) / Decimal::from(other.minor_units))
+    }
+
+    fn ensure_same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(MoneyError::CurrencyMismatch(self.currency, other.currency))
+        }
+    }
+}
+
+/// Amounts compare only within the same currency.
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.currency == other.currency {
+            Some(self.minor_units.cmp(&other.minor_units))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.to_decimal(), self.currency)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MoneyRepr {
+    amount: String,
+    currency: Currency,
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MoneyRepr {
+            amount: self.to_decimal().to_string(),
+            currency: self.currency,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = MoneyRepr::deserialize(deserializer)?;
+        Money::parse_decimal(&repr.amount, repr.currency).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(minor_units: i64) -> Money {
+        Money::from_minor(minor_units, Currency::USD)
+    }
+
+    #[test]
+    fn adds_and_subtracts_within_a_currency() {
+        assert_eq!(usd(1250).checked_add(usd(-250)), Ok(usd(1000)));
+        assert_eq!(usd(1000).checked_sub(usd(1250)), Ok(usd(-250)));
+        assert_eq!(usd(i64::MAX).checked_add(usd(1)), Err(MoneyError::Overflow));
+        assert_eq!(usd(i64::MIN).checked_sub(usd(1)), Err(MoneyError::Overflow));
+
+        let yuan = Money::from_minor(100, Currency::CNY);
+        assert_eq!(usd(100).checked_add(yuan), Err(MoneyError::CurrencyMismatch(Currency::USD, Currency::CNY)));
+        assert_eq!(usd(100).checked_sub(yuan), Err(MoneyError::CurrencyMismatch(Currency::USD, Currency::CNY)));
+        assert_eq!(usd(100).ratio_to(yuan), Err(MoneyError::CurrencyMismatch(Currency::USD, Currency::CNY)));
+        assert_eq!(usd(100).partial_cmp(&yuan), None);
+        assert!(usd(100) < usd(101));
+    }
+
+    #[test]
+    fn rounds_half_away_from_zero() {
+        assert_eq!(Money::from_decimal(Decimal::new(12345, 3), Currency::USD), Ok(usd(1235)));
+        assert_eq!(Money::from_decimal(Decimal::new(-12345, 3), Currency::USD), Ok(usd(-1235)));
+        assert_eq!(Money::from_decimal(Decimal::new(12344, 3), Currency::USD), Ok(usd(1234)));
+
+        // 5% of $0.50 is 2.5 cents.
+        assert_eq!(usd(50).mul_decimal(Decimal::new(5, 2)), Ok(usd(3)));
+        assert_eq!(usd(1999).mul_decimal(Decimal::new(975, 3)), Ok(usd(1949)));
+        assert_eq!(usd(i64::MAX).mul_decimal(Decimal::TWO), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn converts_between_major_and_minor_units() {
+        assert_eq!(usd(1250).to_decimal(), Decimal::new(1250, 2));
+        assert_eq!(usd(1250).to_string(), "12.50 USD");
+        assert_eq!(usd(-5).to_string(), "-0.05 USD");
+        assert_eq!(Money::parse_minor(" 1250 ", Currency::USD), Ok(usd(1250)));
+        assert_eq!(Money::parse_minor("12.50", Currency::USD), Err(MoneyError::InvalidAmount("12.50".to_string())));
+        assert_eq!(usd(300).ratio_to(usd(200)), Ok(Decimal::new(15, 1)));
+        assert_eq!(usd(300).ratio_to(usd(0)), Err(MoneyError::InvalidAmount("division by zero".to_string())));
+        assert!(usd(0).is_zero() && usd(-1).is_negative() && !usd(1).is_negative());
+    }
+
+    #[test]
+    fn converts_through_rate_table() {
+        let rates = RateTable::new(Currency::USD, chrono::Utc::now()).with_rate(Currency::CNY, Decimal::new(72, 1));
+        let yuan = Money::from_minor(7200, Currency::CNY);
+        assert_eq!(usd(1250).convert_to(Currency::CNY, &rates).unwrap(), Money::from_minor(9000, Currency::CNY));
+        assert_eq!(yuan.convert_to(Currency::USD, &rates).unwrap(), usd(1000));
+        assert_eq!(usd(1250).convert_to(Currency::USD, &rates).unwrap(), usd(1250));
+        assert!(usd(1250).convert_to(Currency::EUR, &rates).is_err());
+    }
+
+    #[test]
+    fn parses_currency_codes() {
+        assert_eq!(" usd ".parse::<Currency>(), Ok(Currency::USD));
+        assert_eq!("RMB".parse::<Currency>(), Ok(Currency::CNY));
+        assert_eq!("dmc".parse::<Currency>(), Ok(Currency::DMC));
+        assert_eq!("GBP".parse::<Currency>(), Err(MoneyError::UnknownCurrency("GBP".to_string())));
+    }
+
+    #[test]
+    fn serializes_in_major_units() {
+        let json = serde_json::to_value(usd(1250)).unwrap();
+        assert_eq!(json, serde_json::json!({"amount": "12.50", "currency": "USD"}));
+        assert_eq!(serde_json::from_value::<Money>(json).unwrap(), usd(1250));
+        assert!(serde_json::from_value::<Money>(serde_json::json!({"amount": "1e3", "currency": "USD"})).is_err());
+    }
+
+    #[test]
+    fn parses_plain_decimals_only() {
+        assert_eq!(Money::parse_decimal(" 12.5 ", Currency::CNY), Ok(Money::from_minor(1250, Currency::CNY)));
+        assert_eq!(Money::parse_decimal("-0.05", Currency::USD), Ok(Money::from_minor(-5, Currency::USD)));
+        assert_eq!(Money::parse_decimal("7", Currency::USD), Ok(Money::from_minor(700, Currency::USD)));
+        for amount in ["1e3", "1E3", "1.5e2", "+1", "1.", ".5", "", "-", "1,5", "0x10", "1.005"] {
+            assert_eq!(
+                Money::parse_decimal(amount, Currency::USD),
+                Err(MoneyError::InvalidAmount(amount.to_string())),
+                "{:?}",
+                amount
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_decimals_overflowing_minor_units() {
+        assert_eq!(
+            Money::parse_decimal("79228162514264337593543950335", Currency::CNY),
+            Err(MoneyError::Overflow)
+        );
+        assert_eq!(Money::from_decimal(Decimal::MAX, Currency::USD), Err(MoneyError::Overflow));
+        assert_eq!(
+            Money::from_decimal(Decimal::from(i64::MAX), Currency::USD),
+            Err(MoneyError::Overflow)
+        );
+    }
+}
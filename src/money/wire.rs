@@ -0,0 +1,334 @@
+//! Serde adapters between [`Money`] and each market's price encoding.
+//!
+//! Use them with `#[serde(with = "...")]` on model fields:
+//!
+//! - DMarket: `{"amount": "1250", "currency": "USD"}`, a string of cents ([`dmarket_price`]), or
+//!   decimal dollars such as `"12.5"` for last sales ([`usd_decimal`]).
+//! - CSFloat: integer US cents such as `1250` ([`usd_cents`]), or
+//!   `{"amount": 1250, "currency": "USD"}` ([`csfloat_price_opt`]).
+//! - Buff: decimal strings such as `"12.5"` in yuan ([`cny_decimal`]), or in dollars for Steam
+//!   reference prices ([`usd_decimal_opt`]).
+//!
+//! Every adapter has an `_opt` counterpart for `Option<Money>` fields; pair those with
+//! `#[serde(default)]` so a missing field deserializes as `None`.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Currency, Money, MoneyError};
+
+/// Cents sent either as a JSON string or as a number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MinorUnits {
+    Text(String),
+    Number(i64),
+}
+
+impl MinorUnits {
+    fn into_money<E: serde::de::Error>(self, currency: Currency) -> Result<Money, E> {
+        match self {
+            MinorUnits::Text(text) => Money::parse_minor(&text, currency).map_err(E::custom),
+            MinorUnits::Number(number) => Ok(Money::from_minor(number, currency)),
+        }
+    }
+}
+
+/// Fails unless `value` is in `currency`, for encodings that don't carry a currency.
+fn ensure_currency<E: serde::ser::Error>(value: &Money, currency: Currency) -> Result<(), E> {
+    if value.currency() == currency {
+        Ok(())
+    } else {
+        Err(E::custom(MoneyError::CurrencyMismatch(value.currency(), currency)))
+    }
+}
+
+/// Defines `$name` handling `Option<Money>` through the non-optional adapter `$inner`.
+macro_rules! option_adapter {
+    ($(#[$meta:meta])* $name:ident, $inner:ident) => {
+        $(#[$meta])*
+        pub mod $name {
+            use super::*;
+
+            struct Wrapped(Money);
+
+            impl Serialize for Wrapped {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    super::$inner::serialize(&self.0, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for Wrapped {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    super::$inner::deserialize(deserializer).map(Wrapped)
+                }
+            }
+
+            pub fn serialize<S: Serializer>(value: &Option<Money>, serializer: S) -> Result<S::Ok, S::Error> {
+                value.map(Wrapped).serialize(serializer)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Money>, D::Error> {
+                Ok(Option::<Wrapped>::deserialize(deserializer)?.map(|Wrapped(money)| money))
+            }
+        }
+    };
+}
+
+/// DMarket's `{"amount": "<cents>", "currency": "<code>"}` price object.
+pub mod dmarket_price {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Out {
+        amount: String,
+        currency: Currency,
+    }
+
+    #[derive(Deserialize)]
+    struct In {
+        amount: MinorUnits,
+        currency: String,
+    }
+
+    pub fn serialize<S: Serializer>(value: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        Out {
+            amount: value.minor_units().to_string(),
+            currency: value.currency(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        let price = In::deserialize(deserializer)?;
+        let currency = price.currency.parse().map_err(serde::de::Error::custom)?;
+        price.amount.into_money(currency)
+    }
+}
+
+option_adapter!(
+    /// Optional variant of [`dmarket_price`].
+    dmarket_price_opt,
+    dmarket_price
+);
+
+/// An amount of US cents: a number in CSFloat's API, a string in DMarket's order books.
+pub mod usd_cents {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        ensure_currency(value, Currency::USD)?;
+        serializer.serialize_i64(value.minor_units())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        MinorUnits::deserialize(deserializer)?.into_money(Currency::USD)
+    }
+}
+
+option_adapter!(
+    /// Optional variant of [`usd_cents`].
+    usd_cents_opt,
+    usd_cents
+);
+
+/// CSFloat's `{"amount": <cents>, "currency": "<code>"}` price object.
+pub mod csfloat_price {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Out {
+        amount: i64,
+        currency: Currency,
+    }
+
+    #[derive(Deserialize)]
+    struct In {
+        amount: MinorUnits,
+        currency: String,
+    }
+
+    pub fn serialize<S: Serializer>(value: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        Out {
+            amount: value.minor_units(),
+            currency: value.currency(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        let price = In::deserialize(deserializer)?;
+        let currency = price.currency.parse().map_err(serde::de::Error::custom)?;
+        price.amount.into_money(currency)
+    }
+}
+
+option_adapter!(
+    /// Optional variant of [`csfloat_price`].
+    csfloat_price_opt,
+    csfloat_price
+);
+
+/// Defines an adapter for decimal strings in major units of `$currency`.
+macro_rules! decimal_adapter {
+    ($(#[$meta:meta])* $name:ident, $currency:expr) => {
+        $(#[$meta])*
+        pub mod $name {
+            use super::*;
+
+            pub fn serialize<S: Serializer>(value: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+                ensure_currency(value, $currency)?;
+                serializer.serialize_str(&value.to_decimal().to_string())
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+                let amount = String::deserialize(deserializer)?;
+                Money::parse_decimal(&amount, $currency).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+decimal_adapter!(
+    /// A decimal string in yuan, such as Buff's `"12.5"`.
+    cny_decimal,
+    Currency::CNY
+);
+
+option_adapter!(
+    /// Optional variant of [`cny_decimal`].
+    cny_decimal_opt,
+    cny_decimal
+);
+
+decimal_adapter!(
+    /// A decimal string in US dollars, such as Buff's Steam reference prices.
+    usd_decimal,
+    Currency::USD
+);
+
+option_adapter!(
+    /// Optional variant of [`usd_decimal`].
+    usd_decimal_opt,
+    usd_decimal
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Serialize)]
+    struct Cents(#[serde(with = "usd_cents")] Money);
+
+    #[derive(Debug, Serialize)]
+    struct Yuan(#[serde(with = "cny_decimal")] Money);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Prices {
+        #[serde(with = "dmarket_price")]
+        dmarket: Money,
+        #[serde(with = "usd_cents")]
+        cents: Money,
+        #[serde(with = "csfloat_price")]
+        csfloat: Money,
+        #[serde(with = "cny_decimal")]
+        yuan: Money,
+        #[serde(with = "usd_decimal")]
+        dollars: Money,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct OptionalPrices {
+        #[serde(default, with = "dmarket_price_opt")]
+        dmarket: Option<Money>,
+        #[serde(default, with = "usd_cents_opt")]
+        cents: Option<Money>,
+        #[serde(default, with = "csfloat_price_opt")]
+        csfloat: Option<Money>,
+        #[serde(default, with = "cny_decimal_opt")]
+        yuan: Option<Money>,
+        #[serde(default, with = "usd_decimal_opt")]
+        dollars: Option<Money>,
+    }
+
+    fn prices() -> Prices {
+        Prices {
+            dmarket: Money::from_minor(1250, Currency::USD),
+            cents: Money::from_minor(1999, Currency::USD),
+            csfloat: Money::from_minor(-5, Currency::EUR),
+            yuan: Money::from_minor(8850, Currency::CNY),
+            dollars: Money::from_minor(7, Currency::USD),
+        }
+    }
+
+    #[test]
+    fn round_trips_every_encoding() {
+        let json = serde_json::to_value(prices()).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "dmarket": {"amount": "1250", "currency": "USD"},
+                "cents": 1999,
+                "csfloat": {"amount": -5, "currency": "EUR"},
+                "yuan": "88.50",
+                "dollars": "0.07",
+            })
+        );
+        assert_eq!(serde_json::from_value::<Prices>(json).unwrap(), prices());
+    }
+
+    #[test]
+    fn accepts_market_variations() {
+        let prices = serde_json::from_value::<Prices>(json!({
+            "dmarket": {"amount": 1250, "currency": "usd"},
+            "cents": "1999",
+            "csfloat": {"amount": "-5", "currency": "EUR"},
+            "yuan": "88.5",
+            "dollars": "0.07",
+        }))
+        .unwrap();
+        assert_eq!(prices, self::prices());
+
+        assert!(serde_json::from_value::<Prices>(json!({
+            "dmarket": {"amount": "12.50", "currency": "USD"},
+            "cents": 1999,
+            "csfloat": {"amount": -5, "currency": "EUR"},
+            "yuan": "88.5",
+            "dollars": "0.07",
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn round_trips_optional_encodings() {
+        let full = OptionalPrices {
+            dmarket: Some(prices().dmarket),
+            cents: Some(prices().cents),
+            csfloat: Some(prices().csfloat),
+            yuan: Some(prices().yuan),
+            dollars: Some(prices().dollars),
+        };
+        let json = serde_json::to_value(&full).unwrap();
+        assert_eq!(json, serde_json::to_value(prices()).unwrap());
+        assert_eq!(serde_json::from_value::<OptionalPrices>(json).unwrap(), full);
+
+        let empty = OptionalPrices { dmarket: None, cents: None, csfloat: None, yuan: None, dollars: None };
+        assert_eq!(serde_json::from_value::<OptionalPrices>(json!({})).unwrap(), empty);
+        assert_eq!(serde_json::from_value::<OptionalPrices>(json!({"dmarket": null, "yuan": null})).unwrap(), empty);
+        assert_eq!(
+            serde_json::to_value(&empty).unwrap(),
+            json!({"dmarket": null, "cents": null, "csfloat": null, "yuan": null, "dollars": null})
+        );
+    }
+
+    #[test]
+    fn refuses_to_serialize_other_currencies() {
+        let error = serde_json::to_value(Cents(Money::from_minor(1250, Currency::CNY))).unwrap_err();
+        assert!(error.to_string().contains("Currency mismatch: CNY vs USD"), "{}", error);
+        let error = serde_json::to_value(Yuan(Money::from_minor(1250, Currency::USD))).unwrap_err();
+        assert!(error.to_string().contains("Currency mismatch: USD vs CNY"), "{}", error);
+
+        assert_eq!(serde_json::to_value(Cents(Money::from_minor(1250, Currency::USD))).unwrap(), json!(1250));
+        assert_eq!(serde_json::to_value(Yuan(Money::from_minor(1250, Currency::CNY))).unwrap(), json!("12.50"));
+    }
+}
@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+use crate::money::MoneyError;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Failed to encode snapshot: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid stored price: {0}")]
+    Money(#[from] MoneyError),
+
+    #[error("Migration {version} failed: {source}")]
+    Migration {
+        version: usize,
+        #[source]
+        source: rusqlite::Error,
+    },
+
+    #[error("Database schema version {0} is newer than this build supports")]
+    UnsupportedVersion(usize),
+
+    #[error("Invalid stored data: {0}")]
+    InvalidData(String),
+}
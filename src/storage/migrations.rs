@@ -0,0 +1,121 @@
+use tracing::info;
+use rusqlite::Connection;
+
+use super::StorageError;
+
+/// Schema migrations, applied in order. The database's `user_version` is the number applied.
+///
+/// Append new migrations to the end; never edit one that has shipped.
+const MIGRATIONS: &[&str] = &[
+    // 1: listing and price snapshots
+    "CREATE TABLE listing_snapshots (
+        id INTEGER PRIMARY KEY,
+        captured_at INTEGER NOT NULL,
+        market TEXT NOT NULL,
+        item TEXT NOT NULL,
+        external_id TEXT NOT NULL,
+        price_minor INTEGER NOT NULL,
+        currency TEXT NOT NULL,
+        float_value REAL,
+        paint_seed INTEGER,
+        trade_lock_days INTEGER,
+        raw TEXT NOT NULL
+    );
+    CREATE INDEX listing_snapshots_item ON listing_snapshots (item, market, captured_at);
+
+    CREATE TABLE price_snapshots (
+        id INTEGER PRIMARY KEY,
+        captured_at INTEGER NOT NULL,
+        market TEXT,
+        item TEXT NOT NULL,
+        best_ask_minor INTEGER,
+        best_ask_currency TEXT,
+        best_bid_minor INTEGER,
+        best_bid_currency TEXT,
+        listing_count INTEGER NOT NULL
+    );
+    CREATE INDEX price_snapshots_item ON price_snapshots (item, market, captured_at);",
+];
+
+/// Brings the schema up to date, returning the resulting version.
+pub(crate) fn migrate(conn: &mut Connection) -> Result<usize, StorageError> {
+    let current: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if current > MIGRATIONS.len() {
+        return Err(StorageError::UnsupportedVersion(current));
+    }
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+        let version = index + 1;
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)
+            .and_then(|_| tx.pragma_update(None, "user_version", version))
+            .map_err(|source| StorageError::Migration { version, source })?;
+        tx.commit()?;
+        info!("Applied storage migration {}", version);
+    }
+    Ok(MIGRATIONS.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap()
+    }
+
+    fn tables(conn: &Connection) -> Vec<String> {
+        let mut query = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap();
+        let names = query.query_map([], |row| row.get(0)).unwrap();
+        names.collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn steps_a_new_database_through_every_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(user_version(&conn), 0);
+
+        assert_eq!(migrate(&mut conn).unwrap(), MIGRATIONS.len());
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        assert_eq!(tables(&conn), ["listing_snapshots", "price_snapshots"]);
+    }
+
+    #[test]
+    fn skips_applied_migrations() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO price_snapshots (captured_at, item, listing_count) VALUES (1, 'Revolution Case', 3)",
+            [],
+        )
+        .unwrap();
+
+        // Re-running a `CREATE TABLE` would fail, so success means nothing was applied again.
+        assert_eq!(migrate(&mut conn).unwrap(), MIGRATIONS.len());
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM price_snapshots", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn rolls_back_a_failed_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE price_snapshots (id INTEGER PRIMARY KEY)").unwrap();
+
+        let error = migrate(&mut conn).unwrap_err();
+        assert!(matches!(error, StorageError::Migration { version: 1, .. }), "{}", error);
+        assert_eq!(user_version(&conn), 0);
+        assert_eq!(tables(&conn), ["price_snapshots"]);
+    }
+
+    #[test]
+    fn refuses_newer_schemas() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1).unwrap();
+        assert!(matches!(
+            migrate(&mut conn),
+            Err(StorageError::UnsupportedVersion(version)) if version == MIGRATIONS.len() + 1
+        ));
+    }
+}
@@ -0,0 +1,351 @@
+//! Persistent price snapshots in SQLite.
+//!
+//! [`Storage`] records timestamped snapshots of listings and aggregated prices, and answers
+//! history queries such as "prices of item X on market Y between t1 and t2". The schema is
+//! versioned and migrated automatically when a database is opened.
+//!
+//! Items are stored under their canonical name (see [`ItemIdentity`]), including the Doppler
+//! phase when known, so names from different markets line up.
+
+pub mod error;
+mod migrations;
+
+use chrono::{DateTime, TimeZone, Utc};
+use tracing::debug;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::aggregator::ItemAggregate;
+use crate::identity::ItemIdentity;
+use crate::listing::{NormalizedListing, Tradability};
+use crate::marketplace::Market;
+use crate::money::{Currency, Money};
+
+pub use error::StorageError;
+
+/// A stored listing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListingSnapshot {
+    pub captured_at: DateTime<Utc>,
+    pub market: Market,
+    pub item: String,
+    pub external_id: String,
+    pub price: Money,
+    pub float_value: Option<f64>,
+    pub paint_seed: Option<i32>,
+    pub trade_lock_days: Option<u32>,
+}
+
+/// Stored prices of an item at one point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub captured_at: DateTime<Utc>,
+    /// The market, or `None` for the cross-market view.
+    pub market: Option<Market>,
+    pub item: String,
+    pub best_ask: Option<Money>,
+    pub best_bid: Option<Money>,
+    pub listing_count: u32,
+}
+
+/// A SQLite database of price snapshots.
+pub struct Storage {
+    conn: Mutex<Connection>,
+}
+
+impl Storage {
+    /// Opens or creates the database at `path` and migrates it to the latest schema.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        debug!("Opening storage at {}", path.as_ref().display());
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Opens a temporary database that lives as long as the returned value.
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, StorageError> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        migrations::migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The schema version of the database.
+    pub fn schema_version(&self) -> Result<usize, StorageError> {
+        Ok(self.conn().query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+
+    /// Records `listings` as seen at `captured_at`, returning the number stored.
+    pub fn record_listings(
+        &self,
+        captured_at: DateTime<Utc>,
+        listings: &[NormalizedListing],
+    ) -> Result<usize, StorageError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO listing_snapshots
+                    (captured_at, market, item, external_id, price_minor, currency, float_value,
+                     paint_seed, trade_lock_days, raw)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for listing in listings {
+                let item = listing
+                    .identity()
+                    .map(|identity| identity.to_string())
+                    .unwrap_or_else(|_| listing.market_hash_name.clone());
+                let trade_lock_days = match listing.tradability {
+                    Tradability::Locked { days_remaining } => days_remaining,
+                    _ => None,
+                };
+                insert.execute(params![
+                    captured_at.timestamp(),
+                    market_code(listing.market),
+                    item,
+                    listing.external_id,
+                    listing.price.minor_units(),
+                    listing.price.currency().code(),
+                    listing.float_value,
+                    listing.paint_seed,
+                    trade_lock_days,
+                    serde_json::to_string(&listing.raw)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        debug!("Stored {} listing snapshots", listings.len());
+        Ok(listings.len())
+    }
+
+    /// Records the per-market and overall prices in `aggregates`, returning the number of rows
+    /// stored.
+    pub fn record_aggregates(
+        &self,
+        captured_at: DateTime<Utc>,
+        aggregates: &[ItemAggregate],
+    ) -> Result<usize, StorageError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut stored = 0;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO price_snapshots
+                    (captured_at, market, item, best_ask_minor, best_ask_currency, best_bid_minor,
+                     best_bid_currency, listing_count)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut write = |market: Option<Market>,
+                             item: &str,
+                             ask: Option<Money>,
+                             bid: Option<Money>,
+                             listing_count: usize|
+             -> Result<(), StorageError> {
+                insert.execute(params![
+                    captured_at.timestamp(),
+                    market.map(market_code),
+                    item,
+                    ask.map(|ask| ask.minor_units()),
+                    ask.map(|ask| ask.currency().code()),
+                    bid.map(|bid| bid.minor_units()),
+                    bid.map(|bid| bid.currency().code()),
+                    i64::try_from(listing_count).unwrap_or(i64::MAX),
+                ])?;
+                stored += 1;
+                Ok(())
+            };
+
+            for aggregate in aggregates {
+                let item = aggregate.identity.to_string();
+                for quote in &aggregate.quotes {
+                    write(
+                        Some(quote.market),
+                        &item,
+                        quote.best_ask.as_ref().map(|ask| ask.price),
+                        quote.best_bid.as_ref().map(|bid| bid.price),
+                        quote.listing_count,
+                    )?;
+                }
+                let overall = &aggregate.overall;
+                write(
+                    None,
+                    &item,
+                    overall.best_ask.map(|ask| ask.price),
+                    overall.best_bid.map(|bid| bid.price),
+                    overall.listing_count,
+                )?;
+            }
+        }
+        tx.commit()?;
+        debug!("Stored {} price snapshots", stored);
+        Ok(stored)
+    }
+
+    /// Prices of `item` between `from` and `to` inclusive, oldest first.
+    ///
+    /// With `market` set, only that market's prices are returned; otherwise only the
+    /// cross-market view is.
+    pub fn price_history(
+        &self,
+        item: &str,
+        market: Option<Market>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PricePoint>, StorageError> {
+        let conn = self.conn();
+        let mut query = conn.prepare_cached(
+            "SELECT captured_at, market, item, best_ask_minor, best_ask_currency, best_bid_minor,
+                    best_bid_currency, listing_count
+             FROM price_snapshots
+             WHERE item = ?1 AND market IS ?2 AND captured_at BETWEEN ?3 AND ?4
+             ORDER BY captured_at, id",
+        )?;
+        let rows = query.query_map(
+            params![item_key(item), market.map(market_code), from.timestamp(), to.timestamp()],
+            PriceRow::from_row,
+        )?;
+        rows.map(|row| row?.into_point()).collect()
+    }
+
+    /// Listings of `item` on `market` stored between `from` and `to` inclusive, oldest first.
+    pub fn listing_history(
+        &self,
+        item: &str,
+        market: Market,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ListingSnapshot>, StorageError> {
+        let conn = self.conn();
+        let mut query = conn.prepare_cached(
+            "SELECT captured_at, market, item, external_id, price_minor, currency, float_value,
+                    paint_seed, trade_lock_days
+             FROM listing_snapshots
+             WHERE item = ?1 AND market = ?2 AND captured_at BETWEEN ?3 AND ?4
+             ORDER BY captured_at, id",
+        )?;
+        let rows = query.query_map(
+            params![item_key(item), market_code(market), from.timestamp(), to.timestamp()],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, i64>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, Option<f64>>(6)?,
+                    row.get::<_, Option<i32>>(7)?,
+                    row.get::<_, Option<u32>>(8)?,
+                ))
+            },
+        )?;
+        rows.map(|row| {
+            let (captured_at, market, item, external_id, price, currency, float_value, paint_seed, trade_lock_days) =
+                row?;
+            Ok(ListingSnapshot {
+                captured_at: timestamp(captured_at)?,
+                market: parse_market(&market)?,
+                item,
+                external_id,
+                price: Money::from_minor(price, currency.parse()?),
+                float_value,
+                paint_seed,
+                trade_lock_days,
+            })
+        })
+        .collect()
+    }
+
+    /// The time of the most recent price snapshot, if any.
+    pub fn last_captured_at(&self) -> Result<Option<DateTime<Utc>>, StorageError> {
+        let latest = self
+            .conn()
+            .query_row("SELECT MAX(captured_at) FROM price_snapshots", [], |row| {
+                row.get::<_, Option<i64>>(0)
+            })
+            .optional()?
+            .flatten();
+        latest.map(timestamp).transpose()
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A `price_snapshots` row before its columns are validated.
+struct PriceRow {
+    captured_at: i64,
+    market: Option<String>,
+    item: String,
+    ask: Option<(i64, String)>,
+    bid: Option<(i64, String)>,
+    listing_count: u32,
+}
+
+impl PriceRow {
+    fn from_row(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        let amount = |minor: usize, currency: usize| -> Result<Option<(i64, String)>, rusqlite::Error> {
+            Ok(row.get::<_, Option<i64>>(minor)?.zip(row.get::<_, Option<String>>(currency)?))
+        };
+        Ok(Self {
+            captured_at: row.get(0)?,
+            market: row.get(1)?,
+            item: row.get(2)?,
+            ask: amount(3, 4)?,
+            bid: amount(5, 6)?,
+            listing_count: row.get(7)?,
+        })
+    }
+
+    fn into_point(self) -> Result<PricePoint, StorageError> {
+        let money = |amount: Option<(i64, String)>| -> Result<Option<Money>, StorageError> {
+            amount
+                .map(|(minor, currency)| Ok(Money::from_minor(minor, currency.parse::<Currency>()?)))
+                .transpose()
+        };
+        Ok(PricePoint {
+            captured_at: timestamp(self.captured_at)?,
+            market: self.market.as_deref().map(parse_market).transpose()?,
+            item: self.item,
+            best_ask: money(self.ask)?,
+            best_bid: money(self.bid)?,
+            listing_count: self.listing_count,
+        })
+    }
+}
+
+/// The stored name of an item: its canonical name when it parses.
+fn item_key(name: &str) -> String {
+    ItemIdentity::parse(name)
+        .map(|identity| identity.to_string())
+        .unwrap_or_else(|_| name.to_string())
+}
+
+fn market_code(market: Market) -> &'static str {
+    match market {
+        Market::DMarket => "dmarket",
+        Market::CSFloat => "csfloat",
+        Market::BuffMarket => "buff_market",
+    }
+}
+
+fn parse_market(code: &str) -> Result<Market, StorageError> {
+    match code {
+        "dmarket" => Ok(Market::DMarket),
+        "csfloat" => Ok(Market::CSFloat),
+        "buff_market" => Ok(Market::BuffMarket),
+        _ => Err(StorageError::InvalidData(format!("unknown market {}", code))),
+    }
+}
+
+fn timestamp(seconds: i64) -> Result<DateTime<Utc>, StorageError> {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .ok_or_else(|| StorageError::InvalidData(format!("invalid timestamp {}", seconds)))
+}
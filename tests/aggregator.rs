@@ -0,0 +1,65 @@
+mod mock;
+
+use backend::fx::RateTable;
+use backend::{Aggregator, Currency, ItemIdentity, Market, Marketplace, Money};
+use chrono::Utc;
+use mock::{MockServer, ITEM_NAMES};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+fn usd(cents: i64) -> Money {
+    Money::from_minor(cents, Currency::USD)
+}
+
+fn markets(server: &MockServer) -> Vec<Arc<dyn Marketplace>> {
+    vec![
+        Arc::new(server.dmarket_client()),
+        Arc::new(server.csfloat_client()),
+        Arc::new(server.buff_client()),
+    ]
+}
+
+#[tokio::test]
+async fn aggregates_quotes_across_markets_in_one_currency() {
+    let server = MockServer::start().await;
+    let rates = RateTable::new(Currency::USD, Utc::now()).with_rate(Currency::CNY, Decimal::new(72, 1));
+    let aggregator = Aggregator::new(markets(&server)).with_currency(Currency::USD, Some(rates));
+
+    let aggregate = aggregator.aggregate_one(&ItemIdentity::parse(ITEM_NAMES[0]).unwrap()).await;
+    assert!(!aggregate.is_partial(), "{:?}", aggregate.failures);
+
+    // DMarket lists the item at $10.00, $15.00 and $20.00, with a $9.00 target.
+    let dmarket = aggregate.quote(Market::DMarket).unwrap();
+    assert_eq!(dmarket.best_ask.as_ref().map(|ask| ask.price), Some(usd(1000)));
+    assert_eq!(dmarket.best_bid.as_ref().map(|bid| bid.price), Some(usd(900)));
+    assert_eq!((dmarket.listing_count, dmarket.spread), (3, Some(usd(100))));
+
+    let csfloat = aggregate.quote(Market::CSFloat).unwrap();
+    assert_eq!(csfloat.best_ask.as_ref().map(|ask| ask.price), Some(usd(1250)));
+    assert_eq!((csfloat.listing_count, csfloat.spread), (2, None));
+
+    // Buff reports 1289 offers from ¥86.50 and buy orders up to ¥82.
+    let buff = aggregate.quote(Market::BuffMarket).unwrap();
+    assert_eq!(buff.listing_count, 1289);
+    assert_eq!(buff.spread, Some(Money::from_minor(450, Currency::CNY)));
+
+    // ¥82 / 7.2 = $11.39 outbids DMarket, while ¥86.50 / 7.2 = $12.01 is above its $10.00 ask.
+    let overall = &aggregate.overall;
+    assert_eq!(overall.best_ask.map(|ask| (ask.market, ask.price)), Some((Market::DMarket, usd(1000))));
+    assert_eq!(overall.best_bid.map(|bid| (bid.market, bid.price)), Some((Market::BuffMarket, usd(1139))));
+    assert_eq!(overall.spread, Some(usd(-139)));
+    assert_eq!(overall.listing_count, 3 + 2 + 1289);
+}
+
+#[tokio::test]
+async fn leaves_unconvertible_quotes_out_of_the_overall_view() {
+    let server = MockServer::start().await;
+    let aggregator = Aggregator::new(markets(&server));
+
+    let aggregate = aggregator.aggregate_one(&ItemIdentity::parse(ITEM_NAMES[0]).unwrap()).await;
+    assert_eq!(aggregate.quotes.len(), 3);
+    let overall = &aggregate.overall;
+    assert_eq!(overall.best_ask.map(|ask| (ask.market, ask.price)), Some((Market::DMarket, usd(1000))));
+    assert_eq!(overall.best_bid.map(|bid| (bid.market, bid.price)), Some((Market::DMarket, usd(900))));
+    assert_eq!(overall.spread, Some(usd(100)));
+}
@@ -0,0 +1,144 @@
+mod mock;
+
+use backend::fx::RateTable;
+use backend::{
+    Aggregator, ArbitrageOpportunity, ArbitrageScanner, Currency, Exit, ItemAggregate, ItemIdentity, Market,
+    Marketplace, Money, MoneyError, Ranking, Tradability,
+};
+use chrono::Utc;
+use mock::{MockServer, ITEM_NAMES};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+fn usd(cents: i64) -> Money {
+    Money::from_minor(cents, Currency::USD)
+}
+
+fn rates() -> RateTable {
+    RateTable::new(Currency::USD, Utc::now()).with_rate(Currency::CNY, Decimal::new(72, 1))
+}
+
+/// Prices of the first item on every market: DMarket asks $10.00 and bids $9.00, CSFloat asks
+/// $12.50, and Buff asks ¥86.50 over 1289 offers and bids ¥82.
+async fn aggregate(server: &MockServer) -> Vec<ItemAggregate> {
+    let markets: Vec<Arc<dyn Marketplace>> = vec![
+        Arc::new(server.dmarket_client()),
+        Arc::new(server.csfloat_client()),
+        Arc::new(server.buff_client()),
+    ];
+    let identity = ItemIdentity::parse(ITEM_NAMES[0]).unwrap();
+    vec![Aggregator::new(markets).aggregate_one(&identity).await]
+}
+
+fn summary(opportunities: &[ArbitrageOpportunity]) -> Vec<(Market, Exit, Money, usize)> {
+    opportunities
+        .iter()
+        .map(|opportunity| (opportunity.sell_market, opportunity.exit, opportunity.net_profit, opportunity.liquidity))
+        .collect()
+}
+
+#[tokio::test]
+async fn finds_opportunities_net_of_fees() {
+    let server = MockServer::start().await;
+    let aggregates = aggregate(&server).await;
+    let scanner = ArbitrageScanner::new().with_currency(Currency::USD, Some(rates()));
+
+    // Only DMarket's $10.00 offer is cheap enough. Net of fees it sells for:
+    // - CSFloat listing: $12.50 * 0.98 * 0.972 = $11.91.
+    // - Buff listing: ¥86.50 * 0.975 * 0.99 = ¥83.50 = $11.60.
+    // - Buff bid: ¥82 * 0.975 * 0.99 = ¥79.15 = $10.99.
+    let opportunities = scanner.scan(&aggregates);
+    assert_eq!(
+        summary(&opportunities),
+        [
+            (Market::CSFloat, Exit::Listing, usd(191), 2),
+            (Market::BuffMarket, Exit::Listing, usd(160), 1289),
+            (Market::BuffMarket, Exit::Bid, usd(99), 1289),
+        ]
+    );
+    let best = &opportunities[0];
+    assert_eq!((best.buy_market, best.cost, best.net_proceeds), (Market::DMarket, usd(1000), usd(1191)));
+    assert_eq!(best.sale_price, usd(1250));
+    assert_eq!(best.roi, Decimal::new(191, 3));
+    assert_eq!(opportunities[2].sale_price, Money::from_minor(8200, Currency::CNY));
+
+    // Without rates, Buff prices cannot be compared.
+    let opportunities = ArbitrageScanner::new().scan(&aggregates);
+    assert_eq!(summary(&opportunities), [(Market::CSFloat, Exit::Listing, usd(191), 2)]);
+}
+
+#[tokio::test]
+async fn ranks_and_filters_opportunities() {
+    let server = MockServer::start().await;
+    let aggregates = aggregate(&server).await;
+    let scanner = || ArbitrageScanner::new().with_currency(Currency::USD, Some(rates()));
+
+    let by_liquidity = scanner().with_ranking(Ranking::Liquidity).scan(&aggregates);
+    assert_eq!(
+        summary(&by_liquidity),
+        [
+            (Market::BuffMarket, Exit::Listing, usd(160), 1289),
+            (Market::BuffMarket, Exit::Bid, usd(99), 1289),
+            (Market::CSFloat, Exit::Listing, usd(191), 2),
+        ]
+    );
+    let by_roi = scanner().with_ranking(Ranking::Roi).scan(&aggregates);
+    assert_eq!(summary(&by_roi), summary(&scanner().scan(&aggregates)));
+
+    let bids_only = scanner().with_listing_exit(false).scan(&aggregates);
+    assert_eq!(summary(&bids_only), [(Market::BuffMarket, Exit::Bid, usd(99), 1289)]);
+
+    assert_eq!(scanner().with_min_profit(usd(100)).unwrap().scan(&aggregates).len(), 2);
+    assert_eq!(scanner().with_min_profit(usd(99)).unwrap().scan(&aggregates).len(), 3);
+    assert_eq!(
+        scanner().with_min_profit(Money::from_minor(100, Currency::CNY)).err(),
+        Some(MoneyError::CurrencyMismatch(Currency::CNY, Currency::USD))
+    );
+    assert_eq!(scanner().with_min_roi(Decimal::new(17, 2)).scan(&aggregates).len(), 1);
+}
+
+#[tokio::test]
+async fn accounts_for_trade_locks() {
+    let server = MockServer::start().await;
+    let mut aggregates = aggregate(&server).await;
+    // CSFloat's offer becomes the cheapest one, but it is locked for a week.
+    for quote in &mut aggregates[0].quotes {
+        if quote.market == Market::CSFloat {
+            let ask = quote.best_ask.as_mut().unwrap();
+            ask.price = usd(800);
+            ask.tradability = Tradability::Locked { days_remaining: Some(7) };
+        }
+    }
+    let scanner = || ArbitrageScanner::new().with_currency(Currency::USD, Some(rates()));
+    let buy_markets = |opportunities: &[ArbitrageOpportunity]| {
+        opportunities.iter().map(|opportunity| opportunity.buy_market).collect::<Vec<_>>()
+    };
+
+    // The locked offer earns the most, but ranks after the tradable DMarket one.
+    let opportunities = scanner().scan(&aggregates);
+    let locked = opportunities.iter().filter(|opportunity| opportunity.buy_market == Market::CSFloat);
+    assert!(locked.clone().count() > 0);
+    assert!(locked.clone().all(|opportunity| opportunity.trade_lock_days() == Some(7)));
+    assert!(locked.map(|opportunity| opportunity.net_profit).any(|profit| profit > opportunities[0].net_profit));
+    let dmarket_buys = opportunities.iter().take_while(|opportunity| opportunity.buy_market == Market::DMarket).count();
+    assert!(buy_markets(&opportunities[dmarket_buys..]).iter().all(|market| *market == Market::CSFloat));
+    assert_eq!(buy_markets(&scanner().with_ranking(Ranking::Roi).scan(&aggregates))[0], Market::DMarket);
+
+    assert_eq!(scanner().with_max_trade_lock_days(7).scan(&aggregates).len(), opportunities.len());
+    assert_eq!(buy_markets(&scanner().with_max_trade_lock_days(6).scan(&aggregates)), vec![Market::DMarket; dmarket_buys]);
+
+    // Offers of unknown tradability are kept unless asked otherwise, after tradable ones.
+    for quote in &mut aggregates[0].quotes {
+        if let Some(ask) = quote.best_ask.as_mut() {
+            ask.tradability = match quote.market {
+                Market::CSFloat => Tradability::Tradable,
+                _ => Tradability::Unknown,
+            };
+        }
+    }
+    let opportunities = scanner().with_max_trade_lock_days(0).scan(&aggregates);
+    assert_eq!(opportunities[0].buy_market, Market::CSFloat);
+    assert_eq!(opportunities.last().unwrap().buy_market, Market::DMarket);
+    let tradable_only = scanner().with_max_trade_lock_days(0).with_unknown_tradability(false).scan(&aggregates);
+    assert!(buy_markets(&tradable_only).iter().all(|market| *market == Market::CSFloat));
+}
@@ -0,0 +1,132 @@
+mod mock;
+
+use backend::{
+    ApiErrorKind, BuffMarketClient, BuffMarketError, Currency, EndpointGroup, Market, Marketplace, Money, Quota, RateLimiter,
+    WaitStats,
+};
+use mock::{Failure, MockServer, ITEM_NAMES};
+use std::time::Duration;
+
+#[tokio::test]
+async fn pages_through_market_goods() {
+    let server = MockServer::start().await;
+    let client = server.buff_client();
+
+    let page = client.get_market_listings("csgo", 2, 4).await.unwrap();
+    let data = page.data.unwrap();
+    assert_eq!((data.page_num, data.total_count, data.total_page), (2, 10, 3));
+    assert_eq!(data.items.len(), 4);
+
+    let goods = client.get_all_market_listings("csgo", 4).await.unwrap();
+    assert_eq!(goods.len(), 10);
+    assert_eq!(server.requests_to("/api/market/goods").len(), 4);
+    assert_eq!(goods[0].sell_min_price, Money::from_minor(8650, Currency::CNY));
+}
+
+#[tokio::test]
+async fn survives_transient_failures_while_paging() {
+    let server = MockServer::start().await;
+    let client = server.buff_client();
+    server.fail("/api/market/goods?game=csgo&page_num=2", Failure::status(500), 1);
+
+    let goods = client.get_all_market_listings("csgo", 4).await.unwrap();
+    assert_eq!(goods.len(), 10);
+    assert_eq!(server.requests_to("/api/market/goods").len(), 4);
+}
+
+#[tokio::test]
+async fn stops_at_the_reported_last_page() {
+    let server = MockServer::start().await;
+    let client = server.buff_client();
+    server.state().buff_total_page = Some(2);
+
+    let goods = client.get_all_market_listings("csgo", 4).await.unwrap();
+    assert_eq!(goods.len(), 8);
+}
+
+#[tokio::test]
+async fn crawls_only_the_pages_it_needs() {
+    let server = MockServer::start().await;
+    let client = server.buff_client();
+    let goods = server.state().buff_goods.clone();
+    server.state().buff_goods = goods.iter().cycle().take(200).cloned().collect();
+
+    let listings = client.crawl(Some(100)).await.unwrap();
+    assert_eq!(listings.len(), 100);
+    let requests = server.requests_to("/api/market/goods");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].query("page_num").as_deref(), Some("2"));
+
+    assert_eq!(client.crawl(None).await.unwrap().len(), 200);
+    assert_eq!(server.requests_to("/api/market/goods").len(), 5);
+}
+
+#[tokio::test]
+async fn cloned_clients_share_their_rate_limiter() {
+    let server = MockServer::start().await;
+    let limiter = RateLimiter::new([(EndpointGroup::MarketData, Quota::per_second(20).with_burst(1))]);
+    let client = server.buff_client().with_rate_limiter(limiter.clone());
+    let clone = client.clone();
+
+    let (first, second, third) = tokio::join!(
+        client.get_market_listings("csgo", 1, 4),
+        clone.get_market_listings("csgo", 2, 4),
+        clone.get_market_listings("csgo", 3, 4),
+    );
+    assert!(first.is_ok() && second.is_ok() && third.is_ok());
+
+    // The second and third requests waited 50ms and 100ms for their tokens.
+    let stats = client.rate_limiter().stats(EndpointGroup::MarketData);
+    assert_eq!((stats.requests, stats.delayed), (3, 2));
+    assert!(stats.total_wait > Duration::from_millis(140) && stats.total_wait <= Duration::from_millis(150), "{:?}", stats);
+    assert_eq!(limiter.stats(EndpointGroup::MarketData), stats);
+    assert_eq!(clone.rate_limiter().stats(EndpointGroup::Account), WaitStats::default());
+}
+
+#[tokio::test]
+async fn reads_buy_orders() {
+    let server = MockServer::start().await;
+    let client = server.buff_client();
+
+    let orders = client.get_buy_orders_by_name("csgo", ITEM_NAMES[0], 1).await.unwrap();
+    let data = orders.data.unwrap();
+    assert_eq!(data.items.len(), 2);
+    assert_eq!(data.items[1].price, Money::from_minor(8150, Currency::CNY));
+
+    let bid = client.highest_bid(ITEM_NAMES[0]).await.unwrap().unwrap();
+    assert_eq!(bid.market, Market::BuffMarket);
+    assert_eq!(bid.price, Money::from_minor(8200, Currency::CNY));
+    assert_eq!(bid.quantity, Some(143));
+
+    let ask = client.lowest_ask(ITEM_NAMES[3]).await.unwrap().unwrap();
+    assert_eq!(ask.price, Money::from_minor(8950, Currency::CNY));
+    assert_eq!(client.crawl(Some(3)).await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn reports_api_errors() {
+    let server = MockServer::start().await;
+    let client = BuffMarketClient::builder()
+        .credentials("session=expired", mock::BUFF_CSRF_TOKEN)
+        .base_url(server.url())
+        .build()
+        .unwrap();
+
+    let error = client.get_market_listings("csgo", 1, 4).await.unwrap_err();
+    match error {
+        BuffMarketError::ApiError(error) => {
+            assert_eq!(error.kind, ApiErrorKind::Unauthorized);
+            assert_eq!(error.code.as_deref(), Some("Login Required"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let client = server.buff_client();
+    server.fail("/api/market/goods", Failure::status(404), 1);
+    let error = client.get_market_listings("csgo", 1, 4).await.unwrap_err();
+    assert!(matches!(&error, BuffMarketError::ApiError(error) if error.kind == ApiErrorKind::NotFound), "{:?}", error);
+
+    server.fail("/api/market/goods", Failure::MalformedJson, 1);
+    let error = client.get_market_listings("csgo", 1, 4).await.unwrap_err();
+    assert!(matches!(error, BuffMarketError::JsonParse(_)), "{:?}", error);
+}
@@ -0,0 +1,75 @@
+mod mock;
+
+use backend::{Cassette, CassetteError, DMarketError};
+use mock::{Failure, MockServer, BUFF_CSRF_TOKEN, BUFF_SESSION_COOKIE, CSFLOAT_API_KEY, ITEM_NAMES};
+use std::path::PathBuf;
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("backend-cassettes-{}", std::process::id()))
+        .join(format!("{}.json", name))
+}
+
+#[tokio::test]
+async fn replays_recorded_traffic_without_a_server() {
+    let path = cassette_path("replay");
+    let server = MockServer::start().await;
+    let (public_key, _) = server.dmarket_keys();
+    let cassette = Cassette::record(&path);
+    let dmarket = server.dmarket_client().with_cassette(cassette.clone());
+    let csfloat = server.csfloat_client().with_cassette(cassette.clone());
+    let buff = server.buff_client().with_cassette(cassette.clone());
+
+    let profile = dmarket.account().get_user_profile().await.unwrap();
+    let listings = csfloat.listings().get_listings(None).await.unwrap();
+    let orders = buff.get_buy_orders_by_name("csgo", ITEM_NAMES[0], 1).await.unwrap();
+    assert_eq!(cassette.interactions().len(), 4);
+
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    for secret in [public_key.as_str(), CSFLOAT_API_KEY, BUFF_SESSION_COOKIE, BUFF_CSRF_TOKEN, "dmar ed25519", "mock-trader@example.com"] {
+        assert!(!recorded.contains(secret), "cassette contains {}", secret);
+    }
+
+    drop(server);
+    let cassette = Cassette::replay(&path).unwrap();
+    let dmarket = dmarket.with_cassette(cassette.clone());
+    let csfloat = csfloat.with_cassette(cassette.clone());
+    let buff = buff.with_cassette(cassette.clone());
+
+    let replayed = dmarket.account().get_user_profile().await.unwrap();
+    assert_eq!(replayed.username, profile.username);
+    assert_eq!(replayed.email, "[REDACTED]");
+    let replayed = csfloat.listings().get_listings(None).await.unwrap();
+    assert_eq!(replayed.data.len(), listings.data.len());
+    // Buff's timestamp parameter differs from the recording but is ignored.
+    let replayed = buff.get_buy_orders_by_name("csgo", ITEM_NAMES[0], 1).await.unwrap();
+    assert_eq!(replayed.data.unwrap().items.len(), orders.data.unwrap().items.len());
+    assert_eq!(cassette.remaining(), 0);
+}
+
+#[tokio::test]
+async fn replays_failures_and_retries_in_order() {
+    let path = cassette_path("retries");
+    let server = MockServer::start().await;
+    let cassette = Cassette::record(&path);
+    let client = server.dmarket_client().with_cassette(cassette.clone());
+    server.fail("/account/v1/balance", Failure::rate_limited(30), 1);
+
+    // The Retry-After exceeds the fast policy's maximum, so the 429 is returned as is.
+    assert!(client.account().get_account_balance().await.is_err());
+    let balances = client.account().get_account_balance().await.unwrap();
+    drop(server);
+
+    let cassette = Cassette::replay(&path).unwrap();
+    let client = client.with_cassette(cassette.clone());
+    assert!(client.account().get_account_balance().await.is_err());
+    let replayed = client.account().get_account_balance().await.unwrap();
+    assert_eq!(replayed[0].amount, balances[0].amount);
+
+    let error = client.account().get_account_balance().await.unwrap_err();
+    assert!(
+        matches!(error, DMarketError::CassetteError(CassetteError::NoMatch { .. })),
+        "{:?}",
+        error
+    );
+}
@@ -0,0 +1,67 @@
+mod mock;
+
+use backend::{CSFloatIds, ItemCatalog, MarketIds, MarketItemsQuery};
+use mock::{MockServer, ITEM_NAMES};
+use std::sync::Arc;
+
+const CS2: &str = "a8db";
+
+fn searches(server: &MockServer) -> usize {
+    server
+        .requests_to("/api/market/goods")
+        .iter()
+        .filter(|request| request.query("search").is_some())
+        .count()
+}
+
+#[tokio::test]
+async fn fills_from_market_clients() {
+    let server = MockServer::start().await;
+    let catalog = Arc::new(ItemCatalog::new());
+
+    let dmarket = server.dmarket_client().with_catalog(catalog.clone());
+    dmarket.exchange().get_market_items(&MarketItemsQuery::new(CS2).limit(10)).await.unwrap();
+    let buff = server.buff_client().with_catalog(catalog.clone());
+    buff.get_market_listings("csgo", 1, 20).await.unwrap();
+    let csfloat = server.csfloat_client().with_catalog(catalog.clone());
+    csfloat.listings().get_listings(None).await.unwrap();
+
+    assert_eq!(
+        catalog.get(ITEM_NAMES[0]),
+        Some(MarketIds {
+            dmarket_class_id: Some("a8db:class0".to_string()),
+            csfloat: Some(CSFloatIds { def_index: 7, paint_index: Some(282) }),
+            buff_goods_id: Some(33815),
+        })
+    );
+    for (i, name) in ITEM_NAMES.iter().enumerate() {
+        assert_eq!(catalog.dmarket_class_id(name), Some(format!("a8db:class{}", i)));
+        assert_eq!(catalog.buff_goods_id(name), Some(33815 + i as i64));
+        assert_eq!(catalog.csfloat_ids(name).map(|ids| ids.def_index), Some(7 + i as i32));
+    }
+}
+
+#[tokio::test]
+async fn resolves_buy_orders_from_the_catalog() {
+    let server = MockServer::start().await;
+    let catalog = Arc::new(ItemCatalog::new());
+    catalog.record_buff(ITEM_NAMES[0], 33815);
+    let client = server.buff_client().with_catalog(catalog);
+
+    let orders = client.get_buy_orders_by_name("csgo", ITEM_NAMES[0], 1).await.unwrap();
+    assert_eq!(orders.data.unwrap().items.len(), 2);
+    assert_eq!(searches(&server), 0);
+    let requests = server.requests_to("/api/market/goods/buy_order");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].query("goods_id").as_deref(), Some("33815"));
+}
+
+#[tokio::test]
+async fn searches_for_goods_missing_from_the_catalog() {
+    let server = MockServer::start().await;
+    let client = server.buff_client().with_catalog(Arc::new(ItemCatalog::new()));
+
+    let orders = client.get_buy_orders_by_name("csgo", ITEM_NAMES[0], 1).await.unwrap();
+    assert_eq!(orders.data.unwrap().items.len(), 2);
+    assert_eq!(searches(&server), 1);
+}
@@ -0,0 +1,288 @@
+use async_trait::async_trait;
+use backend::marketplace::{Capabilities, InventoryAsset};
+use backend::{
+    Collector, CollectorConfig, CollectorError, ItemAggregate, JobConfig, JobKind, Market, MarketItem, Marketplace,
+    MarketplaceError, NormalizedListing, Sink,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+const ITEMS: [&str; 6] = [
+    "AK-47 | Redline (Field-Tested)",
+    "AWP | Asiimov (Field-Tested)",
+    "M4A1-S | Printstream (Minimal Wear)",
+    "Desert Eagle | Blaze (Factory New)",
+    "Glock-18 | Fade (Factory New)",
+    "USP-S | Kill Confirmed (Minimal Wear)",
+];
+
+/// A market answering from memory after a delay, tracking how many of its calls run at once.
+struct StubMarket {
+    market: Market,
+    delay: Duration,
+    crawl_delay: Duration,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl StubMarket {
+    fn new(market: Market) -> Self {
+        Self {
+            market,
+            delay: Duration::ZERO,
+            crawl_delay: Duration::ZERO,
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    fn crawl_delay(mut self, crawl_delay: Duration) -> Self {
+        self.crawl_delay = crawl_delay;
+        self
+    }
+
+    async fn call(&self, delay: Duration) {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(delay).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn listing(&self, name: &str) -> NormalizedListing {
+        let item: MarketItem = serde_json::from_value(json!({
+            "itemId": format!("{}-item", self.market),
+            "type": "dmarket",
+            "title": name,
+            "slug": "",
+            "status": "active",
+            "image": "",
+            "classId": "a8db:class0",
+            "game": "csgo",
+            "gameId": "a8db",
+            "price": {"amount": "1250", "currency": "USD"},
+            "extra": {},
+            "attributes": [],
+            "locked": false,
+            "createdAt": 1714000000,
+            "updatedAt": 1714000000,
+            "inMarket": true,
+            "withdrawable": true
+        }))
+        .unwrap();
+        NormalizedListing {
+            market: self.market,
+            ..NormalizedListing::from(item)
+        }
+    }
+}
+
+#[async_trait]
+impl Marketplace for StubMarket {
+    fn market(&self) -> Market {
+        self.market
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            list_offers: true,
+            inventory: true,
+            crawl: true,
+            ..Capabilities::default()
+        }
+    }
+
+    async fn list_offers(&self, market_hash_name: &str, _limit: u32) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        self.call(self.delay).await;
+        Ok(vec![self.listing(market_hash_name)])
+    }
+
+    async fn my_inventory(&self) -> Result<Vec<InventoryAsset>, MarketplaceError> {
+        self.call(self.delay).await;
+        Ok(vec![InventoryAsset {
+            market: self.market,
+            asset_id: "asset-1".to_string(),
+            market_hash_name: ITEMS[0].to_string(),
+            tradable: Some(true),
+        }])
+    }
+
+    async fn crawl(&self, max_items: Option<usize>) -> Result<Vec<NormalizedListing>, MarketplaceError> {
+        self.call(self.crawl_delay).await;
+        Ok(ITEMS.iter().take(max_items.unwrap_or(ITEMS.len())).map(|name| self.listing(name)).collect())
+    }
+}
+
+/// What was written to a [`RecordingSink`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Write {
+    Listings(Vec<Market>),
+    Aggregates(Vec<(String, usize)>),
+    Inventory(Market, usize),
+}
+
+#[derive(Default)]
+struct RecordingSink {
+    writes: Mutex<Vec<Write>>,
+}
+
+impl RecordingSink {
+    fn writes(&self) -> Vec<Write> {
+        self.writes.lock().unwrap().clone()
+    }
+
+    fn record(&self, write: Write) -> Result<(), CollectorError> {
+        self.writes.lock().unwrap().push(write);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for RecordingSink {
+    async fn write_listings(&self, _: DateTime<Utc>, listings: &[NormalizedListing]) -> Result<(), CollectorError> {
+        self.record(Write::Listings(listings.iter().map(|listing| listing.market).collect()))
+    }
+
+    async fn write_aggregates(&self, _: DateTime<Utc>, aggregates: &[ItemAggregate]) -> Result<(), CollectorError> {
+        let items = aggregates
+            .iter()
+            .map(|aggregate| (aggregate.identity.to_string(), aggregate.overall.listing_count))
+            .collect();
+        self.record(Write::Aggregates(items))
+    }
+
+    async fn write_inventory(&self, _: DateTime<Utc>, market: Market, assets: &[InventoryAsset]) -> Result<(), CollectorError> {
+        self.record(Write::Inventory(market, assets.len()))
+    }
+}
+
+fn job(name: &str, interval_secs: u64, markets: &[Market], kind: JobKind) -> JobConfig {
+    JobConfig {
+        name: name.to_string(),
+        interval_secs,
+        markets: markets.to_vec(),
+        kind,
+    }
+}
+
+fn collector(config: &CollectorConfig, markets: Vec<Arc<StubMarket>>, sink: &Arc<RecordingSink>) -> Collector {
+    let markets = markets.into_iter().map(|market| market as Arc<dyn Marketplace>).collect();
+    Collector::new(config, markets, Arc::clone(sink) as Arc<dyn Sink>)
+}
+
+#[tokio::test]
+async fn writes_what_each_job_kind_collects() {
+    let sink = Arc::new(RecordingSink::default());
+    let markets = vec![Arc::new(StubMarket::new(Market::DMarket)), Arc::new(StubMarket::new(Market::CSFloat))];
+    let collector = collector(&CollectorConfig::default(), markets, &sink);
+
+    collector
+        .run_job(&job("crawl", 60, &[], JobKind::FullCrawl { max_items: Some(2) }))
+        .await
+        .unwrap();
+    let hot_items = JobKind::HotItems {
+        items: vec![ITEMS[0].to_string(), "".to_string(), ITEMS[1].to_string()],
+        sample_size: 10,
+    };
+    collector.run_job(&job("hot", 60, &[], hot_items)).await.unwrap();
+    collector
+        .run_job(&job("inventory", 60, &[Market::CSFloat], JobKind::InventorySync))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sink.writes(),
+        [
+            Write::Listings(vec![Market::DMarket; 2]),
+            Write::Listings(vec![Market::CSFloat; 2]),
+            // The unparseable name is skipped; both markets list one offer of each item.
+            Write::Aggregates(vec![(ITEMS[0].to_string(), 2), (ITEMS[1].to_string(), 2)]),
+            Write::Inventory(Market::CSFloat, 1),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn runs_each_job_on_its_interval() {
+    let sink = Arc::new(RecordingSink::default());
+    let markets = vec![Arc::new(StubMarket::new(Market::DMarket)), Arc::new(StubMarket::new(Market::CSFloat))];
+    let config = CollectorConfig {
+        jobs: vec![
+            job("fast", 10, &[Market::DMarket], JobKind::InventorySync),
+            job("slow", 30, &[Market::CSFloat], JobKind::InventorySync),
+        ],
+        ..CollectorConfig::default()
+    };
+
+    collector(&config, markets, &sink)
+        .run(tokio::time::sleep(Duration::from_secs(65)))
+        .await;
+
+    // Jobs run right away, then every interval: at 0s, 10s, ..., 60s and at 0s, 30s, 60s.
+    let mut runs = HashMap::new();
+    for write in sink.writes() {
+        let Write::Inventory(market, _) = write else {
+            panic!("unexpected write {:?}", write);
+        };
+        *runs.entry(market).or_insert(0) += 1;
+    }
+    assert_eq!(runs, HashMap::from([(Market::DMarket, 7), (Market::CSFloat, 3)]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn limits_calls_per_market() {
+    let sink = Arc::new(RecordingSink::default());
+    let dmarket = Arc::new(StubMarket::new(Market::DMarket).delay(Duration::from_secs(1)));
+    let csfloat = Arc::new(StubMarket::new(Market::CSFloat).delay(Duration::from_secs(1)));
+    let config = CollectorConfig {
+        market_calls: HashMap::from([(Market::DMarket, 2), (Market::CSFloat, 6)]),
+        ..CollectorConfig::default()
+    };
+    let collector = collector(&config, vec![Arc::clone(&dmarket), Arc::clone(&csfloat)], &sink);
+    let hot_items = JobKind::HotItems {
+        items: ITEMS.iter().map(|name| name.to_string()).collect(),
+        sample_size: 10,
+    };
+
+    let start = Instant::now();
+    collector.run_job(&job("hot", 60, &[], hot_items)).await.unwrap();
+
+    // The aggregator runs up to 8 queries at once, but DMarket only takes two at a time.
+    assert_eq!(dmarket.max_in_flight.load(Ordering::SeqCst), 2);
+    assert!(csfloat.max_in_flight.load(Ordering::SeqCst) > 2);
+    assert_eq!(start.elapsed(), Duration::from_secs(3));
+    assert!(matches!(&sink.writes()[..], [Write::Aggregates(items)] if items.len() == ITEMS.len()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancels_crawls_and_finishes_other_jobs_on_shutdown() {
+    let sink = Arc::new(RecordingSink::default());
+    let dmarket = StubMarket::new(Market::DMarket)
+        .delay(Duration::from_secs(5))
+        .crawl_delay(Duration::from_secs(3600));
+    let config = CollectorConfig {
+        jobs: vec![
+            job("crawl", 7200, &[], JobKind::FullCrawl { max_items: None }),
+            job("inventory", 7200, &[], JobKind::InventorySync),
+        ],
+        ..CollectorConfig::default()
+    };
+
+    let start = Instant::now();
+    collector(&config, vec![Arc::new(dmarket)], &sink)
+        .run(tokio::time::sleep(Duration::from_secs(1)))
+        .await;
+
+    // Shutdown waits for the inventory sync started at 0s, but not for the hour-long crawl.
+    assert_eq!(start.elapsed(), Duration::from_secs(5));
+    assert_eq!(sink.writes(), [Write::Inventory(Market::DMarket, 1)]);
+}
@@ -0,0 +1,90 @@
+mod mock;
+
+use backend::{ApiErrorKind, CSFloatClient, CSFloatError, CreateListingRequest, Currency, ListingsQuery, Marketplace, Money};
+use mock::{Failure, MockServer, ITEM_NAMES};
+
+#[tokio::test]
+async fn filters_listings() {
+    let server = MockServer::start().await;
+    let client = server.csfloat_client();
+
+    let all = client.listings().get_listings(None).await.unwrap();
+    assert_eq!(all.data.len(), 8);
+
+    let query = ListingsQuery {
+        market_hash_name: Some(ITEM_NAMES[2].to_string()),
+        max_price: Some(Money::from_minor(1500, Currency::USD)),
+        ..ListingsQuery::default()
+    };
+    let filtered = client.listings().get_listings(Some(query)).await.unwrap();
+    assert_eq!(filtered.data.len(), 1);
+    assert_eq!(filtered.data[0].price, Money::from_minor(1450, Currency::USD));
+    assert_eq!(filtered.data[0].item.scm, Some(Money::from_minor(1301, Currency::USD)));
+
+    let listing = client.listings().get_listing(&filtered.data[0].id).await.unwrap();
+    assert_eq!(listing.item.market_hash_name, ITEM_NAMES[2]);
+}
+
+#[tokio::test]
+async fn creates_listings() {
+    let server = MockServer::start().await;
+    let client = server.csfloat_client();
+
+    let listing = client
+        .listings()
+        .create_listing(CreateListingRequest {
+            asset_id: 35000000123,
+            type_: "buy_now".to_string(),
+            price: Money::from_minor(2000, Currency::USD),
+            max_offer_discount: None,
+            reserve_price: None,
+            duration_days: None,
+            description: None,
+            private: None,
+        })
+        .await
+        .unwrap();
+    assert!(listing.is_seller);
+    assert_eq!(listing.price, Money::from_minor(2000, Currency::USD));
+    assert_eq!(server.state().csfloat_listings.len(), 9);
+}
+
+#[tokio::test]
+async fn rejects_unknown_api_keys() {
+    let server = MockServer::start().await;
+    let client = CSFloatClient::builder()
+        .api_key("wrong-key")
+        .base_url(format!("{}/api/v1", server.url()))
+        .build()
+        .unwrap();
+
+    let error = client.listings().get_listings(None).await.unwrap_err();
+    match error {
+        CSFloatError::ApiError(error) => {
+            assert_eq!(error.kind, ApiErrorKind::Unauthorized);
+            assert_eq!(error.message, "Invalid API key");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn retries_until_the_listing_loads() {
+    let server = MockServer::start().await;
+    let client = server.csfloat_client();
+    server.fail("/api/v1/listings", Failure::rate_limited(0), 2);
+
+    let ask = client.lowest_ask(ITEM_NAMES[0]).await.unwrap().unwrap();
+    assert_eq!(ask.price, Money::from_minor(1250, Currency::USD));
+    assert_eq!(server.requests_to("/api/v1/listings").len(), 3);
+}
+
+#[tokio::test]
+async fn reports_malformed_responses() {
+    let server = MockServer::start().await;
+    let client = server.csfloat_client();
+    server.fail("/api/v1/listings", Failure::MalformedJson, 1);
+
+    let error = client.listings().get_listings(None).await.unwrap_err();
+    assert!(matches!(error, CSFloatError::SerializationError(_)), "{:?}", error);
+}
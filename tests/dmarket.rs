@@ -0,0 +1,621 @@
+mod mock;
+
+use backend::{
+    ApiErrorKind, BuyOfferRequest, BuyOrder, CreateTargetRequest, Currency, DMarketClient, DMarketError, DopplerPhase, Exterior,
+    ItemCategory, LastSale, LastSalesQuery, Market, MarketItemsOrder, MarketItemsQuery, Marketplace, Money, MoneyError,
+    OfferType, OrderBook, OrderBookQuery, OrderDirection, SaleType, SalesPeriod, SalesStats, SellOfferItem, SellOfferRequest, Target,
+};
+use futures::{StreamExt, TryStreamExt};
+use mock::{Failure, MockResponse, MockServer, ITEM_NAMES, LAST_SALE_AT};
+use reqwest::StatusCode;
+use serde_json::json;
+
+const CS2: &str = "a8db";
+
+async fn market_items(client: &DMarketClient, limit: u32, offset: u32, title: Option<&str>) -> Result<backend::MarketItemsResponse, DMarketError> {
+    let mut query = MarketItemsQuery::new(CS2).limit(limit).offset(offset);
+    if let Some(title) = title {
+        query = query.title(title);
+    }
+    client.exchange().get_market_items(&query).await
+}
+
+#[tokio::test]
+async fn reads_profile_and_balance() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let profile = client.account().get_user_profile().await.unwrap();
+    assert_eq!(profile.username, "mock-trader");
+    assert_eq!(profile.settings.targets_limit, 300);
+
+    let balances = client.account().get_account_balance().await.unwrap();
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[0].amount, Money::from_minor(12345, Currency::USD));
+    assert_eq!(balances[0].available_to_withdraw, Some(Money::from_minor(10000, Currency::USD)));
+    assert_eq!(balances[1].amount, Money::from_minor(2500, Currency::DMC));
+}
+
+#[tokio::test]
+async fn lists_market_items_and_games() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let page = market_items(&client, 5, 10, None).await.unwrap();
+    assert_eq!(page.total, "12");
+    assert_eq!(page.objects.len(), 2);
+    assert_eq!(page.objects[0].price, Money::from_minor(2250, Currency::USD));
+    assert_eq!(page.objects[0].suggested_price, Some(Money::from_minor(2250, Currency::DMC)));
+
+    let filtered = market_items(&client, 100, 0, Some(ITEM_NAMES[1])).await.unwrap();
+    assert_eq!(filtered.objects.len(), 3);
+    assert!(filtered.objects.iter().all(|item| item.title == ITEM_NAMES[1]));
+
+    let games = client.exchange().get_games().await.unwrap();
+    assert_eq!(games.iter().map(|game| game.id.as_str()).collect::<Vec<_>>(), ["a8db", "9a92"]);
+
+    let offers = client.exchange().get_market_item_details("a8db:class2", 10, 0, "USD").await.unwrap();
+    assert_eq!(offers.objects.len(), 3);
+    assert!(offers.objects.iter().all(|offer| offer.class_id == "a8db:class2"));
+}
+
+#[tokio::test]
+async fn signs_query_strings() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    // Titles need escaping, and the signature must cover the escaped form.
+    market_items(&client, 10, 0, Some("M4A1-S | Printstream (Minimal Wear)")).await.unwrap();
+
+    let request = server.requests_to("/exchange/v1/market/items").pop().unwrap();
+    assert!(request.raw_query.contains("title=M4A1-S+%7C+Printstream+%28Minimal+Wear%29"));
+}
+
+#[tokio::test]
+async fn sends_typed_market_items_queries() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    let query = MarketItemsQuery::new(CS2)
+        .title("Karambit | Doppler")
+        .category(ItemCategory::Knife)
+        .exterior(Exterior::FactoryNew)
+        .exterior(Exterior::MinimalWear)
+        .phase(DopplerPhase::BlackPearl)
+        .float_range(0.0, 0.01)
+        .price_from(Money::from_minor(50000, Currency::USD))
+        .price_to(Money::from_minor(250000, Currency::USD))
+        .offer_type(OfferType::P2P)
+        .order_by(MarketItemsOrder::Price, OrderDirection::Desc)
+        .limit(20)
+        .cursor("next page");
+
+    client.exchange().get_market_items(&query).await.unwrap();
+
+    let request = server.requests_to("/exchange/v1/market/items").pop().unwrap();
+    assert_eq!(request.query("gameId").as_deref(), Some(CS2));
+    assert_eq!(request.query("currency").as_deref(), Some("USD"));
+    assert_eq!(request.query("limit").as_deref(), Some("20"));
+    assert_eq!(request.query("orderBy").as_deref(), Some("price"));
+    assert_eq!(request.query("orderDir").as_deref(), Some("desc"));
+    assert_eq!(request.query("priceFrom").as_deref(), Some("50000"));
+    assert_eq!(request.query("priceTo").as_deref(), Some("250000"));
+    assert_eq!(request.query("types").as_deref(), Some("p2p"));
+    assert_eq!(request.query("cursor").as_deref(), Some("next page"));
+    assert_eq!(
+        request.query("treeFilters").as_deref(),
+        Some("category_0[]=knife,exterior[]=factory new,exterior[]=minimal wear,phase[]=black-pearl,floatValueFrom[]=0,floatValueTo[]=0.01")
+    );
+    // Every value is escaped the same way, and the mock only accepts the request if the
+    // signature covers the escaped query.
+    assert!(request.raw_query.contains("treeFilters=category_0%5B%5D%3Dknife%2Cexterior%5B%5D%3Dfactory+new"));
+    assert!(request.raw_query.contains("cursor=next+page"));
+}
+
+#[tokio::test]
+async fn rejects_invalid_market_items_queries() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    let invalid = [
+        MarketItemsQuery::new(CS2).price_to(Money::from_minor(100, Currency::EUR)),
+        MarketItemsQuery::new(CS2)
+            .price_from(Money::from_minor(500, Currency::USD))
+            .price_to(Money::from_minor(100, Currency::USD)),
+        MarketItemsQuery::new(CS2).float_range(0.5, 0.2),
+        MarketItemsQuery::new(""),
+    ];
+
+    for query in &invalid {
+        let error = client.exchange().get_market_items(query).await.unwrap_err();
+        assert!(
+            matches!(error, DMarketError::InvalidQuery(_) | DMarketError::MoneyError(_)),
+            "{:?} was accepted: {}",
+            query,
+            error
+        );
+    }
+    assert!(server.requests_to("/exchange/v1/market/items").is_empty());
+}
+
+#[tokio::test]
+async fn searches_market_items_by_title() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let found = client.exchange().search_market_items(ITEM_NAMES[2], "usd", 100, 0, Some(CS2)).await.unwrap();
+    assert_eq!(found.objects.len(), 3);
+    assert!(found.objects.iter().all(|item| item.title == ITEM_NAMES[2]));
+
+    let request = server.requests_to("/exchange/v1/market/items").pop().unwrap();
+    assert_eq!(request.query("currency").as_deref(), Some("USD"));
+}
+
+#[tokio::test]
+async fn reads_aggregated_prices_of_titles() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let titles = [ITEM_NAMES[0], ITEM_NAMES[2], "Unknown Item"];
+    let prices = client.exchange().get_aggregated_prices(CS2, &titles).await.unwrap();
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices[0].title, ITEM_NAMES[0]);
+    assert_eq!(prices[0].offer_best_price, Some(Money::from_minor(1000, Currency::USD)));
+    assert_eq!(prices[0].offer_count, 3);
+    assert_eq!(prices[0].order_best_price, Some(Money::from_minor(900, Currency::USD)));
+    assert_eq!(prices[0].order_count, 1);
+    assert_eq!(prices[1].order_best_price, None);
+    assert_eq!(prices[1].order_count, 0);
+
+    let body = server.requests_to("/marketplace-api/v1/aggregated-prices")[0].json();
+    assert_eq!(body["filter"]["game"], CS2);
+    assert_eq!(body["filter"]["titles"], json!(titles));
+}
+
+#[tokio::test]
+async fn batches_aggregated_price_titles() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let mut titles = (0..246).map(|i| format!("Unknown Item {}", i)).collect::<Vec<_>>();
+    titles.extend(ITEM_NAMES.iter().map(|name| name.to_string()));
+    let prices = client.exchange().get_aggregated_prices(CS2, &titles).await.unwrap();
+    assert_eq!(prices.len(), 4);
+
+    let batches = server
+        .requests_to("/marketplace-api/v1/aggregated-prices")
+        .iter()
+        .map(|request| request.json()["filter"]["titles"].as_array().unwrap().len())
+        .collect::<Vec<_>>();
+    assert_eq!(batches, [100, 100, 50]);
+    assert!(client.exchange().get_aggregated_prices::<&str>(CS2, &[]).await.unwrap().is_empty());
+    assert_eq!(server.requests_to("/marketplace-api/v1/aggregated-prices").len(), 3);
+}
+
+#[tokio::test]
+async fn reads_filtered_last_sales() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let query = LastSalesQuery::new(CS2, ITEM_NAMES[0])
+        .period(SalesPeriod::Week)
+        .sale_type(SaleType::Offer)
+        .float_range(0.0, 0.1)
+        .limit(5);
+    let sales = client.exchange().get_last_sales(&query).await.unwrap().sales;
+    assert_eq!(
+        sales.iter().map(|sale| sale.price.minor_units()).collect::<Vec<_>>(),
+        [1100, 1200, 1400, 1500]
+    );
+    assert!(sales.iter().all(|sale| sale.sale_type == SaleType::Offer));
+    assert_eq!(sales[0].date, chrono::DateTime::from_timestamp(LAST_SALE_AT - 2 * 3600, 0).unwrap());
+    assert_eq!(sales[0].float_value, Some(0.02));
+    assert_eq!(sales[0].paint_seed, Some(2));
+
+    let request = server.requests_to("/trade-aggregator/v1/last-sales").pop().unwrap();
+    assert_eq!(request.query("period").as_deref(), Some("7D"));
+    assert_eq!(request.query("filters").as_deref(), Some("floatValueFrom[]=0,floatValueTo[]=0.1"));
+
+    let error = client.exchange().get_last_sales(&LastSalesQuery::new(CS2, "")).await.unwrap_err();
+    assert!(matches!(error, DMarketError::InvalidQuery(_)), "{:?}", error);
+}
+
+#[tokio::test]
+async fn computes_sales_stats_over_a_window() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let since = chrono::DateTime::from_timestamp(LAST_SALE_AT - 7 * 3600, 0).unwrap();
+    let stats = client
+        .exchange()
+        .last_sales_stats(LastSalesQuery::new(CS2, ITEM_NAMES[0]).limit(2), since)
+        .await
+        .unwrap();
+    assert_eq!(stats.count, 4);
+    assert_eq!(stats.median, Some(Money::from_minor(1150, Currency::USD)));
+    assert_eq!(stats.mean, Some(Money::from_minor(1150, Currency::USD)));
+
+    // Paging stops at the first sale before the window.
+    let offsets = server
+        .requests_to("/trade-aggregator/v1/last-sales")
+        .iter()
+        .map(|request| request.query_or("offset", 0))
+        .collect::<Vec<u32>>();
+    assert_eq!(offsets, [0, 2, 4]);
+}
+
+#[tokio::test]
+async fn streams_sales_past_short_pages() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    server.state().dmarket_sales_page_cap = Some(3);
+
+    let sales = client
+        .exchange()
+        .stream_last_sales(LastSalesQuery::new(CS2, ITEM_NAMES[0]).limit(5))
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(sales.len(), 12);
+    assert!(sales.windows(2).all(|pair| pair[0].date > pair[1].date));
+
+    // Every page is short of the limit, so only the empty one ends the stream.
+    let offsets = server
+        .requests_to("/trade-aggregator/v1/last-sales")
+        .iter()
+        .map(|request| request.query_or("offset", 0))
+        .collect::<Vec<u32>>();
+    assert_eq!(offsets, [0, 3, 6, 9, 12]);
+}
+
+#[test]
+fn summarizes_sale_prices() {
+    let sale = |cents| LastSale {
+        price: Money::from_minor(cents, Currency::USD),
+        date: chrono::DateTime::from_timestamp(LAST_SALE_AT, 0).unwrap(),
+        sale_type: SaleType::Offer,
+        float_value: None,
+        paint_seed: None,
+        phase: None,
+    };
+
+    let stats = SalesStats::from_sales(&[sale(1301), sale(1000), sale(1000)]).unwrap();
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.median, Some(Money::from_minor(1000, Currency::USD)));
+    assert_eq!(stats.mean, Some(Money::from_minor(1100, Currency::USD)));
+
+    let stats = SalesStats::from_sales(&[sale(100), sale(101)]).unwrap();
+    assert_eq!(stats.median, Some(Money::from_minor(101, Currency::USD)));
+    assert_eq!(SalesStats::from_sales(&[]).unwrap(), SalesStats { count: 0, median: None, mean: None });
+}
+
+#[tokio::test]
+async fn aggregates_buy_orders_by_price() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let book = client.target().get_order_book(&OrderBookQuery::new(CS2, ITEM_NAMES[0])).await.unwrap();
+    let levels = book
+        .levels
+        .iter()
+        .map(|level| (level.price.minor_units(), level.quantity, level.orders))
+        .collect::<Vec<_>>();
+    assert_eq!(levels, [(1300, 1, 1), (1250, 3, 2), (1200, 5, 1), (1100, 3, 1), (1000, 4, 1)]);
+    assert_eq!(book.best_bid().unwrap().price, Money::from_minor(1300, Currency::USD));
+    assert_eq!(book.total_quantity(), 16);
+    assert!(server.requests()[0].target().contains("AK-47%20%7C%20Redline"));
+
+    let empty = client.target().get_order_book(&OrderBookQuery::new(CS2, ITEM_NAMES[1])).await.unwrap();
+    assert_eq!(empty.best_bid(), None);
+}
+
+#[test]
+fn rejects_order_books_in_mixed_currencies() {
+    let order = |price| BuyOrder {
+        title: ITEM_NAMES[0].to_string(),
+        price,
+        quantity: 1,
+        phase: None,
+        float_part_value: None,
+        paint_seed: None,
+    };
+    let orders = [
+        order(Money::from_minor(1000, Currency::USD)),
+        order(Money::from_minor(1000, Currency::EUR)),
+    ];
+    let error = OrderBook::from_orders(ITEM_NAMES[0], orders).unwrap_err();
+    assert_eq!(error, MoneyError::CurrencyMismatch(Currency::USD, Currency::EUR));
+}
+
+#[tokio::test]
+async fn filters_buy_orders_by_item_attributes() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let query = OrderBookQuery::new(CS2, ITEM_NAMES[0])
+        .phase(DopplerPhase::Phase1)
+        .float_part_value("FN-0")
+        .paint_seed(12);
+    let book = client.target().get_order_book(&query).await.unwrap();
+    let levels = book.levels.iter().map(|level| (level.price.minor_units(), level.quantity)).collect::<Vec<_>>();
+    assert_eq!(levels, [(1250, 2), (1100, 3), (1000, 4)]);
+
+    let query = OrderBookQuery::new(CS2, ITEM_NAMES[0]).phase(DopplerPhase::Phase2).paint_seed(661);
+    let book = client.target().get_order_book(&query).await.unwrap();
+    assert_eq!(book.best_bid().map(|level| level.price.minor_units()), Some(1300));
+    assert_eq!(book.levels[1].quantity, 3);
+
+    let error = client.target().get_order_book(&OrderBookQuery::new(CS2, "")).await.unwrap_err();
+    assert!(matches!(error, DMarketError::InvalidQuery(_)), "{:?}", error);
+}
+
+#[tokio::test]
+async fn rejects_requests_signed_with_another_key() {
+    let server = MockServer::start().await;
+    let (public_key, _) = server.dmarket_keys();
+    let client = DMarketClient::builder()
+        .credentials(public_key, hex::encode([9u8; 32]))
+        .base_url(server.url())
+        .build()
+        .unwrap();
+
+    let error = client.account().get_user_profile().await.unwrap_err();
+    match error {
+        DMarketError::ApiError(error) => {
+            assert_eq!(error.kind, ApiErrorKind::Unauthorized);
+            assert_eq!(error.status, StatusCode::UNAUTHORIZED);
+            assert_eq!(error.message, "Invalid signature");
+            assert!(!error.retryable);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn manages_inventory_offers_and_targets() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let inventory = client.inventory().get_inventory(10, 0, Some(CS2)).await.unwrap();
+    assert_eq!(inventory.total, 3);
+    assert_eq!(inventory.objects[1].title, ITEM_NAMES[1]);
+    assert_eq!(inventory.objects[1].extra.tradable, Some(true));
+
+    let sell = client
+        .trading()
+        .create_sell_offer(&SellOfferRequest {
+            items: vec![SellOfferItem {
+                asset_id: "asset-1".to_string(),
+                price: Money::from_minor(1500, Currency::USD),
+            }],
+        })
+        .await
+        .unwrap();
+    assert_eq!(sell.total_succeed, 1);
+    assert_eq!(sell.items[0].offer_id.as_deref(), Some("offer-asset-1"));
+    let body = server.requests_to("/trading/v1/sell-offer").pop().unwrap().json();
+    assert_eq!(body["items"][0]["price"]["amount"], "1500");
+
+    let targets = client.target().get_targets(10, 0).await.unwrap();
+    assert_eq!(targets.total, 2);
+    assert_eq!(targets.objects[0].price, Some(Money::from_minor(900, Currency::USD)));
+
+    let created = client
+        .target()
+        .create_target(&CreateTargetRequest {
+            targets: vec![Target {
+                price: Some(Money::from_minor(800, Currency::USD)),
+                ..targets.objects[0].clone()
+            }],
+        })
+        .await
+        .unwrap();
+    assert_eq!(created.items[0].target_id.as_deref(), Some("new-target-0"));
+
+    let deleted = client.target().delete_targets(vec!["target-0".to_string()]).await.unwrap();
+    assert_eq!(deleted.total_succeed, 1);
+    assert_eq!(server.state().dmarket_targets.len(), 1);
+}
+
+#[tokio::test]
+async fn streams_market_items_a_page_at_a_time() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    let mut items = client.exchange().stream_market_items(MarketItemsQuery::new(CS2).limit(5));
+
+    for _ in 0..5 {
+        items.next().await.unwrap().unwrap();
+    }
+    assert_eq!(server.requests_to("/exchange/v1/market/items").len(), 1);
+    assert_eq!(items.cursor(), Some("5"));
+
+    let rest = items.by_ref().try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(rest.len(), 7);
+    assert!(items.is_finished());
+    let requests = server.requests_to("/exchange/v1/market/items");
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[1].query("cursor").as_deref(), Some("5"));
+    assert_eq!(requests[2].query("cursor").as_deref(), Some("10"));
+}
+
+#[tokio::test]
+async fn caps_streams_without_requesting_extra_items() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let items = client
+        .exchange()
+        .stream_market_items(MarketItemsQuery::new(CS2).limit(5))
+        .max_items(7)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 7);
+    let requests = server.requests_to("/exchange/v1/market/items");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].query("limit").as_deref(), Some("2"));
+}
+
+#[tokio::test]
+async fn resumes_streams_from_saved_cursors() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let mut items = client.exchange().stream_market_items(MarketItemsQuery::new(CS2).limit(4));
+    for _ in 0..4 {
+        items.next().await.unwrap().unwrap();
+    }
+    server.fail("/exchange/v1/market/items", Failure::status(404), 1);
+    assert!(items.next().await.unwrap().is_err());
+    assert!(items.next().await.is_none());
+    let cursor = items.cursor().unwrap().to_string();
+    assert!(!items.is_finished());
+
+    let rest = client
+        .exchange()
+        .stream_market_items(MarketItemsQuery::new(CS2).limit(4).cursor(cursor.clone()))
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(rest.len(), 8);
+    let resumed = client
+        .exchange()
+        .stream_market_items(MarketItemsQuery::new(CS2).limit(4))
+        .resume_from(cursor)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(resumed.iter().map(|item| &item.item_id).collect::<Vec<_>>(), rest.iter().map(|item| &item.item_id).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn streams_inventory_offers_and_targets() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let inventory = client.inventory().stream_inventory(Some(CS2)).page_size(2).try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(inventory.len(), 3);
+    assert_eq!(server.requests_to("/exchange/v1/user/inventory").len(), 2);
+
+    let offers = client.trading().stream_user_sell_offers(Some(CS2), None).page_size(1).try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(offers.len(), 2);
+
+    let targets = client.target().stream_targets().page_size(1).try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(targets.len(), 2);
+    let requests = server.requests_to("/target-predictor/v1/user/targets");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].query("cursor").as_deref(), Some("1"));
+}
+
+#[tokio::test]
+async fn implements_marketplace() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    let offers = client.list_offers(ITEM_NAMES[0], 20).await.unwrap();
+    assert_eq!(offers.len(), 3);
+    assert!(offers.iter().all(|offer| offer.market == Market::DMarket));
+
+    let ask = client.lowest_ask(ITEM_NAMES[0]).await.unwrap().unwrap();
+    assert_eq!(ask.price, Money::from_minor(1000, Currency::USD));
+    let bid = client.highest_bid(ITEM_NAMES[0]).await.unwrap().unwrap();
+    assert_eq!(bid.price, Money::from_minor(900, Currency::USD));
+    assert_eq!(bid.quantity, Some(1));
+    assert_eq!(client.highest_bid(ITEM_NAMES[2]).await.unwrap(), None);
+
+    let crawled = client.crawl(Some(5)).await.unwrap();
+    assert_eq!(crawled.len(), 5);
+    let everything = client.crawl(None).await.unwrap();
+    assert_eq!(everything.len(), 12);
+
+    let inventory = client.my_inventory().await.unwrap();
+    assert_eq!(inventory.len(), 3);
+    let listings = client.my_listings().await.unwrap();
+    assert_eq!(listings.len(), 2);
+}
+
+#[tokio::test]
+async fn retries_rate_limited_and_unavailable_requests() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    server.fail("/account/v1/balance", Failure::rate_limited(0), 1);
+    server.fail("/account/v1/balance", Failure::status(503), 1);
+
+    client.account().get_account_balance().await.unwrap();
+    assert_eq!(server.requests_to("/account/v1/balance").len(), 3);
+
+    // Every attempt is signed again, and the mock checks each signature.
+    server.fail("/account/v1/user", Failure::status(502), 3);
+    let error = client.account().get_user_profile().await.unwrap_err();
+    assert!(matches!(&error, DMarketError::ApiError(error) if error.kind == ApiErrorKind::ServerError && error.retryable));
+    assert_eq!(server.requests_to("/account/v1/user").len(), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_purchases() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    server.fail("/trading/v1/buy/offers", Failure::status(503), 1);
+
+    let request = BuyOfferRequest {
+        offers: vec!["offer-1".to_string()],
+        currency: Currency::USD,
+    };
+    assert!(client.trading().buy_offer(&request).await.is_err());
+    assert_eq!(server.requests_to("/trading/v1/buy/offers").len(), 1);
+
+    let bought = client.trading().buy_offer(&request).await.unwrap();
+    assert_eq!(bought.items[0].offer_id, "offer-1");
+}
+
+#[tokio::test]
+async fn classifies_trading_errors() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    let request = BuyOfferRequest {
+        offers: vec!["offer-1".to_string()],
+        currency: Currency::USD,
+    };
+    let failure = |code: &str, message: &str| {
+        Failure::Response(MockResponse::json(json!({"code": code, "message": message})).with_status(StatusCode::BAD_REQUEST))
+    };
+    server.fail("/trading/v1/buy/offers", failure("InsufficientFunds", "Not enough money on balance"), 1);
+    server.fail("/trading/v1/buy/offers", failure("OfferNotFound", "Offer was already sold"), 1);
+    server.fail(
+        "/trading/v1/buy/offers",
+        Failure::Response(MockResponse::json(json!({"error": {"code": "BadRequest", "message": "Invalid currency"}})).with_status(StatusCode::BAD_REQUEST)),
+        1,
+    );
+
+    for (kind, code) in [
+        (ApiErrorKind::InsufficientFunds, "InsufficientFunds"),
+        (ApiErrorKind::ItemUnavailable, "OfferNotFound"),
+        (ApiErrorKind::InvalidRequest, "BadRequest"),
+    ] {
+        match client.trading().buy_offer(&request).await.unwrap_err() {
+            DMarketError::ApiError(error) => {
+                assert_eq!(error.kind, kind);
+                assert_eq!(error.code.as_deref(), Some(code));
+                assert_eq!(error.market, Market::DMarket);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    server.fail("/account/v1/balance", Failure::rate_limited(60), 1);
+    match client.account().get_account_balance().await.unwrap_err() {
+        DMarketError::ApiError(error) => assert!(error.kind == ApiErrorKind::RateLimited && error.retryable),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn reports_malformed_responses() {
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+    server.fail("/exchange/v1/market/items", Failure::MalformedJson, 1);
+    server.fail("/exchange/v1/market/items", Failure::Body(r#"{"objects": [], "total": 12}"#.to_string()), 1);
+
+    let error = market_items(&client, 10, 0, None).await.unwrap_err();
+    assert!(matches!(error, DMarketError::JsonError(_)), "{:?}", error);
+    let error = market_items(&client, 10, 0, None).await.unwrap_err();
+    assert!(matches!(error, DMarketError::JsonError(_)), "{:?}", error);
+}
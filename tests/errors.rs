@@ -0,0 +1,62 @@
+mod mock;
+
+use backend::{Aggregator, ApiErrorKind, CSFloatClient, Error, ErrorCategory, ItemIdentity, Market, Marketplace, Operation};
+use mock::{Failure, MockServer, ITEM_NAMES};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn categorizes_errors_of_every_market() {
+    let server = MockServer::start().await;
+
+    server.fail("/account/v1/user", Failure::status(401), 1);
+    let error = Error::from(server.dmarket_client().account().get_user_profile().await.unwrap_err());
+    assert_eq!((error.market(), error.category()), (Market::DMarket, ErrorCategory::Auth));
+    assert!(!error.is_retryable());
+
+    server.fail("/api/v1/listings", Failure::rate_limited(60), 1);
+    let error = Error::from(server.csfloat_client().listings().get_listings(None).await.unwrap_err());
+    assert_eq!((error.market(), error.category()), (Market::CSFloat, ErrorCategory::RateLimited));
+    assert!(error.is_retryable());
+    assert_eq!(error.api_error().map(|error| error.kind), Some(ApiErrorKind::RateLimited));
+
+    server.fail("/api/market/goods", Failure::MalformedJson, 1);
+    let error = Error::from(server.buff_client().get_market_listings("csgo", 1, 10).await.unwrap_err());
+    assert_eq!((error.market(), error.category()), (Market::BuffMarket, ErrorCategory::Upstream));
+
+    let error = Error::from(server.buff_client().my_inventory().await.unwrap_err());
+    assert_eq!(error.category(), ErrorCategory::Validation);
+    assert_eq!(error.to_string(), "invalid request: Buff Market does not support inventory");
+}
+
+#[tokio::test]
+async fn reports_unreachable_markets_as_transport_errors() {
+    let server = MockServer::start().await;
+    let url = format!("{}/api/v1", server.url());
+    drop(server);
+    tokio::task::yield_now().await;
+
+    let client = CSFloatClient::builder()
+        .api_key(mock::CSFLOAT_API_KEY)
+        .base_url(url)
+        .build()
+        .unwrap()
+        .with_retry_policy(mock::fast_retries());
+    let error = Error::from(client.listings().get_listings(None).await.unwrap_err());
+    assert_eq!(error.category(), ErrorCategory::Transport);
+    assert!(error.is_retryable());
+}
+
+#[tokio::test]
+async fn aggregator_failures_carry_categories() {
+    let server = MockServer::start().await;
+    server.fail("/api/v1/listings", Failure::status(503), 3);
+    let markets: Vec<Arc<dyn Marketplace>> = vec![Arc::new(server.dmarket_client()), Arc::new(server.csfloat_client())];
+
+    let identity = ItemIdentity::parse(ITEM_NAMES[0]).unwrap();
+    let aggregate = Aggregator::new(markets).aggregate_one(&identity).await;
+    assert_eq!(aggregate.failures.len(), 1);
+    let failure = &aggregate.failures[0];
+    assert_eq!((failure.market, failure.operation), (Market::CSFloat, Operation::ListOffers));
+    assert_eq!(failure.error.category(), ErrorCategory::Upstream);
+    assert!(failure.error.is_retryable());
+}
@@ -0,0 +1,43 @@
+//! Without a `tracing` subscriber, the crate's events and request spans reach `log` loggers.
+//! Kept in its own test binary: installing a subscriber anywhere in a process turns the bridge off.
+
+mod mock;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use mock::{Failure, MockServer};
+use std::sync::Mutex;
+
+static LOGGER: CapturingLogger = CapturingLogger { records: Mutex::new(Vec::new()) };
+
+struct CapturingLogger {
+    records: Mutex<Vec<(Level, String)>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.records.lock().unwrap().push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+#[tokio::test]
+async fn forwards_events_and_request_spans_to_log() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+    let server = MockServer::start().await;
+
+    server.fail("/account/v1/balance", Failure::status(503), 1);
+    server.dmarket_client().account().get_account_balance().await.unwrap();
+
+    let records = LOGGER.records.lock().unwrap().clone();
+    let logged = |level: Level, text: &str| records.iter().any(|(l, message)| *l == level && message.contains(text));
+    assert!(logged(Level::Warn, "Request failed (503"), "{:?}", records);
+    assert!(logged(Level::Debug, "market_request; market=DMarket endpoint=/account/v1/balance method=GET"), "{:?}", records);
+    assert!(logged(Level::Debug, "market_request; status=200"), "{:?}", records);
+    assert!(logged(Level::Debug, "market_request; retries=1"), "{:?}", records);
+}
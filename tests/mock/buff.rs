@@ -0,0 +1,116 @@
+//! Buff endpoints.
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use super::http::{MockRequest, MockResponse};
+use super::{State, BUFF_CSRF_TOKEN, BUFF_SESSION_COOKIE, ITEM_NAMES};
+
+const GOODS_ITEM: &str = include_str!("../fixtures/buff/goods_item.json");
+const BUY_ORDER: &str = include_str!("../fixtures/buff/buy_order.json");
+
+/// Goods id of the `index`-th generated goods entry.
+pub fn goods_id(index: usize) -> i64 {
+    33815 + index as i64
+}
+
+/// `count` goods entries, the first ones named after [`ITEM_NAMES`], priced from ¥86.50 up.
+pub fn goods(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| {
+            let mut item: Value = serde_json::from_str(GOODS_ITEM).expect("valid fixture");
+            let name = match ITEM_NAMES.get(i) {
+                Some(name) => name.to_string(),
+                None => format!("Sticker | Mock Team {} (Holo)", i),
+            };
+            item["id"] = json!(goods_id(i));
+            item["name"] = json!(name);
+            item["market_hash_name"] = json!(name);
+            item["sell_min_price"] = json!(format!("{}.5", 86 + i));
+            item
+        })
+        .collect()
+}
+
+/// Two buy orders for the first goods entry.
+pub fn buy_orders() -> Vec<(i64, Value)> {
+    [("82", 5), ("81.5", 12)]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (price, count))| {
+            let mut order: Value = serde_json::from_str(BUY_ORDER).expect("valid fixture");
+            order["id"] = json!(format!("240425T000000000{}", i));
+            order["price"] = json!(price);
+            order["buy_num"] = json!(count);
+            (goods_id(0), order)
+        })
+        .collect()
+}
+
+pub(super) fn route(state: &mut State, request: &MockRequest) -> MockResponse {
+    if request.header("Cookie") != Some(BUFF_SESSION_COOKIE) {
+        return login_required();
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/api/market/goods") => {
+            if request.header("x-csrftoken") != Some(BUFF_CSRF_TOKEN) {
+                return MockResponse::error(StatusCode::FORBIDDEN, "CSRF Failed");
+            }
+            let search = request.query("search");
+            let goods = state
+                .buff_goods
+                .iter()
+                .filter(|item| match &search {
+                    Some(search) => item["market_hash_name"].as_str().unwrap_or_default().contains(search.as_str()),
+                    None => true,
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let page_num = request.query_or("page_num", 1usize).max(1);
+            let page_size = request.query_or("page_size", 20usize).max(1);
+            let total_page = state
+                .buff_total_page
+                .unwrap_or_else(|| goods.len().div_ceil(page_size) as i32);
+            let items = goods
+                .iter()
+                .skip((page_num - 1) * page_size)
+                .take(page_size)
+                .cloned()
+                .collect::<Vec<_>>();
+            ok(json!({
+                "items": items,
+                "page_num": page_num,
+                "page_size": page_size,
+                "total_count": goods.len(),
+                "total_page": total_page
+            }))
+        }
+        ("GET", "/api/market/goods/buy_order") => {
+            let goods_id = request.query_or("goods_id", 0i64);
+            let items = state
+                .buff_buy_orders
+                .iter()
+                .filter(|(id, _)| *id == goods_id)
+                .map(|(_, order)| order.clone())
+                .collect::<Vec<_>>();
+            ok(json!({
+                "items": items,
+                "page_num": request.query_or("page_num", 1),
+                "page_size": 10,
+                "total_count": items.len(),
+                "total_page": 1
+            }))
+        }
+        _ => MockResponse::not_found(&request.path),
+    }
+}
+
+fn ok(data: Value) -> MockResponse {
+    MockResponse::json(json!({"code": "OK", "data": data, "msg": null}))
+}
+
+/// Buff reports most errors with a 200 status and a non-`OK` code.
+fn login_required() -> MockResponse {
+    MockResponse::json(json!({"code": "Login Required", "data": null, "msg": "Please log in"}))
+}
@@ -0,0 +1,72 @@
+//! CSFloat endpoints.
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use super::http::{MockRequest, MockResponse};
+use super::{State, CSFLOAT_API_KEY, ITEM_NAMES};
+
+const LISTING: &str = include_str!("../fixtures/csfloat/listing.json");
+
+/// `count` listings cycling through [`ITEM_NAMES`], priced from $12.50 up.
+pub fn listings(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| {
+            let mut listing: Value = serde_json::from_str(LISTING).expect("valid fixture");
+            listing["id"] = json!(format!("7000000000000000{:02}", i));
+            listing["price"] = json!(1250 + i as i64 * 100);
+            listing["item"]["asset_id"] = json!(format!("350000000{:02}", i));
+            listing["item"]["market_hash_name"] = json!(ITEM_NAMES[i % ITEM_NAMES.len()]);
+            listing["item"]["def_index"] = json!(7 + (i % ITEM_NAMES.len()) as i64);
+            listing
+        })
+        .collect()
+}
+
+pub(super) fn route(state: &mut State, request: &MockRequest) -> MockResponse {
+    if request.header("Authorization") != Some(CSFLOAT_API_KEY) {
+        return MockResponse::error(StatusCode::UNAUTHORIZED, "Invalid API key");
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/api/v1/listings") => {
+            let name = request.query("market_hash_name");
+            let max_price = request.query("max_price").and_then(|price| price.parse::<i64>().ok());
+            let limit = request.query_or("limit", 50usize);
+            let data = state
+                .csfloat_listings
+                .iter()
+                .filter(|listing| match &name {
+                    Some(name) => listing["item"]["market_hash_name"] == name.as_str(),
+                    None => true,
+                })
+                .filter(|listing| match max_price {
+                    Some(max_price) => listing["price"].as_i64().unwrap_or_default() <= max_price,
+                    None => true,
+                })
+                .take(limit)
+                .cloned()
+                .collect::<Vec<_>>();
+            MockResponse::json(json!({"data": data, "cursor": null}))
+        }
+        ("GET", path) => {
+            let id = path.trim_start_matches("/api/v1/listings/");
+            match state.csfloat_listings.iter().find(|listing| listing["id"] == id) {
+                Some(listing) => MockResponse::json(listing.clone()),
+                None => MockResponse::error(StatusCode::NOT_FOUND, "listing not found"),
+            }
+        }
+        ("POST", "/api/v1/listings") => {
+            let body = request.json();
+            let mut listing = listings(1).remove(0);
+            listing["id"] = json!(format!("7100000000{}", body["asset_id"]));
+            listing["type"] = body["type"].clone();
+            listing["price"] = body["price"].clone();
+            listing["is_seller"] = json!(true);
+            listing["item"]["asset_id"] = json!(body["asset_id"].to_string());
+            state.csfloat_listings.push(listing.clone());
+            MockResponse::json(listing)
+        }
+        _ => MockResponse::not_found(&request.path),
+    }
+}
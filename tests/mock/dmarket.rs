@@ -0,0 +1,362 @@
+//! DMarket endpoints.
+
+use ed25519_dalek::{Signature, Verifier};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use super::http::{MockRequest, MockResponse};
+use super::{State, ITEM_NAMES, LAST_SALE_AT};
+
+const USER: &str = include_str!("../fixtures/dmarket/user.json");
+const BALANCE: &str = include_str!("../fixtures/dmarket/balance.json");
+const MARKET_ITEM: &str = include_str!("../fixtures/dmarket/market_item.json");
+const INVENTORY_ITEM: &str = include_str!("../fixtures/dmarket/inventory_item.json");
+const GAMES: &str = include_str!("../fixtures/dmarket/games.json");
+
+fn fixture(text: &str) -> Value {
+    serde_json::from_str(text).expect("valid fixture")
+}
+
+/// `count` raw market items cycling through [`ITEM_NAMES`], priced from $10.00 up.
+pub fn market_items(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| {
+            let mut item = fixture(MARKET_ITEM);
+            let cents = (1000 + i * 125).to_string();
+            item["itemId"] = json!(format!("c8f0b6d2-6a3e-4b8e-9e1f-{:012}", i));
+            item["assetId"] = json!(format!("3f1c2b4a-0000-4000-8000-{:012}", i));
+            item["title"] = json!(ITEM_NAMES[i % ITEM_NAMES.len()]);
+            item["classId"] = json!(format!("a8db:class{}", i % ITEM_NAMES.len()));
+            item["price"] = json!({"USD": cents, "DMC": cents});
+            item
+        })
+        .collect()
+}
+
+pub fn inventory_items(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| {
+            let mut item = fixture(INVENTORY_ITEM);
+            item["itemId"] = json!(format!("7d2e9f10-0000-4000-8000-{:012}", i));
+            item["name"] = json!(ITEM_NAMES[i % ITEM_NAMES.len()]);
+            item
+        })
+        .collect()
+}
+
+pub fn targets(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| {
+            json!({
+                "id": format!("target-{}", i),
+                "targetType": "buy",
+                "gameId": "a8db",
+                "classId": format!("a8db:class{}", i),
+                "title": ITEM_NAMES[i % ITEM_NAMES.len()],
+                "price": {"amount": (900 + i * 50).to_string(), "currency": "USD"},
+                "status": "active",
+                "createdAt": 1714000000,
+                "updatedAt": 1714000000
+            })
+        })
+        .collect()
+}
+
+/// `count` raw sales alternating between the first two [`ITEM_NAMES`], one an hour back from
+/// [`LAST_SALE_AT`], priced from $10.00 up in steps of $0.50. Every third is a filled target.
+pub fn sales(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| {
+            let cents = 1000 + i * 50;
+            json!({
+                "title": ITEM_NAMES[i % 2],
+                "price": format!("{}.{:02}", cents / 100, cents % 100),
+                "date": (LAST_SALE_AT - i as i64 * 3600).to_string(),
+                "txOperationType": if i % 3 == 0 { "Target" } else { "Offer" },
+                "offerAttributes": {"floatValue": i as f64 / 100.0, "paintSeed": i}
+            })
+        })
+        .collect()
+}
+
+/// Buy orders of the first item name at four price levels, some restricting attributes.
+pub fn buy_orders() -> Vec<Value> {
+    let order = |cents: i64, amount: u32, attributes: Value| {
+        json!({"title": ITEM_NAMES[0], "price": cents.to_string(), "amount": amount, "attributes": attributes})
+    };
+    vec![
+        order(1250, 2, json!({})),
+        order(1100, 3, json!({"exterior": "field-tested"})),
+        order(1300, 1, json!({"paintSeed": "661"})),
+        order(1250, 1, json!({"phase": "phase-2"})),
+        order(1200, 5, json!({"floatPartValue": "FT-0"})),
+        order(1000, 4, json!({})),
+    ]
+}
+
+/// Whether a raw sale matches the `floatValueFrom[]`/`floatValueTo[]` parts of `filters`.
+fn matches_float(sale: &Value, filters: &str) -> bool {
+    let float = sale["offerAttributes"]["floatValue"].as_f64().unwrap_or_default();
+    filters.split(',').all(|filter| match filter.split_once("[]=") {
+        Some(("floatValueFrom", min)) => min.parse().is_ok_and(|min: f64| float >= min),
+        Some(("floatValueTo", max)) => max.parse().is_ok_and(|max: f64| float <= max),
+        _ => true,
+    })
+}
+
+/// Converts a raw market item into the shape of `MarketItemsResponse`, as the offer search
+/// endpoints return it.
+fn as_offer(item: &Value) -> Value {
+    json!({
+        "itemId": item["itemId"],
+        "type": item["type"],
+        "title": item["title"],
+        "description": item["description"],
+        "slug": item["slug"],
+        "status": item["status"],
+        "ownersCount": item["ownersCount"],
+        "image": item["image"],
+        "classId": item["classId"],
+        "game": item["game"],
+        "price": {"amount": item["price"]["USD"], "currency": "USD"},
+        "suggestedPrice": {"amount": item["price"]["DMC"], "currency": "DMC"},
+        "discount": item["discount"],
+        "extra": {
+            "exterior": item["exterior"],
+            "category": item["category"],
+            "categoryPath": item["categoryPath"],
+            "tradable": item["tradable"],
+            "floatValue": item["floatValue"],
+            "gameId": item["gameId"]
+        },
+        "attributes": [],
+        "locked": item["locked"],
+        "createdAt": item["createdAt"],
+        "updatedAt": item["updatedAt"],
+        "inMarket": item["inMarket"],
+        "gameId": item["gameId"],
+        "withdrawable": item["withdrawable"],
+        "tradeLock": item["tradeLock"],
+        "offerType": item["offerType"],
+        "assetId": item["assetId"]
+    })
+}
+
+/// Cents of the `USD` price of each raw market item or target titled `title`.
+fn prices_of(items: &[Value], title: &str, price: fn(&Value) -> &Value) -> Vec<i64> {
+    items
+        .iter()
+        .filter(|item| item["title"] == title)
+        .filter_map(|item| price(item).as_str()?.parse().ok())
+        .collect()
+}
+
+/// The aggregated prices entry of `title`, or `None` when it is neither listed nor ordered.
+/// Like DMarket, a zero price stands in for a missing best offer or order.
+fn aggregated_price(state: &State, title: &str) -> Option<Value> {
+    let offers = prices_of(&state.dmarket_items, title, |item| &item["price"]["USD"]);
+    let orders = prices_of(&state.dmarket_targets, title, |target| &target["price"]["amount"]);
+    if offers.is_empty() && orders.is_empty() {
+        return None;
+    }
+    Some(json!({
+        "title": title,
+        "offerBestPrice": {"amount": offers.iter().min().unwrap_or(&0).to_string(), "currency": "USD"},
+        "offerCount": offers.len(),
+        "orderBestPrice": {"amount": orders.iter().max().unwrap_or(&0).to_string(), "currency": "USD"},
+        "orderCount": orders.len()
+    }))
+}
+
+/// The `limit` items of `items` at `cursor`, or at `offset` without one, and the cursor of the
+/// page after them. Cursors are offsets; the last page gets an empty cursor, like DMarket's.
+fn page<'a>(request: &MockRequest, items: &'a [Value]) -> (&'a [Value], String) {
+    let offset = request
+        .query("cursor")
+        .and_then(|cursor| cursor.parse().ok())
+        .unwrap_or_else(|| request.query_or("offset", 0usize))
+        .min(items.len());
+    let limit = request.query_or("limit", 100usize);
+    let end = (offset + limit).min(items.len());
+    let cursor = if end < items.len() { end.to_string() } else { String::new() };
+    (&items[offset..end], cursor)
+}
+
+pub(super) fn route(state: &mut State, request: &MockRequest) -> MockResponse {
+    if let Err(message) = verify_signature(state, request) {
+        return MockResponse::error(StatusCode::UNAUTHORIZED, &message);
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/account/v1/user") => MockResponse::json(fixture(USER)),
+        ("GET", "/account/v1/balance") => MockResponse::json(fixture(BALANCE)),
+        ("GET", "/exchange/v1/games") => MockResponse::json(fixture(GAMES)),
+        ("GET", "/exchange/v1/market/items") => {
+            let title = request.query("title");
+            let items = state
+                .dmarket_items
+                .iter()
+                .filter(|item| match &title {
+                    Some(title) => item["title"].as_str().unwrap_or_default().contains(title.as_str()),
+                    None => true,
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let (objects, cursor) = page(request, &items);
+            MockResponse::json(json!({
+                "cursor": cursor,
+                "objects": objects,
+                "total": items.len().to_string()
+            }))
+        }
+        ("POST", "/marketplace-api/v1/aggregated-prices") => {
+            let body = request.json();
+            let prices = body["filter"]["titles"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|title| aggregated_price(state, title.as_str()?))
+                .collect::<Vec<_>>();
+            let offset = body["cursor"].as_str().and_then(|cursor| cursor.parse().ok()).unwrap_or(0usize).min(prices.len());
+            let limit = body["limit"].as_str().and_then(|limit| limit.parse().ok()).unwrap_or(100usize);
+            let end = (offset + limit).min(prices.len());
+            let cursor = if end < prices.len() { end.to_string() } else { String::new() };
+            MockResponse::json(json!({"aggregatedPrices": &prices[offset..end], "nextCursor": cursor}))
+        }
+        ("GET", "/trade-aggregator/v1/last-sales") => {
+            let title = request.query("title").unwrap_or_default();
+            let sale_type = request.query("txOperationType[]");
+            let filters = request.query("filters").unwrap_or_default();
+            let sales = state
+                .dmarket_sales
+                .iter()
+                .filter(|sale| sale["title"] == title.as_str())
+                .filter(|sale| sale_type.as_ref().is_none_or(|sale_type| sale["txOperationType"] == sale_type.as_str()))
+                .filter(|sale| matches_float(sale, &filters))
+                .cloned()
+                .collect::<Vec<_>>();
+            let (sales, _) = page(request, &sales);
+            let cap = state.dmarket_sales_page_cap.unwrap_or(sales.len()).min(sales.len());
+            MockResponse::json(json!({"sales": &sales[..cap]}))
+        }
+        ("GET", path) if path.starts_with("/marketplace-api/v1/targets-by-title/a8db/") => {
+            let title = urlencoding::decode(path.trim_start_matches("/marketplace-api/v1/targets-by-title/a8db/"))
+                .unwrap_or_default()
+                .into_owned();
+            let orders = state
+                .dmarket_buy_orders
+                .iter()
+                .filter(|order| order["title"] == title.as_str())
+                .cloned()
+                .collect::<Vec<_>>();
+            MockResponse::json(json!({"orders": orders}))
+        }
+        ("GET", "/exchange/v1/user/inventory") => {
+            let (items, cursor) = page(request, &state.dmarket_inventory);
+            MockResponse::json(json!({
+                "cursor": cursor,
+                "items": items,
+                "total": state.dmarket_inventory.len()
+            }))
+        }
+        ("GET", "/offers-search/v1/user/sell-offers") => {
+            let offers = state.dmarket_items.iter().take(2).map(as_offer).collect::<Vec<_>>();
+            let (objects, cursor) = page(request, &offers);
+            MockResponse::json(json!({"cursor": cursor, "objects": objects, "total": offers.len().to_string()}))
+        }
+        ("GET", path) if path.starts_with("/offers-search/v1/aggregated-class/") => {
+            let class_id = path
+                .trim_start_matches("/offers-search/v1/aggregated-class/")
+                .trim_end_matches("/sell-offers");
+            let offers = state
+                .dmarket_items
+                .iter()
+                .filter(|item| item["classId"] == class_id)
+                .map(as_offer)
+                .collect::<Vec<_>>();
+            MockResponse::json(json!({"objects": page(request, &offers).0, "total": offers.len().to_string()}))
+        }
+        ("GET", "/target-predictor/v1/user/targets") => {
+            let (objects, cursor) = page(request, &state.dmarket_targets);
+            MockResponse::json(json!({
+                "cursor": cursor,
+                "objects": objects,
+                "total": state.dmarket_targets.len()
+            }))
+        }
+        ("POST", "/target-predictor/v1/target") => {
+            let targets = request.json()["targets"].as_array().cloned().unwrap_or_default();
+            let items = targets
+                .iter()
+                .enumerate()
+                .map(|(i, _)| json!({"Status": "Created", "TargetID": format!("new-target-{}", i)}))
+                .collect::<Vec<_>>();
+            summary(items)
+        }
+        ("DELETE", "/target-predictor/v1/target") => {
+            let ids = request.json()["targets"].as_array().cloned().unwrap_or_default();
+            state
+                .dmarket_targets
+                .retain(|target| !ids.contains(&target["id"]));
+            let items = ids
+                .iter()
+                .map(|id| json!({"Status": "Deleted", "TargetID": id}))
+                .collect::<Vec<_>>();
+            summary(items)
+        }
+        ("POST", "/trading/v1/sell-offer") => {
+            let items = request.json()["items"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .iter()
+                .map(|item| json!({"Status": "Created", "AssetID": item["assetId"], "OfferID": format!("offer-{}", item["assetId"].as_str().unwrap_or_default())}))
+                .collect::<Vec<_>>();
+            summary(items)
+        }
+        ("POST", "/trading/v1/buy/offers") => {
+            let items = request.json()["offers"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .iter()
+                .map(|offer| json!({"Status": "Bought", "OfferID": offer}))
+                .collect::<Vec<_>>();
+            summary(items)
+        }
+        _ => MockResponse::not_found(&request.path),
+    }
+}
+
+/// DMarket's batch operation summary.
+fn summary(items: Vec<Value>) -> MockResponse {
+    MockResponse::json(json!({
+        "TotalSucceed": items.len(),
+        "TotalFailed": 0,
+        "HasErrors": false,
+        "Items": items
+    }))
+}
+
+/// Checks the `X-Request-Sign` header against the request, as DMarket does.
+fn verify_signature(state: &State, request: &MockRequest) -> Result<(), String> {
+    let expected_key = state.dmarket_signing_key.verifying_key();
+    let api_key = request.header("X-Api-Key").ok_or("Missing X-Api-Key")?;
+    if api_key != hex::encode(expected_key.to_bytes()) {
+        return Err("Unknown API key".to_string());
+    }
+    let timestamp = request.header("X-Sign-Date").ok_or("Missing X-Sign-Date")?;
+    let signature = request
+        .header("X-Request-Sign")
+        .and_then(|value| value.strip_prefix("dmar ed25519 "))
+        .ok_or("Missing or malformed X-Request-Sign")?;
+    let signature = hex::decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or("Malformed signature")?;
+
+    let message = format!("{}{}{}{}", request.method, request.target(), request.body, timestamp);
+    expected_key
+        .verify(message.as_bytes(), &signature)
+        .map_err(|_| "Invalid signature".to_string())
+}
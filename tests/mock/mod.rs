@@ -0,0 +1,250 @@
+//! In-process mock of the DMarket, CSFloat and Buff APIs.
+//!
+//! A [`MockServer`] listens on a local port and serves the endpoints the clients call, built from
+//! the payloads in `tests/fixtures`. The three markets share one server since their paths do not
+//! overlap. DMarket requests must carry a valid Ed25519 signature, CSFloat requests the API key
+//! and Buff requests the session cookie and CSRF token. Failures can be scripted per path.
+
+#![allow(dead_code)]
+
+mod buff;
+mod csfloat;
+mod dmarket;
+mod http;
+
+use backend::{BuffMarketClient, CSFloatClient, DMarketClient, RateLimiter, RetryPolicy};
+use ed25519_dalek::SigningKey;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+pub use http::{MockRequest, MockResponse};
+
+pub const CSFLOAT_API_KEY: &str = "csfloat-test-api-key";
+pub const BUFF_SESSION_COOKIE: &str = "session=buff-test-session; csrf_token=buff-test-csrf";
+pub const BUFF_CSRF_TOKEN: &str = "buff-test-csrf";
+
+/// Time of the latest generated DMarket sale.
+pub const LAST_SALE_AT: i64 = 1714000000;
+
+/// Item names the generated listings cycle through.
+pub const ITEM_NAMES: [&str; 4] = [
+    "AK-47 | Redline (Field-Tested)",
+    "AWP | Asiimov (Field-Tested)",
+    "M4A1-S | Printstream (Minimal Wear)",
+    "Desert Eagle | Blaze (Factory New)",
+];
+
+/// A failure served instead of the normal response.
+#[derive(Debug, Clone)]
+pub enum Failure {
+    /// Responds with `status` and a JSON error body, plus `Retry-After` when given.
+    Status { status: StatusCode, retry_after: Option<u64> },
+    /// Responds 200 with a body that is not valid JSON.
+    MalformedJson,
+    /// Responds 200 with `body` verbatim.
+    Body(String),
+    /// Responds with `response` as is.
+    Response(MockResponse),
+}
+
+impl Failure {
+    pub fn status(status: u16) -> Self {
+        Failure::Status {
+            status: StatusCode::from_u16(status).expect("valid status"),
+            retry_after: None,
+        }
+    }
+
+    pub fn rate_limited(retry_after: u64) -> Self {
+        Failure::Status {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            retry_after: Some(retry_after),
+        }
+    }
+
+    fn into_response(self) -> MockResponse {
+        match self {
+            Failure::Status { status, retry_after } => {
+                let mut response = MockResponse::error(status, "Scripted failure");
+                if let Some(seconds) = retry_after {
+                    response = response.with_header("Retry-After", &seconds.to_string());
+                }
+                response
+            }
+            Failure::MalformedJson => MockResponse::raw(StatusCode::OK, "{\"objects\": [".to_string()),
+            Failure::Body(body) => MockResponse::raw(StatusCode::OK, body),
+            Failure::Response(response) => response,
+        }
+    }
+}
+
+/// Data served by the mock and what it has received.
+pub struct State {
+    pub dmarket_signing_key: SigningKey,
+    /// Raw DMarket market items, as returned by `/exchange/v1/market/items`.
+    pub dmarket_items: Vec<Value>,
+    /// Raw DMarket inventory items.
+    pub dmarket_inventory: Vec<Value>,
+    /// DMarket targets of the user.
+    pub dmarket_targets: Vec<Value>,
+    /// Raw DMarket sales, newest first.
+    pub dmarket_sales: Vec<Value>,
+    /// Serves at most this many DMarket sales per page, whatever the requested limit.
+    pub dmarket_sales_page_cap: Option<usize>,
+    /// Raw buy orders of all DMarket users.
+    pub dmarket_buy_orders: Vec<Value>,
+    /// CSFloat listings.
+    pub csfloat_listings: Vec<Value>,
+    /// Buff market goods, paged by `page_num` and `page_size`.
+    pub buff_goods: Vec<Value>,
+    /// Buff buy orders, keyed by goods id.
+    pub buff_buy_orders: Vec<(i64, Value)>,
+    /// Overrides Buff's reported `total_page`, e.g. to report fewer pages than there are.
+    pub buff_total_page: Option<i32>,
+    failures: VecDeque<(String, Failure)>,
+    requests: Vec<MockRequest>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            dmarket_signing_key: SigningKey::from_bytes(&[7u8; 32]),
+            dmarket_items: dmarket::market_items(12),
+            dmarket_inventory: dmarket::inventory_items(3),
+            dmarket_targets: dmarket::targets(2),
+            dmarket_sales: dmarket::sales(24),
+            dmarket_sales_page_cap: None,
+            dmarket_buy_orders: dmarket::buy_orders(),
+            csfloat_listings: csfloat::listings(8),
+            buff_goods: buff::goods(10),
+            buff_buy_orders: buff::buy_orders(),
+            buff_total_page: None,
+            failures: VecDeque::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+/// A running mock server. It stops when dropped.
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock server");
+        let url = format!("http://{}", listener.local_addr().expect("local address"));
+        let state = Arc::new(Mutex::new(State::new()));
+        let task = tokio::spawn(http::serve(listener, Arc::clone(&state), route));
+        Self { url, state, task }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Locks the served data, e.g. to replace fixtures before a test.
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Serves `failure` for the next `times` requests whose path and query start with `path`.
+    pub fn fail(&self, path: &str, failure: Failure, times: usize) {
+        let mut state = self.state();
+        for _ in 0..times {
+            state.failures.push_back((path.to_string(), failure.clone()));
+        }
+    }
+
+    /// Every request received so far.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Requests received so far whose path starts with `path`.
+    pub fn requests_to(&self, path: &str) -> Vec<MockRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.path.starts_with(path))
+            .collect()
+    }
+
+    /// The hex-encoded DMarket key pair the server accepts signatures from.
+    pub fn dmarket_keys(&self) -> (String, String) {
+        let state = self.state();
+        (
+            hex::encode(state.dmarket_signing_key.verifying_key().to_bytes()),
+            hex::encode(state.dmarket_signing_key.to_bytes()),
+        )
+    }
+
+    pub fn dmarket_client(&self) -> DMarketClient {
+        let (public_key, private_key) = self.dmarket_keys();
+        DMarketClient::builder()
+            .credentials(public_key, private_key)
+            .base_url(&self.url)
+            .build()
+            .expect("DMarket client")
+            .with_retry_policy(fast_retries())
+            .with_rate_limiter(RateLimiter::unlimited())
+    }
+
+    pub fn csfloat_client(&self) -> CSFloatClient {
+        CSFloatClient::builder()
+            .api_key(CSFLOAT_API_KEY)
+            .base_url(format!("{}/api/v1", self.url))
+            .build()
+            .expect("CSFloat client")
+            .with_retry_policy(fast_retries())
+            .with_rate_limiter(RateLimiter::unlimited())
+    }
+
+    pub fn buff_client(&self) -> BuffMarketClient {
+        BuffMarketClient::builder()
+            .credentials(BUFF_SESSION_COOKIE, BUFF_CSRF_TOKEN)
+            .base_url(&self.url)
+            .build()
+            .expect("Buff client")
+            .with_retry_policy(fast_retries())
+            .with_rate_limiter(RateLimiter::unlimited())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The default retry policy without its delays, so tests stay fast.
+pub fn fast_retries() -> RetryPolicy {
+    RetryPolicy::default()
+        .with_backoff(Duration::from_millis(1), Duration::from_secs(5))
+        .with_jitter(false)
+}
+
+fn route(state: &mut State, request: MockRequest) -> MockResponse {
+    state.requests.push(request.clone());
+    if let Some(index) = state
+        .failures
+        .iter()
+        .position(|(path, _)| request.target().starts_with(path.as_str()))
+    {
+        let (_, failure) = state.failures.remove(index).expect("failure at index");
+        return failure.into_response();
+    }
+
+    if request.path.starts_with("/api/v1/") {
+        csfloat::route(state, &request)
+    } else if request.path.starts_with("/api/market/") {
+        buff::route(state, &request)
+    } else {
+        dmarket::route(state, &request)
+    }
+}
@@ -0,0 +1,125 @@
+mod mock;
+
+use backend::http::redact;
+use backend::{BuffMarketClient, CSFloatClient, DMarketClient, Secret};
+use log::{LevelFilter, Log, Metadata, Record};
+use mock::{Failure, MockServer, BUFF_CSRF_TOKEN, BUFF_SESSION_COOKIE, CSFLOAT_API_KEY};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::sync::{Mutex, OnceLock};
+
+/// Collects every log record of this test binary.
+struct CapturingLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(format!("{} {}", record.target(), record.args()));
+    }
+
+    fn flush(&self) {}
+}
+
+fn logger() -> &'static CapturingLogger {
+    static LOGGER: OnceLock<&'static CapturingLogger> = OnceLock::new();
+    LOGGER.get_or_init(|| {
+        let logger = Box::leak(Box::new(CapturingLogger { lines: Mutex::new(Vec::new()) }));
+        log::set_logger(logger).expect("no other logger");
+        log::set_max_level(LevelFilter::Trace);
+        logger
+    })
+}
+
+fn assert_hidden(output: &str, secrets: &[&str]) {
+    for secret in secrets {
+        assert!(!output.contains(secret), "{:?} leaked into {}", secret, output);
+    }
+}
+
+#[test]
+fn secrets_print_redacted() {
+    let secret = Secret::new("hunter2");
+    assert_eq!(secret.expose(), "hunter2");
+    assert_eq!(format!("{}", secret), "[REDACTED]");
+    assert_eq!(format!("{:?}", Some(&secret)), "Some([REDACTED])");
+}
+
+#[test]
+fn redacts_sensitive_headers_and_body_fields() {
+    let mut headers = HeaderMap::new();
+    headers.insert("X-Api-Key", HeaderValue::from_static("public-key"));
+    headers.insert("X-Request-Sign", HeaderValue::from_static("dmar ed25519 abcdef"));
+    headers.insert("Cookie", HeaderValue::from_static("session=abc"));
+    headers.insert("X-Sign-Date", HeaderValue::from_static("1700000000"));
+
+    let formatted = format!("{:?}", redact::headers(&headers));
+    assert_hidden(&formatted, &["public-key", "abcdef", "session=abc"]);
+    assert!(formatted.contains("\"x-sign-date\": \"1700000000\""));
+    assert!(formatted.contains("\"x-api-key\": \"[REDACTED]\""));
+
+    let body = r#"{"username":"trader","steamAccount":{"apiKey":"steam-key"},"email":"a@b.c"}"#;
+    let formatted = redact::body(body).to_string();
+    assert_hidden(&formatted, &["steam-key", "a@b.c"]);
+    assert!(formatted.contains("trader"));
+    assert_eq!(redact::body("not json").to_string(), "not json");
+}
+
+#[tokio::test]
+async fn client_debug_output_hides_credentials() {
+    let server = MockServer::start().await;
+    let (public_key, private_key) = server.dmarket_keys();
+
+    let dmarket = format!("{:?}", server.dmarket_client());
+    let builder = format!("{:?}", DMarketClient::builder().credentials(public_key.as_str(), private_key.as_str()));
+    assert_hidden(&dmarket, &[&public_key, &private_key]);
+    assert_hidden(&builder, &[&public_key, &private_key]);
+
+    let csfloat = format!("{:?}", server.csfloat_client());
+    let builder = format!("{:?}", CSFloatClient::builder().api_key(CSFLOAT_API_KEY));
+    assert_hidden(&csfloat, &[CSFLOAT_API_KEY]);
+    assert_hidden(&builder, &[CSFLOAT_API_KEY]);
+
+    let buff = format!("{:?}", server.buff_client());
+    let builder = format!("{:?}", BuffMarketClient::builder().credentials(BUFF_SESSION_COOKIE, BUFF_CSRF_TOKEN));
+    assert_hidden(&buff, &[BUFF_SESSION_COOKIE, BUFF_CSRF_TOKEN]);
+    assert_hidden(&builder, &[BUFF_SESSION_COOKIE, BUFF_CSRF_TOKEN]);
+}
+
+#[tokio::test]
+async fn debug_logs_never_contain_credentials() {
+    let logger = logger();
+    let server = MockServer::start().await;
+    let (public_key, private_key) = server.dmarket_keys();
+
+    let dmarket = server.dmarket_client();
+    dmarket.account().get_user_profile().await.unwrap();
+    server.fail("/account/v1/balance", Failure::status(401), 1);
+    dmarket.account().get_account_balance().await.unwrap_err();
+
+    let csfloat = server.csfloat_client();
+    csfloat.listings().get_listings(None).await.unwrap();
+    server.fail("/api/v1/listings", Failure::status(500), 1);
+    csfloat.listings().get_listings(None).await.unwrap();
+
+    let buff = server.buff_client();
+    buff.get_market_listings("csgo", 1, 20).await.unwrap();
+
+    let output = logger.lines.lock().unwrap().join("\n");
+    assert!(output.contains("request to URL"), "nothing was logged: {}", output);
+    assert_hidden(
+        &output,
+        &[
+            &public_key,
+            &private_key,
+            "dmar ed25519",
+            "mock-trader@example.com",
+            CSFLOAT_API_KEY,
+            BUFF_SESSION_COOKIE,
+            BUFF_CSRF_TOKEN,
+        ],
+    );
+}
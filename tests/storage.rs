@@ -0,0 +1,100 @@
+mod mock;
+
+use backend::{Aggregator, Currency, ItemIdentity, Market, Marketplace, Money, Storage};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mock::{MockServer, ITEM_NAMES};
+use std::sync::Arc;
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(1_714_000_000 + seconds, 0).unwrap()
+}
+
+fn usd(cents: i64) -> Money {
+    Money::from_minor(cents, Currency::USD)
+}
+
+#[tokio::test]
+async fn queries_listing_history_by_item_market_and_time() {
+    let server = MockServer::start().await;
+    let storage = Storage::open_in_memory().unwrap();
+    let dmarket = server.dmarket_client().list_offers(ITEM_NAMES[0], 50).await.unwrap();
+    let csfloat = server.csfloat_client().list_offers(ITEM_NAMES[0], 50).await.unwrap();
+    let other = server.dmarket_client().list_offers(ITEM_NAMES[1], 50).await.unwrap();
+
+    assert_eq!(storage.record_listings(at(0), &dmarket).unwrap(), 3);
+    assert_eq!(storage.record_listings(at(60), &[dmarket.clone(), csfloat, other].concat()).unwrap(), 8);
+
+    let history = storage.listing_history(ITEM_NAMES[0], Market::DMarket, at(0), at(60)).unwrap();
+    assert_eq!(history.len(), 6);
+    assert_eq!(history[0].captured_at, at(0));
+    assert_eq!(history[0].item, ITEM_NAMES[0]);
+    assert_eq!(history[0].external_id, dmarket[0].external_id);
+    assert_eq!(history.iter().map(|snapshot| snapshot.price).collect::<Vec<_>>()[..3], [usd(1000), usd(1500), usd(2000)]);
+
+    // Both ends of the range are inclusive.
+    assert_eq!(storage.listing_history(ITEM_NAMES[0], Market::DMarket, at(1), at(60)).unwrap().len(), 3);
+    assert_eq!(storage.listing_history(ITEM_NAMES[0], Market::DMarket, at(0), at(59)).unwrap().len(), 3);
+    assert_eq!(storage.listing_history(ITEM_NAMES[0], Market::CSFloat, at(0), at(60)).unwrap().len(), 2);
+    assert_eq!(storage.listing_history(ITEM_NAMES[1], Market::DMarket, at(0), at(60)).unwrap().len(), 3);
+    assert!(storage.listing_history(ITEM_NAMES[0], Market::BuffMarket, at(0), at(60)).unwrap().is_empty());
+
+    // Names are matched in canonical form.
+    let spaced = storage.listing_history("AK-47 |  Redline  (Field-Tested)", Market::DMarket, at(0), at(60)).unwrap();
+    assert_eq!(spaced.len(), 6);
+}
+
+#[tokio::test]
+async fn queries_price_history_per_market_and_overall() {
+    let server = MockServer::start().await;
+    let storage = Storage::open_in_memory().unwrap();
+    let markets: Vec<Arc<dyn Marketplace>> = vec![Arc::new(server.dmarket_client()), Arc::new(server.csfloat_client())];
+    let aggregator = Aggregator::new(markets);
+    let items = [ItemIdentity::parse(ITEM_NAMES[0]).unwrap(), ItemIdentity::parse(ITEM_NAMES[1]).unwrap()];
+    assert_eq!(storage.last_captured_at().unwrap(), None);
+
+    let aggregates = aggregator.aggregate(&items).await;
+    // Two markets and the overall view for each item.
+    assert_eq!(storage.record_aggregates(at(0), &aggregates).unwrap(), 6);
+    assert_eq!(storage.record_aggregates(at(300), &aggregates).unwrap(), 6);
+    assert_eq!(storage.last_captured_at().unwrap(), Some(at(300)));
+
+    let dmarket = storage.price_history(ITEM_NAMES[0], Some(Market::DMarket), at(0), at(300)).unwrap();
+    assert_eq!(dmarket.len(), 2);
+    assert_eq!(dmarket[1].captured_at, at(300));
+    assert_eq!(dmarket[0].market, Some(Market::DMarket));
+    assert_eq!((dmarket[0].best_ask, dmarket[0].best_bid, dmarket[0].listing_count), (Some(usd(1000)), Some(usd(900)), 3));
+
+    let csfloat = storage.price_history(ITEM_NAMES[0], Some(Market::CSFloat), at(0), at(0)).unwrap();
+    assert_eq!(csfloat.len(), 1);
+    assert_eq!((csfloat[0].best_ask, csfloat[0].best_bid, csfloat[0].listing_count), (Some(usd(1250)), None, 2));
+
+    let overall = storage.price_history(ITEM_NAMES[0], None, at(0), at(300)).unwrap();
+    assert_eq!(overall.len(), 2);
+    assert_eq!(overall[0].market, None);
+    assert_eq!((overall[0].best_ask, overall[0].listing_count), (Some(usd(1000)), 5));
+
+    assert!(storage.price_history(ITEM_NAMES[0], None, at(1), at(299)).unwrap().is_empty());
+    assert_eq!(storage.price_history(ITEM_NAMES[1], None, at(0), at(300) + Duration::days(1)).unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn reopening_a_migrated_database_keeps_its_data() {
+    let server = MockServer::start().await;
+    let listings = server.dmarket_client().list_offers(ITEM_NAMES[0], 50).await.unwrap();
+    let path = std::env::temp_dir().join(format!("backend-storage-{}.db", std::process::id()));
+
+    let version = {
+        let storage = Storage::open(&path).unwrap();
+        storage.record_listings(at(0), &listings).unwrap();
+        storage.schema_version().unwrap()
+    };
+    assert!(version > 0);
+
+    let storage = Storage::open(&path).unwrap();
+    assert_eq!(storage.schema_version().unwrap(), version);
+    assert_eq!(storage.listing_history(ITEM_NAMES[0], Market::DMarket, at(0), at(0)).unwrap().len(), 3);
+    drop(storage);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
@@ -0,0 +1,203 @@
+mod mock;
+
+use mock::{Failure, MockServer};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[derive(Debug, Clone)]
+struct SpanData {
+    name: String,
+    parent: Option<usize>,
+    fields: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+struct EventData {
+    message: String,
+    span: Option<usize>,
+}
+
+#[derive(Default)]
+struct Recorded {
+    spans: Vec<SpanData>,
+    events: Vec<EventData>,
+    stack: Vec<usize>,
+}
+
+/// Records the spans and events of the tests that install it.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Recorded>>);
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut recorded = self.0.lock().unwrap();
+        let parent = match attributes.parent() {
+            Some(parent) => Some(parent.into_u64() as usize - 1),
+            None if attributes.is_contextual() => recorded.stack.last().copied(),
+            None => None,
+        };
+        let mut fields = HashMap::new();
+        attributes.record(&mut FieldVisitor(&mut fields));
+        recorded.spans.push(SpanData {
+            name: attributes.metadata().name().to_string(),
+            parent,
+            fields,
+        });
+        Id::from_u64(recorded.spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut recorded = self.0.lock().unwrap();
+        let span = &mut recorded.spans[span.into_u64() as usize - 1];
+        values.record(&mut FieldVisitor(&mut span.fields));
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut recorded = self.0.lock().unwrap();
+        let mut fields = HashMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let span = recorded.stack.last().copied();
+        recorded.events.push(EventData {
+            message: fields.remove("message").unwrap_or_default(),
+            span,
+        });
+    }
+
+    fn enter(&self, span: &Id) {
+        self.0.lock().unwrap().stack.push(span.into_u64() as usize - 1);
+    }
+
+    fn exit(&self, _: &Id) {
+        self.0.lock().unwrap().stack.pop();
+    }
+}
+
+impl Recorder {
+    fn spans(&self, name: &str) -> Vec<SpanData> {
+        let recorded = self.0.lock().unwrap();
+        recorded.spans.iter().filter(|span| span.name == name).cloned().collect()
+    }
+
+    fn span(&self, index: usize) -> SpanData {
+        self.0.lock().unwrap().spans[index].clone()
+    }
+
+    fn events(&self) -> Vec<EventData> {
+        self.0.lock().unwrap().events.clone()
+    }
+}
+
+fn field<'a>(span: &'a SpanData, name: &str) -> Option<&'a str> {
+    span.fields.get(name).map(String::as_str)
+}
+
+#[tokio::test]
+async fn records_market_endpoint_status_and_retries() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let server = MockServer::start().await;
+    let client = server.dmarket_client();
+
+    server.fail("/account/v1/balance", Failure::status(503), 1);
+    client.account().get_account_balance().await.unwrap();
+
+    let spans = recorder.spans("market_request");
+    assert_eq!(spans.len(), 1);
+    let span = &spans[0];
+    assert_eq!(field(span, "market"), Some("DMarket"));
+    assert_eq!(field(span, "endpoint"), Some("/account/v1/balance"));
+    assert_eq!(field(span, "method"), Some("GET"));
+    assert_eq!(field(span, "status"), Some("200"));
+    assert_eq!(field(span, "retries"), Some("1"));
+    assert!(field(span, "latency_ms").is_some());
+    assert_eq!(field(span, "error"), None);
+
+    let retry = recorder
+        .events()
+        .into_iter()
+        .find(|event| event.message.starts_with("Request failed (503"))
+        .expect("retry warning");
+    assert_eq!(recorder.span(retry.span.expect("retry logged in a span")).name, "market_request");
+}
+
+#[tokio::test]
+async fn records_final_error_status_without_retries() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let server = MockServer::start().await;
+
+    server.fail("/api/v1/listings", Failure::status(404), 1);
+    server.csfloat_client().listings().get_listings(None).await.unwrap_err();
+
+    let span = &recorder.spans("market_request")[0];
+    assert_eq!(field(span, "market"), Some("CSFloat"));
+    assert_eq!(field(span, "endpoint"), Some("/api/v1/listings"));
+    assert_eq!(field(span, "status"), Some("404"));
+    assert_eq!(field(span, "retries"), Some("0"));
+}
+
+#[tokio::test]
+async fn records_pagination_cursors() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let server = MockServer::start().await;
+
+    server
+        .dmarket_client()
+        .exchange()
+        .get_market_items(&backend::MarketItemsQuery::new("a8db").limit(10).cursor("page-2"))
+        .await
+        .unwrap();
+    server.buff_client().get_market_listings("csgo", 3, 20).await.unwrap();
+
+    let spans = recorder.spans("market_request");
+    assert_eq!(field(&spans[0], "cursor"), Some("page-2"));
+    assert_eq!(field(&spans[1], "market"), Some("Buff Market"));
+    assert_eq!(field(&spans[1], "cursor"), Some("3"));
+}
+
+#[tokio::test]
+async fn nests_crawl_requests_in_market_spans() {
+    use backend::{Collector, CollectorConfig, JobConfig, JobKind, LogSink, Marketplace};
+
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let server = MockServer::start().await;
+    let markets: Vec<Arc<dyn Marketplace>> = vec![Arc::new(server.dmarket_client())];
+    let job = JobConfig {
+        name: "crawl".to_string(),
+        interval_secs: 60,
+        markets: Vec::new(),
+        kind: JobKind::FullCrawl { max_items: Some(5) },
+    };
+    let collector = Collector::new(&CollectorConfig::default(), markets, Arc::new(LogSink));
+
+    collector.run_job(&job).await.unwrap();
+
+    let request = recorder.spans("market_request").remove(0);
+    let crawl = recorder.span(request.parent.expect("request inside crawl"));
+    assert_eq!(crawl.name, "crawl");
+    assert_eq!(field(&crawl, "market"), Some("DMarket"));
+}